@@ -0,0 +1,121 @@
+extern crate sdl_rust;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use sdl_rust::broadphase::SpatialHash;
+use sdl_rust::collision;
+use sdl_rust::collision::Aabb;
+
+// Compares finding colliding pairs by checking every pair against a
+// SpatialHash, for more and more moving boxes at the same crowdedness
+const COUNTS: [usize; 6] = [100, 500, 1000, 2000, 5000, 10000];
+const FRAMES: u32 = 20;
+
+const CELL: f32 = 32.0;
+// World area per box, so bigger counts get a bigger world
+const AREA_PER_BOX: f32 = 40.0 * 40.0;
+// Pixels per frame, at most
+const SPEED: f32 = 4.0;
+
+struct Mover {
+	aabb: Aabb,
+	vel: (f32, f32),
+}
+
+fn scatter(n: usize, rng: &mut StdRng) -> (Vec<Mover>, f32) {
+	let side = (n as f32 * AREA_PER_BOX).sqrt();
+
+	let movers = (0..n)
+		.map(|_| {
+			let w = rng.gen_range(8.0..24.0);
+			let h = rng.gen_range(8.0..24.0);
+			Mover {
+				aabb: Aabb::new(rng.gen_range(0.0..side - w), rng.gen_range(0.0..side - h), w, h),
+				vel: (rng.gen_range(-SPEED..SPEED), rng.gen_range(-SPEED..SPEED)),
+			}
+		})
+		.collect();
+
+	(movers, side)
+}
+
+// Bounce off the edges of the world
+fn step(movers: &mut [Mover], side: f32) {
+	for m in movers.iter_mut() {
+		m.aabb.x += m.vel.0;
+		m.aabb.y += m.vel.1;
+		if m.aabb.left() < 0.0 || m.aabb.right() > side {
+			m.vel.0 = -m.vel.0;
+		}
+		if m.aabb.top() < 0.0 || m.aabb.bottom() > side {
+			m.vel.1 = -m.vel.1;
+		}
+	}
+}
+
+fn all_pairs(movers: &[Mover]) -> usize {
+	let mut hits = 0;
+	for (i, a) in movers.iter().enumerate() {
+		for b in &movers[i + 1..] {
+			if collision::aabb_aabb(&a.aabb, &b.aabb).is_some() {
+				hits += 1;
+			}
+		}
+	}
+	hits
+}
+
+fn hashed_pairs(hash: &SpatialHash<usize>, movers: &[Mover]) -> usize {
+	hash.pairs()
+		.into_iter()
+		.filter(|&(a, b)| collision::aabb_aabb(&movers[a].aabb, &movers[b].aabb).is_some())
+		.count()
+}
+
+fn per_frame(total: Duration) -> f64 {
+	total.as_secs_f64() * 1000.0 / FRAMES as f64
+}
+
+fn main() {
+	println!("{:>7}  {:>14}  {:>14}  {:>8}", "boxes", "all pairs (ms)", "hashed (ms)", "hits");
+
+	for &n in COUNTS.iter() {
+		let mut rng = StdRng::seed_from_u64(n as u64);
+
+		// Every pair, every frame
+		let (mut movers, side) = scatter(n, &mut rng);
+		let mut naive = Duration::from_secs(0);
+		for _ in 0..FRAMES {
+			step(&mut movers, side);
+			let start = Instant::now();
+			black_box(all_pairs(&movers));
+			naive += start.elapsed();
+		}
+
+		// Same boxes and moves, through the hash
+		let mut rng = StdRng::seed_from_u64(n as u64);
+		let (mut movers, side) = scatter(n, &mut rng);
+		let mut hash = SpatialHash::new(CELL);
+		for (i, m) in movers.iter().enumerate() {
+			hash.insert(i, m.aabb);
+		}
+
+		let mut hashed = Duration::from_secs(0);
+		let mut hashed_hits = 0;
+		for _ in 0..FRAMES {
+			step(&mut movers, side);
+			let start = Instant::now();
+			for (i, m) in movers.iter().enumerate() {
+				hash.update(i, m.aabb);
+			}
+			hashed_hits = hashed_pairs(&hash, &movers);
+			hashed += start.elapsed();
+		}
+
+		println!("{:>7}  {:>14.3}  {:>14.3}  {:>8}", n, per_frame(naive), per_frame(hashed), hashed_hits);
+	}
+}
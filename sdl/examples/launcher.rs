@@ -0,0 +1,242 @@
+extern crate sdl_rust;
+
+use std::env;
+use std::process;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use sdl_rust::SDLCore;
+use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::text;
+use sdl_rust::text::{Text, TextStyle};
+
+// Pull in every example as a module so we can get at its Demo. Each still has
+// its own main, which we don't use, and its own warnings, which we don't want.
+#[path = "sdl01_hello.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl01_hello;
+#[path = "sdl02_basic.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl02_basic;
+#[path = "sdl03_draw.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl03_draw;
+#[path = "sdl04_events.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl04_events;
+#[path = "sdl05_key_events.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl05_key_events;
+#[path = "sdl06_motion.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl06_motion;
+#[path = "sdl07_keystates.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl07_keystates;
+#[path = "sdl08_rect_collision.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl08_rect_collision;
+#[path = "sdl09_circ_collision.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl09_circ_collision;
+#[path = "sdl10_tiling.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl10_tiling;
+#[path = "sdl11_overengineered_tiling.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl11_overengineered_tiling;
+#[path = "sdl12_simple_bg_scroll.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl12_simple_bg_scroll;
+#[path = "sdl13_side_scroll.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl13_side_scroll;
+#[path = "sdl14_top_down_scroll.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl14_top_down_scroll;
+#[path = "sdl15_anim.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl15_anim;
+#[path = "sdl16_time.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl16_time;
+#[path = "sdl17_fps.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl17_fps;
+#[path = "sdl18_fixed_timestep.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl18_fixed_timestep;
+#[path = "sdl19_scenes.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl19_scenes;
+#[path = "sdl20_tilemap.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl20_tilemap;
+#[path = "sdl21_audio.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl21_audio;
+
+const TITLE: &str = "SDL Examples Launcher";
+const CAM_W: u32 = 640;
+const CAM_H: u32 = 480;
+
+const ROW_H: u32 = 20;
+const ROW_GAP: u32 = 4;
+const MARGIN: u32 = 10;
+const FONT_SIZE: u16 = 14;
+// How far in from the left of its row each name is drawn
+const TEXT_INSET: i32 = 8;
+// As many rows as fit, past that the list scrolls
+const VISIBLE_ROWS: usize = ((CAM_H - 2 * MARGIN + ROW_GAP) / (ROW_H + ROW_GAP)) as usize;
+
+struct Entry {
+	name: &'static str,
+	run: fn(),
+}
+
+const DEMOS: &[Entry] = &[
+	Entry { name: "sdl01_hello", run: || sdl_rust::runner("sdl01_hello", sdl01_hello::SDL01::init) },
+	Entry { name: "sdl02_basic", run: || sdl_rust::runner("sdl02_basic", sdl02_basic::SDL02::init) },
+	Entry { name: "sdl03_draw", run: || sdl_rust::runner("sdl03_draw", sdl03_draw::SDL03::init) },
+	Entry { name: "sdl04_events", run: || sdl_rust::runner("sdl04_events", sdl04_events::SDL04::init) },
+	Entry { name: "sdl05_key_events", run: || sdl_rust::runner("sdl05_key_events", sdl05_key_events::SDL05::init) },
+	Entry { name: "sdl06_motion", run: || sdl_rust::runner("sdl06_motion", sdl06_motion::SDL06::init) },
+	Entry { name: "sdl07_keystates", run: || sdl_rust::runner("sdl07_keystates", sdl07_keystates::SDL07::init) },
+	Entry { name: "sdl08_rect_collision", run: || sdl_rust::runner("sdl08_rect_collision", sdl08_rect_collision::SDL08::init) },
+	Entry { name: "sdl09_circ_collision", run: || sdl_rust::runner("sdl09_circ_collision", sdl09_circ_collision::SDL09::init) },
+	Entry { name: "sdl10_tiling", run: || sdl_rust::runner("sdl10_tiling", sdl10_tiling::SDL10::init) },
+	Entry { name: "sdl11_overengineered_tiling", run: || sdl_rust::runner("sdl11_overengineered_tiling", sdl11_overengineered_tiling::SDL11::init) },
+	Entry { name: "sdl12_simple_bg_scroll", run: || sdl_rust::runner("sdl12_simple_bg_scroll", sdl12_simple_bg_scroll::SDL12::init) },
+	Entry { name: "sdl13_side_scroll", run: || sdl_rust::runner("sdl13_side_scroll", sdl13_side_scroll::SDL13::init) },
+	Entry { name: "sdl14_top_down_scroll", run: || sdl_rust::runner("sdl14_top_down_scroll", sdl14_top_down_scroll::SDL14::init) },
+	Entry { name: "sdl15_anim", run: || sdl_rust::runner("sdl15_anim", sdl15_anim::SDL15::init) },
+	Entry { name: "sdl16_time", run: || sdl_rust::runner("sdl16_time", sdl16_time::SDL16::init) },
+	Entry { name: "sdl17_fps", run: || sdl_rust::runner("sdl17_fps", sdl17_fps::SDL17::init) },
+	Entry { name: "sdl18_fixed_timestep", run: || sdl_rust::runner("sdl18_fixed_timestep", sdl18_fixed_timestep::SDL18::init) },
+	Entry { name: "sdl19_scenes", run: || sdl_rust::runner("sdl19_scenes", sdl19_scenes::SDL19::init) },
+	Entry { name: "sdl20_tilemap", run: || sdl_rust::runner("sdl20_tilemap", sdl20_tilemap::SDL20::init) },
+	Entry { name: "sdl21_audio", run: || sdl_rust::runner("sdl21_audio", sdl21_audio::SDL21::init) },
+];
+
+fn find(name: &str) -> Option<&'static Entry> {
+	DEMOS.iter().find(|e| e.name == name || e.name.starts_with(&format!("{}_", name)))
+}
+
+fn list() {
+	for (i, e) in DEMOS.iter().enumerate() {
+		println!("{:2}  {}", i + 1, e.name);
+	}
+}
+
+// Shows the menu until something is picked (Some) or the user quits (None).
+// Each demo makes its own SDLCore, and SDL only allows one event pump at a
+// time, so the menu's core is dropped before the demo runs.
+fn menu(selected: usize) -> Result<Option<usize>, Error> {
+	let mut core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
+	let texture_creator = core.wincan.texture_creator();
+	let mut text = Text::new(&texture_creator)?;
+	let font = text.load_font(text::DEFAULT_FONT, FONT_SIZE)?;
+	let label = TextStyle::new(Color::BLACK);
+	let line_h = text.line_height(font)? as i32;
+
+	let mut selected = selected;
+	// First row on screen
+	let mut top = 0;
+
+	let picked = 'menu: loop {
+		for event in core.poll_events() {
+			match event {
+				Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'menu None,
+				Event::KeyDown{keycode: Some(Keycode::Up), ..} => {
+					selected = (selected + DEMOS.len() - 1) % DEMOS.len();
+				},
+				Event::KeyDown{keycode: Some(Keycode::Down), ..} => {
+					selected = (selected + 1) % DEMOS.len();
+				},
+				Event::KeyDown{keycode: Some(Keycode::Return), ..} => break 'menu Some(selected),
+				_ => {},
+			}
+		}
+
+		// Keep the selection on screen
+		if selected < top {
+			top = selected;
+		}
+		else if selected >= top + VISIBLE_ROWS {
+			top = selected + 1 - VISIBLE_ROWS;
+		}
+
+		core.wincan.set_draw_color(Color::RGBA(0, 128, 128, 255));
+		core.wincan.clear();
+
+		for (row, i) in (top..DEMOS.len().min(top + VISIBLE_ROWS)).enumerate() {
+			let color = if i == selected {
+				Color::CYAN
+			}
+			else {
+				Color::GRAY
+			};
+
+			let y = (MARGIN + (row as u32) * (ROW_H + ROW_GAP)) as i32;
+			core.wincan.set_draw_color(color);
+			core.wincan.fill_rect(Rect::new(MARGIN as i32, y, CAM_W - 2 * MARGIN, ROW_H))?;
+
+			let name = format!("{:2}  {}", i + 1, DEMOS[i].name);
+			let text_y = y + (ROW_H as i32 - line_h) / 2;
+			text.draw(&mut core.wincan, font, &name, MARGIN as i32 + TEXT_INSET, text_y, &label)?;
+		}
+
+		core.present()?;
+	};
+
+	// If recording, find out whether it saved
+	core.finish_recording()?;
+	Ok(picked)
+}
+
+fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+
+	match args.iter().map(|a| a.as_str()).collect::<Vec<_>>()[..] {
+		[] => {},
+		["--list"] => {
+			list();
+			return;
+		},
+		["--run", name] => {
+			match find(name) {
+				Some(e) => (e.run)(),
+				None => {
+					eprintln!("No demo named {}, try --list", name);
+					process::exit(1);
+				},
+			}
+			return;
+		},
+		_ => {
+			eprintln!("Usage: launcher [--list | --run <name>]");
+			process::exit(1);
+		},
+	}
+
+	list();
+
+	let mut selected = 0;
+	loop {
+		match menu(selected) {
+			Ok(Some(i)) => {
+				(DEMOS[i].run)();
+				selected = i;
+			},
+			Ok(None) => break,
+			Err(e) => {
+				eprintln!("Launcher failed: {}", e);
+				process::exit(1);
+			},
+		}
+	}
+}
@@ -4,10 +4,10 @@ use std::time::Duration;
 use std::thread;
 
 use sdl2::pixels::Color;
-use sdl2::image::LoadTexture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL01 Hello World";
 const CAM_W: u32 = 640;
@@ -19,33 +19,34 @@ pub struct SDL01 {
 }
 
 impl Demo for SDL01 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL01{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
 
-		let ms = texture_creator.load_texture("images/hello_world_win.png")?;
-		let tux = texture_creator.load_texture("images/tuxdoge.png")?;
+		let ms = sdl_rust::load_texture(&texture_creator, "images/hello_world_win.png")?;
+		let tux = sdl_rust::load_texture(&texture_creator, "images/tuxdoge.png")?;
 
 		self.core.wincan.set_draw_color(Color::RGBA(0, 128, 128, 255));
 		self.core.wincan.clear();
 
 		self.core.wincan.copy(&ms, None, None)?;
-		self.core.wincan.present();
+		self.core.present()?;
 
 		// Note SDL has a timer subsystem, but the Rust SDL bindings recommend
 		// the use of std::thread::sleep and std::time instead
 		thread::sleep(Duration::from_millis(TIMEOUT));
 
 		self.core.wincan.copy(&tux, None, None)?;
-		self.core.wincan.present();
+		self.core.present()?;
 
 		thread::sleep(Duration::from_millis(TIMEOUT));
 
-		Ok(())
+		// If recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -7,6 +7,7 @@ use sdl2::pixels::Color;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL02 Basic window";
 const CAM_W: u32 = 1920;
@@ -18,18 +19,19 @@ pub struct SDL02 {
 }
 
 impl Demo for SDL02 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL02{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		self.core.wincan.set_draw_color(Color::RGBA(0, 128, 128, 255));
 		self.core.wincan.clear();
-		self.core.wincan.present();
+		self.core.present()?;
 		thread::sleep(Duration::from_millis(TIMEOUT));
 
-		Ok(())
+		// If recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
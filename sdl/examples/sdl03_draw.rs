@@ -10,6 +10,7 @@ use sdl2::render::BlendMode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL03 Drawing";
 const CAM_W: u32 = 640;
@@ -21,12 +22,12 @@ pub struct SDL03 {
 }
 
 impl Demo for SDL03 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL03{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let g = Color::RGBA(0, 255, 0, 255);
 		let b = Color::RGBA(0, 0, 255, 255);
 
@@ -82,10 +83,11 @@ impl Demo for SDL03 {
 		self.core.wincan.set_draw_color(Color::RGBA(0, 255, 0, 128));
 		self.core.wincan.fill_rects(&rs[..])?;
 
-		self.core.wincan.present();
+		self.core.present()?;
 		thread::sleep(Duration::from_millis(TIMEOUT));
 
-		Ok(())
+		// If recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
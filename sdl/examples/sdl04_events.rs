@@ -8,6 +8,7 @@ use sdl2::event::Event;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL04 Event Handling";
 const CAM_W: u32 = 640;
@@ -19,14 +20,14 @@ pub struct SDL04 {
 }
 
 impl Demo for SDL04 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL04{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} => break 'gameloop,
 					_ => {},
@@ -37,14 +38,14 @@ impl Demo for SDL04 {
 			self.draw_demo()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
 impl SDL04 {
 	// Code from SDL03 repeated...
-	fn draw_demo(&mut self) -> Result<(), String> {
+	fn draw_demo(&mut self) -> Result<(), Error> {
 		let g = Color::RGBA(0, 255, 0, 255);
 		let b = Color::RGBA(0, 0, 255, 255);
 
@@ -100,7 +101,7 @@ impl SDL04 {
 		self.core.wincan.set_draw_color(Color::RGBA(0, 255, 0, 128));
 		self.core.wincan.fill_rects(&rs[..])?;
 
-		self.core.wincan.present();
+		self.core.present()?;
 		// Got rid of timeout!
 
 		Ok(())
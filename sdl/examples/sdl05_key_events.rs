@@ -7,6 +7,7 @@ use sdl2::keyboard::Keycode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL05 Key Events";
 const CAM_W: u32 = 640;
@@ -17,12 +18,12 @@ pub struct SDL05 {
 }
 
 impl Demo for SDL05 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL05{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let bg_color = Color::RGBA(0, 128, 128, 255);
 		let mut draw_color = Color::CYAN;
 		
@@ -30,7 +31,7 @@ impl Demo for SDL05 {
 		let r = Rect::new((CAM_W/2 - w/2) as i32, (CAM_H /2 - w/2) as i32, w, w);
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					Event::KeyDown{keycode: Some(k), ..} => {
@@ -52,11 +53,11 @@ impl Demo for SDL05 {
 			self.core.wincan.set_draw_color(draw_color);
 			self.core.wincan.fill_rect(r)?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -7,6 +7,7 @@ use sdl2::keyboard::Keycode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL06 Key Events";
 const CAM_W: u32 = 640;
@@ -17,12 +18,12 @@ pub struct SDL06 {
 }
 
 impl Demo for SDL06 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL06{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let w = 25;
 		let mut x_pos = (CAM_W/2 - w/2) as i32;
 		let mut y_pos = (CAM_H/2 - w/2) as i32;
@@ -31,7 +32,7 @@ impl Demo for SDL06 {
 		let mut y_vel = 0;
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					Event::KeyDown{keycode: Some(k), ..} => {
@@ -56,11 +57,11 @@ impl Demo for SDL06 {
 			self.core.wincan.set_draw_color(Color::CYAN);
 			self.core.wincan.fill_rect(Rect::new(x_pos, y_pos, w, w))?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -1,7 +1,5 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
-
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::event::Event;
@@ -9,100 +7,77 @@ use sdl2::keyboard::Keycode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::kinematics::Body;
 
 const TITLE: &str = "SDL07 Key Events";
 const CAM_W: u32 = 640;
 const CAM_H: u32 = 480;
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
-
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
-	}
-}
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
 
 pub struct SDL07 {
 	core: SDLCore,
 }
 
 impl Demo for SDL07 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL07{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let w = 25;
-		let mut x_pos = (CAM_W/2 - w/2) as i32;
-		let mut y_pos = (CAM_H/2 - w/2) as i32;
-
-		let mut x_vel = 0;
-		let mut y_vel = 0;
+		let x_pos = (CAM_W/2 - w/2) as f32;
+		let y_pos = (CAM_H/2 - w/2) as f32;
+		// Speeds up while a key's held, and slows to a stop when let go
+		let mut player = Body::new(x_pos, y_pos, w, w)
+			.max_speed(SPEED_LIMIT)
+			.linear_drag(FRICTION);
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			let keystate = self.core.pressed_keys();
 
-			let mut x_deltav = 0;
-			let mut y_deltav = 0;
+			let mut accel = (0.0, 0.0);
 			if keystate.contains(&Keycode::W) {
-				y_deltav -= ACCEL_RATE;
+				accel.1 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				accel.0 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::S) {
-				y_deltav += ACCEL_RATE;
+				accel.1 += ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				accel.0 += ACCEL;
 			}
-
-			// Slow down to 0 vel if no input and non-zero velocity
-			x_deltav = resist(x_vel, x_deltav);
-			y_deltav = resist(y_vel, y_deltav);
-
-			// Don't exceed speed limit
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-			y_vel = (y_vel + y_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
+			player.set_accel(accel.0, accel.1);
+			player.update(self.core.delta());
 
 			// Stay inside the viewing window
-			x_pos = (x_pos + x_vel).clamp(0, (CAM_W - w) as i32);
-			y_pos = (y_pos + y_vel).clamp(0, (CAM_H - w) as i32);
+			player.clamp_to(Rect::new(0, 0, CAM_W, CAM_H));
 
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 
 			self.core.wincan.set_draw_color(Color::CYAN);
-			self.core.wincan.fill_rect(Rect::new(x_pos, y_pos, w, w))?;
+			self.core.wincan.fill_rect(player.rect())?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -1,7 +1,5 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
-
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::event::Event;
@@ -9,135 +7,96 @@ use sdl2::keyboard::Keycode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::kinematics::Body;
+use sdl_rust::collision::Aabb;
 
 const TITLE: &str = "SDL08 Rect Collisions";
 const CAM_W: u32 = 640;
 const CAM_H: u32 = 480;
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
-
-fn check_collision(a: &Rect, b: &Rect) -> bool {
-	if a.bottom() < b.top()
-		|| a.top() > b.bottom()
-		|| a.right() < b.left()
-		|| a.left() > b.right()
-	{
-		false
-	}
-	else {
-		true
-	}
-}
-
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
-	}
-}
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
 
 pub struct SDL08 {
 	core: SDLCore,
 }
 
 impl Demo for SDL08 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL08{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let w = 25;
 
 		let static_box = Rect::new((CAM_W/2 + 2*w) as i32, (CAM_H/2 - w/2) as i32, w, w);
 
-		let x_pos = (CAM_W/2 - w/2) as i32;
-		let y_pos = (CAM_H/2 - w/2) as i32;		
-		let mut player_box = Rect::new(x_pos, y_pos, w, w);
-
-		let mut x_vel = 0;
-		let mut y_vel = 0;
+		let x_pos = (CAM_W/2 - w/2) as f32;
+		let y_pos = (CAM_H/2 - w/2) as f32;
+		let mut player = Body::new(x_pos, y_pos, w, w)
+			.max_speed(SPEED_LIMIT)
+			.linear_drag(FRICTION);
+
+		// The box, then walls just off each side of the screen
+		let (cam_w, cam_h) = (CAM_W as f32, CAM_H as f32);
+		let colliders = [
+			Aabb::from(static_box),
+			Aabb::new(-cam_w, 0.0, cam_w, cam_h),
+			Aabb::new(cam_w, 0.0, cam_w, cam_h),
+			Aabb::new(0.0, -cam_h, cam_w, cam_h),
+			Aabb::new(0.0, cam_h, cam_w, cam_h),
+		];
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			let keystate = self.core.pressed_keys();
 
-			let mut x_deltav = 0;
-			let mut y_deltav = 0;
+			let mut accel = (0.0, 0.0);
 			if keystate.contains(&Keycode::W) {
-				y_deltav -= ACCEL_RATE;
+				accel.1 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				accel.0 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::S) {
-				y_deltav += ACCEL_RATE;
+				accel.1 += ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				accel.0 += ACCEL;
 			}
 
-			// Slow down to 0 vel if no input and non-zero velocity
-			x_deltav = resist(x_vel, x_deltav);
-			y_deltav = resist(y_vel, y_deltav);
-
-			// Don't exceed speed limit
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-			y_vel = (y_vel + y_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-
-			// Try to move horizontally
-			player_box.set_x(player_box.x() + x_vel);
-			// Use the "go-back" approach to collision resolution
-			if check_collision(&player_box, &static_box)
-				|| player_box.left() < 0
-				|| player_box.right() > CAM_W as i32
-			{
-				player_box.set_x(player_box.x() - x_vel);
-			}
+			player.set_accel(accel.0, accel.1);
 
-			// Try to move vertically
-			player_box.set_y(player_box.y() + y_vel);
-			if check_collision(&player_box, &static_box)
-				|| player_box.top() < 0
-				|| player_box.bottom() > CAM_H as i32
-			{
-				player_box.set_y(player_box.y() - y_vel);
-			}	
+			// Sweep the whole move against everything in the way, and slide
+			// along whatever stops us. Unlike going back to where we were,
+			// this ends up flush against walls, however fast we're going.
+			let moved = player.move_and_slide(self.core.delta(), &colliders);
+			let touching_box = moved.hit_x == Some(0) || moved.hit_y == Some(0);
 
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 
-			self.core.wincan.set_draw_color(Color::RED);
+			self.core.wincan.set_draw_color(if touching_box { Color::YELLOW } else { Color::RED });
 			self.core.wincan.fill_rect(static_box)?;
 
 			self.core.wincan.set_draw_color(Color::CYAN);
-			self.core.wincan.fill_rect(player_box)?;
+			self.core.wincan.fill_rect(player.rect())?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -1,20 +1,25 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
-
 use sdl2::pixels::Color;
-use sdl2::rect::Point;
+use sdl2::rect::{Point, Rect};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::collision;
+use sdl_rust::collision::Circle;
+use sdl_rust::kinematics::Body;
 
 const TITLE: &str = "SDL09 Circular Collisions";
 const CAM_W: u32 = 640;
 const CAM_H: u32 = 480;
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
 
 struct NOTSDL_Circ {
 	x: i32,
@@ -31,44 +36,12 @@ impl NOTSDL_Circ {
 		}
 	}
 
-	fn x(&self) -> i32 {
-		self.x
-	}
-
-	fn y(&self) -> i32 {
-		self.y
-	}
-
-	fn r(&self) -> u32 {
-		self.r
-	}
-
-	fn set_x(&mut self, x: i32) {
-		self.x = x;
-	}
-
-	fn set_y(&mut self, y: i32) {
-		self.y = y;
-	}
-
-	fn left(&self) -> i32 {
-		self.x - (self.r as i32)
-	}
-
-	fn right(&self) -> i32 {
-		self.x + (self.r as i32)
-	}
-
-	fn top(&self) -> i32 {
-		self.y - (self.r as i32)
-	}
-
-	fn bottom(&self) -> i32 {
-		self.y + (self.r as i32)
+	fn circle(&self) -> Circle {
+		Circle::new(self.x as f32, self.y as f32, self.r as f32)
 	}
 
 	fn get_points(&self) -> Vec<Point> {
-		let rad = self.r() as i32;
+		let rad = self.r as i32;
 
 		// I really <3 Rust iterators...
 		(-rad..rad)
@@ -87,104 +60,63 @@ impl NOTSDL_Circ {
 	}
 }
 
-fn check_collision(a: &NOTSDL_Circ, b: &NOTSDL_Circ) -> bool {
-	let radsum = (a.r() + b.r()) as i32;
-	let distsq = (a.x() - b.x()).pow(2) + (a.y() - b.y()).pow(2);
-
-	distsq < radsum.pow(2)
-}
-
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
-	}
-}
-
 pub struct SDL09 {
 	core: SDLCore,
 }
 
 impl Demo for SDL09 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL09{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let r = 12;
 
 		let static_dot = NOTSDL_Circ::new((CAM_W/2 + 2*r) as i32, (CAM_H/2) as i32, r);
-		let mut player_dot = NOTSDL_Circ::new((CAM_W/2) as i32, (CAM_H/2) as i32, r);
-
-		let mut x_vel = 0;
-		let mut y_vel = 0;
+		// The player moves as the box around its circle
+		let mut player = Body::new((CAM_W/2 - r) as f32, (CAM_H/2 - r) as f32, 2*r, 2*r)
+			.max_speed(SPEED_LIMIT)
+			.linear_drag(FRICTION);
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			let keystate = self.core.pressed_keys();
 
-			let mut x_deltav = 0;
-			let mut y_deltav = 0;
+			let mut accel = (0.0, 0.0);
 			if keystate.contains(&Keycode::W) {
-				y_deltav -= ACCEL_RATE;
+				accel.1 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				accel.0 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::S) {
-				y_deltav += ACCEL_RATE;
+				accel.1 += ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				accel.0 += ACCEL;
 			}
 
-			// Slow down to 0 vel if no input and non-zero velocity
-			x_deltav = resist(x_vel, x_deltav);
-			y_deltav = resist(y_vel, y_deltav);
-
-			// Don't exceed speed limit
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-			y_vel = (y_vel + y_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-
-			// Try to move horizontally
-			player_dot.set_x(player_dot.x() + x_vel);
-			// Use the "go-back" approach to collision resolution
-			if check_collision(&player_dot, &static_dot)
-				|| player_dot.left() < 0
-				|| player_dot.right() > CAM_W as i32
-			{
-				player_dot.set_x(player_dot.x() - x_vel);
+			player.set_accel(accel.0, accel.1);
+			player.update(self.core.delta());
+
+			// Instead of going back, push the player out of the other dot by
+			// just enough, so it slides around the edge
+			let (x, y) = player.center();
+			let player_circle = Circle::new(x, y, r as f32);
+			if let Some(contact) = collision::circle_circle(&player_circle, &static_dot.circle()) {
+				player.resolve(&contact);
 			}
+			player.clamp_to(Rect::new(0, 0, CAM_W, CAM_H));
 
-			// Try to move vertically
-			player_dot.set_y(player_dot.y() + y_vel);
-			if check_collision(&player_dot, &static_dot)
-				|| player_dot.top() < 0
-				|| player_dot.bottom() > CAM_H as i32
-			{
-				player_dot.set_y(player_dot.y() - y_vel);
-			}	
+			let (x, y) = player.center();
+			let player_dot = NOTSDL_Circ::new(x.round() as i32, y.round() as i32, r);
 
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
@@ -195,11 +127,11 @@ impl Demo for SDL09 {
 			self.core.wincan.set_draw_color(Color::CYAN);
 			self.core.wincan.draw_points(&(player_dot.get_points())[..])?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -8,11 +8,11 @@ use sdl2::pixels::Color;
 use sdl2::event::Event;
 use sdl2::rect::Rect;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
 use sdl2::render::Texture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
 
 const TITLE: &str = "SDL10 Tiling";
 
@@ -26,16 +26,16 @@ pub struct SDL10 {
 }
 
 impl Demo for SDL10 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL10{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
 
-		let bird_sheet = texture_creator.load_texture("images/birds.png")?;
-		let brick_sheet = texture_creator.load_texture("images/bricks.png")?;
+		let bird_sheet = sdl_rust::load_texture(&texture_creator, "images/birds.png")?;
+		let brick_sheet = sdl_rust::load_texture(&texture_creator, "images/bricks.png")?;
 
 		let mut rng = thread_rng();
 		let bird_locations: Vec<_> = (0..8)
@@ -50,7 +50,7 @@ impl Demo for SDL10 {
 			.collect();
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
@@ -82,11 +82,11 @@ impl Demo for SDL10 {
 				self.core.wincan.copy(&bird_sheet, src, b.clone())?;
 			}
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
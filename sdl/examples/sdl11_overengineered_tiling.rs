@@ -1,18 +1,18 @@
 extern crate sdl_rust;
 extern crate rand;
 
-use rand::thread_rng;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use sdl2::pixels::Color;
 use sdl2::event::Event;
 use sdl2::rect::Rect;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
-use sdl2::render::Texture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::assets::{AssetManager, TextureHandle};
 
 const TITLE: &str = "SDL11 Tiling";
 
@@ -21,14 +21,18 @@ const CAM_H: u32 = 480;
 
 const TILE_SIZE: u32 = 100;
 
-struct Bird<'a> {
+// Scatters the birds the same way every run, so golden tests can check them
+const BIRD_SEED: u64 = 11;
+
+// Handles, unlike &Texture, don't need a lifetime
+struct Bird {
 	num: i32,
 	pos: Rect,
-	texture: &'a Texture<'a>,
+	texture: TextureHandle,
 }
 
-impl<'a> Bird<'a> {
-	fn new(num: i32, pos: Rect, texture: &'a Texture) -> Bird<'a> {
+impl Bird {
+	fn new(num: i32, pos: Rect, texture: TextureHandle) -> Bird {
 		Bird {
 			num,
 			pos,
@@ -37,14 +41,14 @@ impl<'a> Bird<'a> {
 	}
 }
 
-struct Brick<'a> {
+struct Brick {
 	num: i32,
 	pos: Rect,
-	texture: &'a Texture<'a>,
+	texture: TextureHandle,
 }
 
-impl<'a> Brick<'a> {
-	fn new(num: i32, pos: Rect, texture: &'a Texture) -> Brick<'a> {
+impl Brick {
+	fn new(num: i32, pos: Rect, texture: TextureHandle) -> Brick {
 		Brick {
 			num,
 			pos,
@@ -56,10 +60,10 @@ impl<'a> Brick<'a> {
 trait Drawable {
 	fn src(&self) -> Rect;
 	fn pos(&self) -> Rect;
-	fn texture(&self) -> &Texture;
+	fn texture(&self) -> TextureHandle;
 }
 
-impl<'a> Drawable for Bird<'a> {
+impl Drawable for Bird {
 	fn src(&self) -> Rect {
 		let x = (self.num % 2) * (TILE_SIZE as i32);
 		let y = ((self.num % 4) / 2) * (TILE_SIZE as i32);
@@ -70,12 +74,12 @@ impl<'a> Drawable for Bird<'a> {
 		self.pos
 	}
 
-	fn texture(&self) -> &'a Texture {
+	fn texture(&self) -> TextureHandle {
 		self.texture
 	}
 }
 
-impl<'a> Drawable for Brick<'a> {
+impl Drawable for Brick {
 	fn src(&self) -> Rect {
 		let x = (self.num % 4) * (TILE_SIZE as i32);
 		Rect::new(x, 0, TILE_SIZE, TILE_SIZE)
@@ -85,7 +89,7 @@ impl<'a> Drawable for Brick<'a> {
 		self.pos
 	}
 
-	fn texture(&self) -> &'a Texture {
+	fn texture(&self) -> TextureHandle {
 		self.texture
 	}
 }
@@ -95,18 +99,20 @@ pub struct SDL11 {
 }
 
 impl Demo for SDL11 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL11{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
 
-		let bird_sheet = texture_creator.load_texture("images/birds.png")?;
-		let brick_sheet = texture_creator.load_texture("images/bricks.png")?;
+		let mut assets = AssetManager::new(&texture_creator);
+
+		let bird_sheet = assets.load_texture("images/birds.png")?;
+		let brick_sheet = assets.load_texture("images/bricks.png")?;
 
-		let mut rng = thread_rng();
+		let mut rng = StdRng::seed_from_u64(BIRD_SEED);
 		let birds: Vec<_> = (0..8)
 			.map(|i| {
 				Bird::new(
@@ -117,7 +123,7 @@ impl Demo for SDL11 {
 						TILE_SIZE,
 						TILE_SIZE,
 					),
-					&bird_sheet,
+					bird_sheet,
 				)
 			})
 			.collect();
@@ -132,13 +138,13 @@ impl Demo for SDL11 {
 						TILE_SIZE,
 						TILE_SIZE,
 					),
-					&brick_sheet,
+					brick_sheet,
 				)
 			})
 			.collect();
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
@@ -157,14 +163,14 @@ impl Demo for SDL11 {
 			self.core.wincan.clear();
 
 			for d in drawable_iter {
-				self.core.wincan.copy(d.texture(), d.src(), d.pos())?;
+				assets.draw(&mut self.core.wincan, d.texture(), d.src(), d.pos())?;
 			}
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -1,16 +1,17 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
-
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
 use sdl2::render::Texture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::kinematics::Body;
+use sdl_rust::assets::AssetManager;
+use sdl_rust::parallax::{Parallax, Layer};
 
 const TITLE: &str = "SDL12 Simple BG scrolling";
 
@@ -19,8 +20,14 @@ const CAM_H: u32 = 480;
 
 const TILE_SIZE: u32 = 100;
 
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
+
+// Pixels per second the background scrolls by on its own
+const BG_SPEED: f32 = 60.0;
 
 enum PlayerType {
 	Bird,
@@ -30,13 +37,12 @@ enum PlayerType {
 }
 
 struct Player<'a> {
-	pos: Rect,
 	src: Rect,
 	texture: Texture<'a>,
 }
 
 impl<'a> Player<'a> {
-	fn new(t: PlayerType, pos: Rect, texture: Texture<'a>) -> Player {
+	fn new(t: PlayerType, texture: Texture<'a>) -> Player {
 		let (x, y) = match t {
 			PlayerType::Bird => (0, 0),
 			PlayerType::Plane => (TILE_SIZE, 0),
@@ -46,21 +52,11 @@ impl<'a> Player<'a> {
 
 		let src = Rect::new(x as i32, y as i32, TILE_SIZE, TILE_SIZE);
 		Player {
-			pos,
 			src,
 			texture,
 		}
 	}
 
-	fn pos(&self) -> Rect {
-		self.pos
-	}
-
-	fn update_pos(&mut self, vel: (i32, i32), x_bounds: (i32, i32), y_bounds: (i32, i32)) {
-		self.pos.set_x((self.pos.x() + vel.0).clamp(x_bounds.0, x_bounds.1));
-		self.pos.set_y((self.pos.y() + vel.1).clamp(y_bounds.0, y_bounds.1));
-	}
-
 	fn src(&self) -> Rect {
 		self.src
 	}
@@ -70,113 +66,91 @@ impl<'a> Player<'a> {
 	}
 }
 
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
-	}
-}
-
 pub struct SDL12 {
 	core: SDLCore,
 }
 
 impl Demo for SDL12 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL12{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
 
-		// BG is the same size and window, but will scroll to simulate
-		// consistent horizontal movement
-		let bg = texture_creator.load_texture("images/small_bg.png")?;
+		let mut assets = AssetManager::new(&texture_creator);
 
-		// Will indicate the offset to use in rendering bg image
-		let mut bg_offset = 0;
+		// BG is the same size and window, but drifts left to simulate
+		// consistent horizontal movement. The camera never moves here, so
+		// the drift is all the scrolling there is.
+		let mut bg = Parallax::new();
+		bg.push(
+			Layer::new(assets.load_texture("images/small_bg.png")?)
+				.size(CAM_W, CAM_H)
+				.drift(-BG_SPEED, 0.0)
+		);
 
-		let mut p = Player::new(
+		let p = Player::new(
 			PlayerType::Plane,
-			Rect::new(
-				(CAM_W/2 - TILE_SIZE/2) as i32,
-				(CAM_H/2 - TILE_SIZE/2) as i32,
-				TILE_SIZE,
-				TILE_SIZE,
-			),
-			texture_creator.load_texture("images/birds.png")?,
+			sdl_rust::load_texture(&texture_creator, "images/birds.png")?,
 		);
-
-		let mut x_vel = 0;
-		let mut y_vel = 0;
+		let mut player = Body::new(
+			(CAM_W/2 - TILE_SIZE/2) as f32,
+			(CAM_H/2 - TILE_SIZE/2) as f32,
+			TILE_SIZE,
+			TILE_SIZE,
+		)
+			.max_speed(SPEED_LIMIT)
+			.linear_drag(FRICTION);
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			let keystate = self.core.pressed_keys();
 
-			let mut x_deltav = 0;
-			let mut y_deltav = 0;
+			let mut accel = (0.0, 0.0);
 			if keystate.contains(&Keycode::W) {
-				y_deltav -= ACCEL_RATE;
+				accel.1 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				accel.0 -= ACCEL;
 			}
 			if keystate.contains(&Keycode::S) {
-				y_deltav += ACCEL_RATE;
+				accel.1 += ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				accel.0 += ACCEL;
 			}
-			x_deltav = resist(x_vel, x_deltav);
-			y_deltav = resist(y_vel, y_deltav);
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
-			y_vel = (y_vel + y_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
+			player.set_accel(accel.0, accel.1);
+
+			let dt = self.core.delta();
 
 			// Back to moving the player only within the camera window
-			p.update_pos((x_vel, y_vel), (0, (CAM_W - TILE_SIZE) as i32), (0, (CAM_H - TILE_SIZE) as i32));
+			player.update(dt);
+			player.clamp_to(Rect::new(0, 0, CAM_W, CAM_H));
 
-			bg_offset -= 1;
-			if bg_offset < -(CAM_W as i32) {
-				bg_offset = -1;
-			}
+			bg.update(dt, &assets);
 
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 
 			// Draw background
-			self.core.wincan.copy(&bg, None, Rect::new(bg_offset, 0, CAM_W, CAM_H))?;
-			self.core.wincan.copy(&bg, None, Rect::new(bg_offset + (CAM_W as i32), 0, CAM_W, CAM_H))?;
+			bg.draw(&mut self.core, &assets)?;
 
 			// Draw player
-			self.core.wincan.copy(p.texture(), p.src(), p.pos())?;
+			self.core.wincan.copy(p.texture(), p.src(), player.rect())?;
 
-			self.core.wincan.present();
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -1,16 +1,20 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
+use std::time::Duration;
 
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
-use sdl2::render::Texture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::kinematics::Body;
+use sdl_rust::assets::{AssetManager, TextureHandle};
+use sdl_rust::animation::SpriteSheet;
+use sdl_rust::text;
+use sdl_rust::text::Text;
 
 const TITLE: &str = "SDL15 Animation";
 
@@ -25,55 +29,36 @@ const TILE_SIZE: u32 = 100;
 const LTHIRD: i32 = ((CAM_W as i32) / 3) - (TILE_SIZE as i32)/2;
 const RTHIRD: i32 = ((CAM_W as i32) * 2 / 3) - (TILE_SIZE as i32)/2;
 
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
 
 // Note: redesigned player struct
-struct Player<'a> {
-	pos: Rect,
-	texture: Texture<'a>,
+struct Player {
+	body: Body,
+	texture: TextureHandle,
 }
 
-impl<'a> Player<'a> {
-	fn new(pos: Rect, texture: Texture<'a>) -> Player {
+impl Player {
+	fn new(body: Body, texture: TextureHandle) -> Player {
 		Player {
-			pos,
+			body,
 			texture,
 		}
 	}
 
 	fn x(&self) -> i32 {
-		self.pos.x()
+		self.body.rect().x()
 	}
 
 	fn y(&self) -> i32 {
-		self.pos.y()
+		self.body.rect().y()
 	}
 
-	// Horizontal movement only!
-	fn update_pos(&mut self, vel: i32, x_bounds: (i32, i32)) {
-		self.pos.set_x((self.pos.x() + vel).clamp(x_bounds.0, x_bounds.1));
-	}
-
-	fn texture(&self) -> &Texture {
-		&self.texture
-	}
-}
-
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
+	fn texture(&self) -> TextureHandle {
+		self.texture
 	}
 }
 
@@ -82,67 +67,73 @@ pub struct SDL15 {
 }
 
 impl Demo for SDL15 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL15{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
+		let mut assets = AssetManager::new(&texture_creator);
 
+		// Edit any of the images while this runs and the change shows up
+		// within a second
+		assets.watch(Some(Duration::from_secs(1)));
+		// For saying what failed to reload
+		let mut text = Text::new(&texture_creator)?;
+		let font = text.load_font(text::DEFAULT_FONT, 16)?;
 
 		// BG is the same size and window, but will scroll as the user moves
-		let bg = texture_creator.load_texture("images/bg.png")?;
+		let bg = assets.load_texture("images/bg.png")?;
 		let mut scroll_offset = 0;
 
 		// Also drawing bricks again
-		let brick_sheet = texture_creator.load_texture("images/bricks.png")?;		
+		let brick_sheet = assets.load_texture("images/bricks.png")?;
+
+		// Frames and timing come from an Aseprite export of walking.png
+		let walking = SpriteSheet::load_aseprite("images/walking.json", &mut assets)?;
 
 		let mut p = Player::new(
-			Rect::new(
-				TILE_SIZE as i32,
-				(CAM_H - TILE_SIZE*2) as i32,
-				TILE_SIZE,
-				TILE_SIZE,
-			),
-			texture_creator.load_texture("images/walking.png")?,
+			Body::new(TILE_SIZE as f32, (CAM_H - TILE_SIZE*2) as f32, TILE_SIZE, TILE_SIZE)
+				.max_speed(SPEED_LIMIT)
+				.linear_drag(FRICTION),
+			walking.texture(),
 		);
 
-		// Used to keep track of animation status
-		let mut frames = 0;
-		let mut src_x = 0;
-
+		// Plays the "walk" clip by time, so it looks the same at any frame rate
+		let mut walk = walking.animator("walk")
+			.ok_or_else(|| Error::Other("images/walking.json has no \"walk\" tag".to_string()))?;
 		// Make sure player is facing the right direction
 		let mut flip = false;
 
-		let mut x_vel = 0;
-
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			// Between frames is a safe time to swap textures
+			assets.reload_changed();
+
+			let keystate = self.core.pressed_keys();
 
 			// Horizontal movement only!
-			let mut x_deltav = 0;
+			let mut x_accel = 0.0;
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				x_accel -= ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				x_accel += ACCEL;
 			}
-			x_deltav = resist(x_vel, x_deltav);
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
+			p.body.set_accel(x_accel, 0.0);
 
-			p.update_pos(x_vel, (0, (LEVEL_LEN - TILE_SIZE) as i32));
+			// Recorded along with input, so replays move and animate the same
+			let dt = self.core.delta();
+			p.body.update(dt);
+			p.body.clamp_to(Rect::new(0, 0, LEVEL_LEN, CAM_H));
+			let x_vel = p.body.vel().0;
 
 			scroll_offset = if p.x() > scroll_offset + RTHIRD {
 				(p.x() - RTHIRD).clamp(0, (LEVEL_LEN - CAM_W) as i32)
@@ -158,42 +149,28 @@ impl Demo for SDL15 {
 			let mut brick_offset = -(scroll_offset % (TILE_SIZE as i32));
 
 			// Check if we need to update anything for animation
-			flip = if x_vel > 0 && flip {
+			flip = if x_vel > 0.0 && flip {
 				false
 			}
-			else if x_vel < 0 && !flip {
+			else if x_vel < 0.0 && !flip {
 				true
 			}
 			else {
 				flip
 			};
 
-			src_x = if x_vel != 0 {
-				// Why not just:
-				/*frames = ((frames + 1) % 4);
-				frames * 100
-				*/
-				// Why do this instead?
-				frames = if (frames + 1) / 6 > 3 {
-					0
-				}
-				else {
-					frames + 1
-				};
-
-				(frames / 6) * 100
+			// Only walk while moving, otherwise hold the current frame
+			if x_vel != 0.0 {
+				walk.update(dt);
 			}
-			else {
-				src_x
-			};
-			
+
 			// Check if we need to updated scroll offset
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 
 			// Draw background
-			self.core.wincan.copy(&bg, None, Rect::new(bg_offset, 0, CAM_W, CAM_H))?;
-			self.core.wincan.copy(&bg, None, Rect::new(bg_offset + (CAM_W as i32), 0, CAM_W, CAM_H))?;
+			assets.draw(&mut self.core.wincan, bg, None, Rect::new(bg_offset, 0, CAM_W, CAM_H))?;
+			assets.draw(&mut self.core.wincan, bg, None, Rect::new(bg_offset + (CAM_W as i32), 0, CAM_W, CAM_H))?;
 
 			// Draw bricks
 			let mut i = (scroll_offset % ((TILE_SIZE as i32) * 4)) / (TILE_SIZE as i32);
@@ -201,16 +178,18 @@ impl Demo for SDL15 {
 				let src = Rect::new((i % 4) * (TILE_SIZE as i32), 0, TILE_SIZE, TILE_SIZE);
 				let pos = Rect::new(brick_offset, (CAM_H - TILE_SIZE) as i32, TILE_SIZE, TILE_SIZE);
 
-				self.core.wincan.copy(&brick_sheet, src, pos)?;
+				assets.draw(&mut self.core.wincan, brick_sheet, src, pos)?;
 
 				i += 1;
 				brick_offset += TILE_SIZE as i32;
 			}
 
 			// Draw player using copyex for extra features
+			let player_texture = assets.texture(p.texture())
+				.ok_or_else(|| Error::Other("Player texture was unloaded".to_string()))?;
 			self.core.wincan.copy_ex(
-				p.texture(),
-				Rect::new(src_x, 0, TILE_SIZE, TILE_SIZE),
+				player_texture,
+				walk.src(),
 				Rect::new(p.x() - scroll_offset, p.y(), TILE_SIZE, TILE_SIZE),
 				0.0,
 				None,
@@ -218,11 +197,14 @@ impl Demo for SDL15 {
 				false,
 			)?;
 
-			self.core.wincan.present();
+			// Red border if an edited image didn't load
+			assets.draw_error_overlay(&mut self.core.wincan, &mut text, font)?;
+
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -5,10 +5,12 @@ use std::time::Instant;
 use sdl2::pixels::Color;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::text;
+use sdl_rust::text::{Align, Text, TextStyle};
 
 const TITLE: &str = "SDL16 Event Handling";
 const CAM_W: u32 = 640;
@@ -20,20 +22,33 @@ pub struct SDL16 {
 }
 
 impl Demo for SDL16 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		Ok(SDL16{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
-		let bg = texture_creator.load_texture("images/nightmare_fuel.png")?;
+		let bg = sdl_rust::load_texture(&texture_creator, "images/nightmare_fuel.png")?;
+
+		let mut text = Text::new(&texture_creator)?;
+		let big = text.load_font(text::DEFAULT_FONT, 32)?;
+		let small = text.load_font(text::DEFAULT_FONT, 18)?;
+
+		// Outlined, so it shows up on any part of the image
+		let timer_style = TextStyle::new(Color::WHITE).outline(2, Color::BLACK);
+		let caption = "Stare at the image for as long as you can stand it. Escape when you've had enough.";
+		let caption_style = TextStyle::new(Color::YELLOW)
+			.wrap(CAM_W - 80)
+			.align(Align::Center)
+			.shadow(2, 2, Color::BLACK);
+		let (caption_w, caption_h) = text.measure(small, caption, &caption_style)?;
 
 		// We'll use the Instant::elapsed method to get an std::time::Durtion
 		let start = Instant::now();
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
 					_ => {},
@@ -43,14 +58,25 @@ impl Demo for SDL16 {
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 			self.core.wincan.copy(&bg, None, None)?;
-			self.core.wincan.present();
-		}
 
-		// Out of game loop, print time and return Ok
-		let t = start.elapsed().as_millis();
-		println!("\n\nYou managed to stare at the image for {}ms!", t);
+			// Changes every frame, so each is rendered once and falls out of
+			// the cache; the caption is only rendered the first time
+			let t = start.elapsed().as_millis();
+			text.draw(&mut self.core.wincan, big, &format!("{}ms", t), 20, 20, &timer_style)?;
+			text.draw(
+				&mut self.core.wincan,
+				small,
+				caption,
+				((CAM_W - caption_w) / 2) as i32,
+				(CAM_H - caption_h - 20) as i32,
+				&caption_style,
+			)?;
+
+			self.core.present()?;
+		}
 
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
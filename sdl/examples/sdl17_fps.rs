@@ -1,20 +1,24 @@
 extern crate sdl_rust;
 
-use std::collections::HashSet;
-use std::time::{Instant, Duration};
-
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::image::LoadTexture;
 use sdl2::render::Texture;
 
 use sdl_rust::SDLCore;
 use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::kinematics::Body;
+use sdl_rust::animation::{Animator, Clip, Playback};
+use sdl_rust::overlay::DebugOverlay;
+use sdl_rust::profiler::Phase;
 
 const TITLE: &str = "SDL17 Calculate FPS";
 // Repeat of SDL15 Animation with FPS calculation
+// F3: hide/show the FPS overlay
+// L: turn a 60 FPS software frame limit on/off
+// Run with SDL_RUST_PROFILE=1 (or =timings.csv) for where each frame goes
 
 const CAM_W: u32 = 640;
 const CAM_H: u32 = 480;
@@ -26,32 +30,34 @@ const TILE_SIZE: u32 = 100;
 const LTHIRD: i32 = ((CAM_W as i32) / 3) - (TILE_SIZE as i32)/2;
 const RTHIRD: i32 = ((CAM_W as i32) * 2 / 3) - (TILE_SIZE as i32)/2;
 
-const SPEED_LIMIT: i32 = 5;
-const ACCEL_RATE: i32 = 1;
+// With L
+const FRAME_LIMIT: u32 = 60;
+
+// Pixels per second, and per second per second. Friction works against the
+// player's acceleration too, so pushing nets ACCEL - FRICTION.
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL: f32 = 7200.0;
+const FRICTION: f32 = 3600.0;
 
 struct Player<'a> {
-	pos: Rect,
+	body: Body,
 	texture: Texture<'a>,
 }
 
 impl<'a> Player<'a> {
-	fn new(pos: Rect, texture: Texture<'a>) -> Player {
+	fn new(body: Body, texture: Texture<'a>) -> Player {
 		Player {
-			pos,
+			body,
 			texture,
 		}
 	}
 
 	fn x(&self) -> i32 {
-		self.pos.x()
+		self.body.rect().x()
 	}
 
 	fn y(&self) -> i32 {
-		self.pos.y()
-	}
-
-	fn update_pos(&mut self, vel: i32, x_bounds: (i32, i32)) {
-		self.pos.set_x((self.pos.x() + vel).clamp(x_bounds.0, x_bounds.1));
+		self.body.rect().y()
 	}
 
 	fn texture(&self) -> &Texture {
@@ -59,90 +65,78 @@ impl<'a> Player<'a> {
 	}
 }
 
-fn resist(vel: i32, deltav: i32) -> i32 {
-	if deltav == 0 {
-		if vel > 0 {
-			-1
-		}
-		else if vel < 0 {
-			1
-		}
-		else {
-			deltav
-		}
-	}
-	else {
-		deltav
-	}
-}
-
 pub struct SDL17 {
 	core: SDLCore,
 }
 
 impl Demo for SDL17 {
-	fn init() -> Result<Self, String> {
+	fn init() -> Result<Self, Error> {
 		// With vsync:
 		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
 		// Without vsync:
 		//let core = SDLCore::init(TITLE, false, CAM_W, CAM_H)?;
+		// Without vsync, but held to 60 FPS in software:
+		//let core = SDLCore::builder(TITLE, CAM_W, CAM_H).frame_limit(60).build()?;
 		Ok(SDL17{ core })
 	}
 
-	fn run(&mut self) -> Result<(), String> {
+	fn run(&mut self) -> Result<(), Error> {
 		let texture_creator = self.core.wincan.texture_creator();
 
-		let bg = texture_creator.load_texture("images/bg.png")?;
+		let bg = sdl_rust::load_texture(&texture_creator, "images/bg.png")?;
 		let mut scroll_offset = 0;
 
-		let brick_sheet = texture_creator.load_texture("images/bricks.png")?;		
+		let brick_sheet = sdl_rust::load_texture(&texture_creator, "images/bricks.png")?;		
 
 		let mut p = Player::new(
-			Rect::new(
-				TILE_SIZE as i32,
-				(CAM_H - TILE_SIZE*2) as i32,
-				TILE_SIZE,
-				TILE_SIZE,
-			),
-			texture_creator.load_texture("images/walking.png")?,
+			Body::new(TILE_SIZE as f32, (CAM_H - TILE_SIZE*2) as f32, TILE_SIZE, TILE_SIZE)
+				.max_speed(SPEED_LIMIT)
+				.linear_drag(FRICTION),
+			sdl_rust::load_texture(&texture_creator, "images/walking.png")?,
 		);
 
-		let mut frames = 0;
-		let mut src_x = 0;
+		// Four 100px frames, a tenth of a second each, whatever the FPS
+		let mut walk = Animator::new(Clip::strip("walk", 0, 0, TILE_SIZE, TILE_SIZE, 4, 0.1, Playback::Loop));
 
 		let mut flip = false;
-		let mut x_vel = 0;
 
-		// FPS tracking
-		let mut all_frames = 0;
-		let mut last_time = Instant::now();
-		println!("\n\n");
+		// FPS tracking, on screen from the start
+		let mut overlay = DebugOverlay::new(&texture_creator)?.visible(true);
 
 		'gameloop: loop {
-			for event in self.core.event_pump.poll_iter() {
+			for event in self.core.poll_events() {
+				if overlay.handle_event(&event) {
+					continue;
+				}
 				match event {
 					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
+					Event::KeyDown{keycode: Some(Keycode::L), repeat: false, ..} => {
+						let limit = if self.core.pacer().is_some() { None } else { Some(FRAME_LIMIT) };
+						self.core.set_frame_limit(limit);
+					},
 					_ => {},
 				}
 			}
 
-			let keystate: HashSet<Keycode> = self.core.event_pump
-				.keyboard_state()
-				.pressed_scancodes()
-				.filter_map(Keycode::from_scancode)
-				.collect();
+			// Input and present() are timed by SDLCore itself
+			self.core.begin_phase(Phase::Update);
+
+			let keystate = self.core.pressed_keys();
 
-			let mut x_deltav = 0;
+			let mut x_accel = 0.0;
 			if keystate.contains(&Keycode::A) {
-				x_deltav -= ACCEL_RATE;
+				x_accel -= ACCEL;
 			}
 			if keystate.contains(&Keycode::D) {
-				x_deltav += ACCEL_RATE;
+				x_accel += ACCEL;
 			}
-			x_deltav = resist(x_vel, x_deltav);
-			x_vel = (x_vel + x_deltav).clamp(-SPEED_LIMIT, SPEED_LIMIT);
+			p.body.set_accel(x_accel, 0.0);
 
-			p.update_pos(x_vel, (0, (LEVEL_LEN - TILE_SIZE) as i32));
+			// Recorded along with input, so replays move and animate the same
+			let dt = self.core.delta();
+			p.body.update(dt);
+			p.body.clamp_to(Rect::new(0, 0, LEVEL_LEN, CAM_H));
+			let x_vel = p.body.vel().0;
 
 			scroll_offset = if p.x() > scroll_offset + RTHIRD {
 				(p.x() - RTHIRD).clamp(0, (LEVEL_LEN - CAM_W) as i32)
@@ -157,30 +151,31 @@ impl Demo for SDL17 {
 			let bg_offset = -(scroll_offset % (CAM_W as i32));
 			let mut brick_offset = -(scroll_offset % (TILE_SIZE as i32));
 
-			flip = if x_vel > 0 && flip {
+			flip = if x_vel > 0.0 && flip {
 				false
 			}
-			else if x_vel < 0 && !flip {
+			else if x_vel < 0.0 && !flip {
 				true
 			}
 			else {
 				flip
 			};
 
-			src_x = if x_vel != 0 {
-				frames = if (frames + 1) / 6 > 3 {
-					0
-				}
-				else {
-					frames + 1
-				};
+			overlay.frame(dt);
+			overlay.watch("x_vel", format!("{:.0}", x_vel));
+			overlay.watch("scroll", scroll_offset);
+			match self.core.pacer() {
+				Some(p) => overlay.watch("limit", format!("{} FPS, {} missed", p.target_fps(), p.missed())),
+				None => overlay.watch("limit", "off"),
+			}
 
-				(frames / 6) * 100
+			if x_vel != 0.0 {
+				walk.update(dt);
 			}
-			else {
-				src_x
-			};
-			
+
+			self.core.end_phase(Phase::Update);
+			self.core.begin_phase(Phase::Render);
+
 			self.core.wincan.set_draw_color(Color::BLACK);
 			self.core.wincan.clear();
 
@@ -203,7 +198,7 @@ impl Demo for SDL17 {
 			// Draw player
 			self.core.wincan.copy_ex(
 				p.texture(),
-				Rect::new(src_x, 0, TILE_SIZE, TILE_SIZE),
+				walk.src(),
 				Rect::new(p.x() - scroll_offset, p.y(), TILE_SIZE, TILE_SIZE),
 				0.0,
 				None,
@@ -211,22 +206,14 @@ impl Demo for SDL17 {
 				false,
 			)?;
 
-			self.core.wincan.present();
+			overlay.draw(&mut self.core)?;
+			self.core.end_phase(Phase::Render);
 
-			// FPS Calculation
-			all_frames += 1;
-			let elapsed = last_time.elapsed();
-			if elapsed > Duration::from_secs(5) {
-				let fps_avg = (all_frames as f64) / elapsed.as_secs_f64();
-				println!("Average FPS: {:.2}", fps_avg);
-
-				all_frames = 0;
-				last_time = Instant::now();
-			}
+			self.core.present()?;
 		}
 
-		// Out of game loop, return Ok
-		Ok(())
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
 	}
 }
 
@@ -0,0 +1,139 @@
+extern crate sdl_rust;
+
+use std::error::Error as StdError;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use sdl_rust::SDLCore;
+use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::game_loop::{GameLoop, Flow, lerp};
+use sdl_rust::input::Input;
+use sdl_rust::controller::{Controllers, ControllerChange};
+
+const TITLE: &str = "SDL18 Fixed Timestep";
+// Repeat of SDL07, but speeds are per second instead of per frame, so it
+// moves the same with or without vsync. Controls come from
+// config/input.json, and work with a controller too.
+
+const CAM_W: u32 = 640;
+const CAM_H: u32 = 480;
+
+const UPDATES_PER_SEC: u32 = 60;
+
+// Pixels per second, and pixels per second per second
+const SPEED_LIMIT: f32 = 300.0;
+const ACCEL_RATE: f32 = 1800.0;
+// Held to go this much faster
+const DASH: f32 = 2.0;
+
+const W: u32 = 25;
+
+fn resist(vel: f32, deltav: f32, dt: f32) -> f32 {
+	if deltav == 0.0 {
+		// Don't overshoot past 0
+		-vel.signum() * vel.abs().min(ACCEL_RATE * dt)
+	}
+	else {
+		deltav
+	}
+}
+
+struct Square {
+	// Keep last update's position to interpolate from
+	prev: (f32, f32),
+	pos: (f32, f32),
+	vel: (f32, f32),
+}
+
+pub struct SDL18 {
+	core: SDLCore,
+}
+
+impl Demo for SDL18 {
+	fn init() -> Result<Self, Error> {
+		// Try without vsync too, speed should stay the same
+		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
+		Ok(SDL18{ core })
+	}
+
+	fn run(&mut self) -> Result<(), Error> {
+		let start = (((CAM_W - W) / 2) as f32, ((CAM_H - W) / 2) as f32);
+		let mut square = Square {
+			prev: start,
+			pos: start,
+			vel: (0.0, 0.0),
+		};
+
+		let mut input = Input::load("config/input.json")?;
+		// Plug one in (or pull it out) any time
+		let mut controllers = Controllers::new(&self.core)?;
+		for e in controllers.startup_errors() {
+			eprintln!("{}: {}", e, e.source().map_or(String::new(), |s| s.to_string()));
+		}
+
+		GameLoop::new(UPDATES_PER_SEC).run(
+			&mut self.core,
+			&mut square,
+			|_core, sq, events, dt| {
+				for e in events {
+					match controllers.handle_event(e) {
+						Ok(Some(ControllerChange::Connected{ name, .. })) => println!("Connected {}", name),
+						Ok(Some(ControllerChange::Disconnected{ .. })) => println!("Controller disconnected"),
+						Ok(None) => {},
+						// Keep going with whatever else is plugged in
+						Err(e) => eprintln!("{}: {}", e, e.source().map_or(String::new(), |s| s.to_string())),
+					}
+				}
+				input.update(events);
+
+				if input.just_pressed("quit") {
+					return Ok(Flow::Quit);
+				}
+
+				// Press F1, then whatever you'd rather dash with
+				if input.just_pressed("rebind_dash") {
+					println!("Press a key or button to dash with");
+					input.listen("dash");
+				}
+
+				let mut x_deltav = input.axis("move_x") * ACCEL_RATE * dt;
+				let mut y_deltav = input.axis("move_y") * ACCEL_RATE * dt;
+
+				x_deltav = resist(sq.vel.0, x_deltav, dt);
+				y_deltav = resist(sq.vel.1, y_deltav, dt);
+
+				let limit = if input.pressed("dash") { SPEED_LIMIT * DASH } else { SPEED_LIMIT };
+				sq.vel.0 = (sq.vel.0 + x_deltav).clamp(-limit, limit);
+				sq.vel.1 = (sq.vel.1 + y_deltav).clamp(-limit, limit);
+
+				sq.prev = sq.pos;
+				sq.pos.0 = (sq.pos.0 + sq.vel.0 * dt).clamp(0.0, (CAM_W - W) as f32);
+				sq.pos.1 = (sq.pos.1 + sq.vel.1 * dt).clamp(0.0, (CAM_H - W) as f32);
+
+				Ok(Flow::Continue)
+			},
+			|core, sq, alpha| {
+				// Draw partway between the last two updates
+				let x = lerp(sq.prev.0, sq.pos.0, alpha).round() as i32;
+				let y = lerp(sq.prev.1, sq.pos.1, alpha).round() as i32;
+
+				core.wincan.set_draw_color(Color::BLACK);
+				core.wincan.clear();
+
+				core.wincan.set_draw_color(Color::CYAN);
+				core.wincan.fill_rect(Rect::new(x, y, W, W))?;
+
+				Ok(())
+			},
+		)?;
+
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
+	}
+}
+
+fn main() {
+	sdl_rust::runner(TITLE, SDL18::init);
+}
@@ -0,0 +1,183 @@
+extern crate sdl_rust;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::BlendMode;
+
+use sdl_rust::SDLCore;
+use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::game_loop::GameLoop;
+use sdl_rust::scene::{Scene, SceneStack, Transition};
+
+const TITLE: &str = "SDL19 Scenes";
+const CAM_W: u32 = 640;
+const CAM_H: u32 = 480;
+
+const W: u32 = 25;
+// Pixels per second
+const SPEED: f32 = 200.0;
+
+// Press Enter to start
+struct TitleScreen {
+	t: f32,
+}
+
+impl<'a> Scene<'a> for TitleScreen {
+	fn handle_event(&mut self, _core: &mut SDLCore, event: &Event) -> Result<Transition<'a>, Error> {
+		match event {
+			Event::KeyDown{keycode: Some(Keycode::Return), ..} => {
+				// Title screen isn't coming back, so replace it
+				Ok(Transition::Replace(Box::new(Play::new())))
+			},
+			Event::KeyDown{keycode: Some(Keycode::Escape), ..} => Ok(Transition::Quit),
+			_ => Ok(Transition::None),
+		}
+	}
+
+	fn update(&mut self, _core: &mut SDLCore, dt: f32) -> Result<Transition<'a>, Error> {
+		self.t += dt;
+		Ok(Transition::None)
+	}
+
+	fn render(&self, core: &mut SDLCore, _alpha: f32) -> Result<(), Error> {
+		core.wincan.set_draw_color(Color::RGBA(0, 128, 128, 255));
+		core.wincan.clear();
+
+		// Pulse a square so we know it's alive
+		let w = (100.0 + 25.0 * (self.t * 4.0).sin()) as u32;
+		core.wincan.set_draw_color(Color::CYAN);
+		core.wincan.fill_rect(Rect::new(((CAM_W - w) / 2) as i32, ((CAM_H - w) / 2) as i32, w, w))?;
+
+		Ok(())
+	}
+}
+
+// Move with WASD, P to pause
+struct Play {
+	pos: (f32, f32),
+}
+
+impl Play {
+	fn new() -> Play {
+		Play {
+			pos: (((CAM_W - W) / 2) as f32, ((CAM_H - W) / 2) as f32),
+		}
+	}
+}
+
+impl<'a> Scene<'a> for Play {
+	fn pause(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		println!("Paused at ({:.0}, {:.0})", self.pos.0, self.pos.1);
+		Ok(())
+	}
+
+	fn resume(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		println!("Resumed");
+		Ok(())
+	}
+
+	fn handle_event(&mut self, _core: &mut SDLCore, event: &Event) -> Result<Transition<'a>, Error> {
+		match event {
+			Event::KeyDown{keycode: Some(Keycode::P), ..} => Ok(Transition::Push(Box::new(Pause))),
+			Event::KeyDown{keycode: Some(Keycode::Escape), ..} => Ok(Transition::Quit),
+			_ => Ok(Transition::None),
+		}
+	}
+
+	fn update(&mut self, core: &mut SDLCore, dt: f32) -> Result<Transition<'a>, Error> {
+		let keystate = core.pressed_keys();
+
+		if keystate.contains(&Keycode::W) {
+			self.pos.1 -= SPEED * dt;
+		}
+		if keystate.contains(&Keycode::A) {
+			self.pos.0 -= SPEED * dt;
+		}
+		if keystate.contains(&Keycode::S) {
+			self.pos.1 += SPEED * dt;
+		}
+		if keystate.contains(&Keycode::D) {
+			self.pos.0 += SPEED * dt;
+		}
+
+		self.pos.0 = self.pos.0.clamp(0.0, (CAM_W - W) as f32);
+		self.pos.1 = self.pos.1.clamp(0.0, (CAM_H - W) as f32);
+
+		Ok(Transition::None)
+	}
+
+	fn render(&self, core: &mut SDLCore, _alpha: f32) -> Result<(), Error> {
+		core.wincan.set_draw_color(Color::BLACK);
+		core.wincan.clear();
+
+		core.wincan.set_draw_color(Color::CYAN);
+		core.wincan.fill_rect(Rect::new(self.pos.0 as i32, self.pos.1 as i32, W, W))?;
+
+		Ok(())
+	}
+}
+
+// P to resume, Q to quit. Play stays visible underneath.
+struct Pause;
+
+impl<'a> Scene<'a> for Pause {
+	fn handle_event(&mut self, _core: &mut SDLCore, event: &Event) -> Result<Transition<'a>, Error> {
+		match event {
+			Event::KeyDown{keycode: Some(Keycode::P), ..}
+				| Event::KeyDown{keycode: Some(Keycode::Escape), ..} => Ok(Transition::Pop),
+			Event::KeyDown{keycode: Some(Keycode::Q), ..} => Ok(Transition::Quit),
+			_ => Ok(Transition::None),
+		}
+	}
+
+	fn update(&mut self, _core: &mut SDLCore, _dt: f32) -> Result<Transition<'a>, Error> {
+		Ok(Transition::None)
+	}
+
+	fn render(&self, core: &mut SDLCore, _alpha: f32) -> Result<(), Error> {
+		// Dim whatever is underneath
+		core.wincan.set_blend_mode(BlendMode::Blend);
+		core.wincan.set_draw_color(Color::RGBA(0, 0, 0, 160));
+		core.wincan.fill_rect(None)?;
+		core.wincan.set_blend_mode(BlendMode::None);
+
+		// Pause symbol
+		core.wincan.set_draw_color(Color::WHITE);
+		core.wincan.fill_rect(Rect::new((CAM_W / 2 - 30) as i32, (CAM_H / 2 - 40) as i32, 20, 80))?;
+		core.wincan.fill_rect(Rect::new((CAM_W / 2 + 10) as i32, (CAM_H / 2 - 40) as i32, 20, 80))?;
+
+		Ok(())
+	}
+
+	fn is_overlay(&self) -> bool {
+		true
+	}
+}
+
+pub struct SDL19 {
+	core: SDLCore,
+}
+
+impl Demo for SDL19 {
+	fn init() -> Result<Self, Error> {
+		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
+		Ok(SDL19{ core })
+	}
+
+	fn run(&mut self) -> Result<(), Error> {
+		let mut scenes = SceneStack::new();
+		scenes.push(&mut self.core, Box::new(TitleScreen{ t: 0.0 }))?;
+
+		scenes.run(&mut self.core, &GameLoop::new(60))?;
+
+		// If recording, find out whether it saved
+		self.core.finish_recording()
+	}
+}
+
+fn main() {
+	sdl_rust::runner(TITLE, SDL19::init);
+}
@@ -0,0 +1,140 @@
+extern crate sdl_rust;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use sdl_rust::SDLCore;
+use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::assets::AssetManager;
+use sdl_rust::camera::Follow;
+use sdl_rust::tilemap::{Tilemap, Property};
+use sdl_rust::collision::Aabb;
+use sdl_rust::kinematics::Body;
+use sdl_rust::overlay::DebugOverlay;
+
+const TITLE: &str = "SDL20 Tilemap";
+// The level is made in Tiled (maps/level.tmx): a decoration layer you can
+// walk over, and a wall layer whose tiles are marked "solid"
+// F3: debug overlay, with the walls being checked and the camera's bounds
+
+const CAM_W: u32 = 640;
+const CAM_H: u32 = 480;
+
+const TILE_SIZE: u32 = 100;
+const PLAYER_SIZE: u32 = 50;
+
+// Pixels per second
+const SPEED: f32 = 300.0;
+
+pub struct SDL20 {
+	core: SDLCore,
+}
+
+impl Demo for SDL20 {
+	fn init() -> Result<Self, Error> {
+		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
+		Ok(SDL20{ core })
+	}
+
+	fn run(&mut self) -> Result<(), Error> {
+		let texture_creator = self.core.wincan.texture_creator();
+		let mut assets = AssetManager::new(&texture_creator);
+
+		let map = Tilemap::load("maps/level.tmx", &mut assets)?;
+		let birds = assets.load_texture("images/birds.png")?;
+
+		let mut overlay = DebugOverlay::new(&texture_creator)?;
+		overlay.set_show_colliders(true);
+		overlay.set_show_camera(true);
+
+		self.core.cam.set_bounds(Some(map.bounds()));
+		self.core.cam.set_mode(Follow::Centered);
+
+		// Start in the middle of the tile the map says to
+		let spawn = |name| map.properties()
+			.get(name)
+			.and_then(Property::as_int)
+			.unwrap_or(1) as i32;
+		let start = map.tile_rect(spawn("spawn_x"), spawn("spawn_y")).center();
+		let mut player = Body::new(
+			(start.x() - (PLAYER_SIZE / 2) as i32) as f32,
+			(start.y() - (PLAYER_SIZE / 2) as i32) as f32,
+			PLAYER_SIZE,
+			PLAYER_SIZE,
+		);
+
+		'gameloop: loop {
+			for event in self.core.poll_events() {
+				if overlay.handle_event(&event) {
+					continue;
+				}
+				match event {
+					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
+					_ => {},
+				}
+			}
+
+			let keystate = self.core.pressed_keys();
+
+			let dt = self.core.delta();
+
+			let mut vel = (0.0, 0.0);
+			if keystate.contains(&Keycode::W) {
+				vel.1 -= SPEED;
+			}
+			if keystate.contains(&Keycode::A) {
+				vel.0 -= SPEED;
+			}
+			if keystate.contains(&Keycode::S) {
+				vel.1 += SPEED;
+			}
+			if keystate.contains(&Keycode::D) {
+				vel.0 += SPEED;
+			}
+
+			// Sweep against the solid tiles anywhere we could get to this
+			// frame, sliding along any we hit
+			player.set_vel(vel.0, vel.1);
+			let reach = (SPEED * dt).ceil() as i32 + 1;
+			let r = player.rect();
+			let area = Rect::new(r.x() - reach, r.y() - reach, r.width() + 2 * reach as u32, r.height() + 2 * reach as u32);
+			let walls: Vec<Aabb> = map.solid_rects(area).into_iter().map(Aabb::from).collect();
+			player.move_and_slide(dt, &walls);
+
+			overlay.frame(dt);
+			for &w in walls.iter() {
+				overlay.collider(w);
+			}
+			overlay.collider(player.aabb());
+			let (px, py) = player.pos();
+			overlay.watch("player", format!("{:.0}, {:.0}", px, py));
+
+			let p = player.rect();
+			self.core.cam.follow(p, dt);
+
+			self.core.wincan.set_draw_color(Color::BLACK);
+			self.core.wincan.clear();
+
+			// Only the tiles on screen get drawn
+			map.draw(&mut self.core, &assets)?;
+
+			if let Some(t) = assets.texture(birds) {
+				self.core.copy_world(t, Rect::new(0, 0, TILE_SIZE, TILE_SIZE), p)?;
+			}
+
+			overlay.draw(&mut self.core)?;
+
+			self.core.present()?;
+		}
+
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
+	}
+}
+
+fn main() {
+	sdl_rust::runner(TITLE, SDL20::init);
+}
@@ -0,0 +1,122 @@
+extern crate sdl_rust;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use sdl_rust::SDLCore;
+use sdl_rust::Demo;
+use sdl_rust::Error;
+use sdl_rust::audio::Audio;
+
+const TITLE: &str = "SDL21 Audio";
+// A, S, D: blip on the left, middle, right
+// Tab: crossfade to the other music loop
+// M: mute, Up/Down: master volume
+
+const CAM_W: u32 = 640;
+const CAM_H: u32 = 480;
+
+const PAD_SIZE: u32 = 120;
+// Seconds
+const CROSSFADE: f32 = 1.5;
+const FLASH: f32 = 0.15;
+
+pub struct SDL21 {
+	core: SDLCore,
+}
+
+impl Demo for SDL21 {
+	fn init() -> Result<Self, Error> {
+		let core = SDLCore::init(TITLE, true, CAM_W, CAM_H)?;
+		Ok(SDL21{ core })
+	}
+
+	fn run(&mut self) -> Result<(), Error> {
+		let mut audio = Audio::new(&self.core)?;
+
+		let blip = audio.load("sounds/blip.wav")?;
+		let loops = [
+			audio.load("sounds/loop_a.wav")?,
+			audio.load("sounds/loop_b.wav")?,
+		];
+
+		let mut track = 0;
+		audio.play_music(loops[track], true, CROSSFADE)?;
+
+		// Pads light up for a moment when they play
+		let pans = [-1.0, 0.0, 1.0];
+		let mut flashes = [0.0; 3];
+
+		'gameloop: loop {
+			for event in self.core.poll_events() {
+				match event {
+					Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'gameloop,
+					Event::KeyDown{keycode: Some(k), repeat: false, ..} => {
+						let pad = match k {
+							Keycode::A => Some(0),
+							Keycode::S => Some(1),
+							Keycode::D => Some(2),
+							_ => None,
+						};
+						if let Some(i) = pad {
+							audio.play_with(blip, 1.0, pans[i])?;
+							flashes[i] = FLASH;
+						}
+
+						match k {
+							Keycode::Tab => {
+								track = 1 - track;
+								audio.play_music(loops[track], true, CROSSFADE)?;
+							},
+							Keycode::M => audio.set_muted(!audio.is_muted()),
+							Keycode::Up => audio.set_master_volume(audio.master_volume() + 0.1),
+							Keycode::Down => audio.set_master_volume(audio.master_volume() - 0.1),
+							_ => {},
+						}
+					},
+					_ => {},
+				}
+			}
+
+			let dt = self.core.delta();
+			audio.update(dt);
+			for f in flashes.iter_mut() {
+				*f = (*f - dt).max(0.0);
+			}
+
+			self.core.wincan.set_draw_color(Color::BLACK);
+			self.core.wincan.clear();
+
+			let gap = (CAM_W - 3 * PAD_SIZE) / 4;
+			for (i, &flash) in flashes.iter().enumerate() {
+				let x = gap + (i as u32) * (PAD_SIZE + gap);
+				let pad = Rect::new(x as i32, ((CAM_H - PAD_SIZE) / 2) as i32, PAD_SIZE, PAD_SIZE);
+
+				self.core.wincan.set_draw_color(if flash > 0.0 { Color::YELLOW } else { Color::RGB(60, 60, 90) });
+				self.core.wincan.fill_rect(pad)?;
+			}
+
+			// Master volume along the bottom, red while muted
+			let full = CAM_W - 2 * gap;
+			let bar = Rect::new(gap as i32, (CAM_H - 60) as i32, ((full as f32) * audio.master_volume()) as u32, 20);
+			self.core.wincan.set_draw_color(if audio.is_muted() { Color::RED } else { Color::GREEN });
+			self.core.wincan.fill_rect(bar)?;
+
+			// Which loop is playing, along the top
+			let marker = Rect::new((gap + (track as u32) * (full / 2)) as i32, 40, full / 2, 10);
+			self.core.wincan.set_draw_color(Color::CYAN);
+			self.core.wincan.fill_rect(marker)?;
+
+			self.core.present()?;
+		}
+
+		// Out of game loop; if recording, find out whether it saved
+		self.core.finish_recording()
+	}
+}
+
+fn main() {
+	sdl_rust::runner(TITLE, SDL21::init);
+}
@@ -0,0 +1,607 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use sdl2::rect::Rect;
+use serde_json::Value;
+
+use crate::Error;
+use crate::assets::{AssetManager, TextureHandle};
+
+// What a clip does when it gets to its last frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Playback {
+	// Back to the first frame
+	Loop,
+	// Back down to the first frame, then up again
+	PingPong,
+	// Stop on the last frame
+	Once,
+}
+
+// One frame of a clip: where it is in the sprite sheet, and for how long
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClipFrame {
+	pub src: Rect,
+	// Seconds
+	pub duration: f32,
+}
+
+// A named run of frames, e.g., "walk"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clip {
+	name: String,
+	frames: Vec<ClipFrame>,
+	playback: Playback,
+}
+
+impl Clip {
+	pub fn new(name: &str, playback: Playback) -> Clip {
+		Clip {
+			name: name.to_string(),
+			frames: Vec::new(),
+			playback,
+		}
+	}
+
+	// `count` frames of the same size, side by side starting at (x, y), each
+	// shown for `duration` seconds. SDL15's walk is
+	// Clip::strip("walk", 0, 0, 100, 100, 4, 0.1, Playback::Loop).
+	#[allow(clippy::too_many_arguments)]
+	pub fn strip(
+		name: &str,
+		x: i32,
+		y: i32,
+		w: u32,
+		h: u32,
+		count: u32,
+		duration: f32,
+		playback: Playback,
+	) -> Clip
+	{
+		let mut clip = Clip::new(name, playback);
+		for i in 0..count {
+			clip = clip.frame(Rect::new(x + (i * w) as i32, y, w, h), duration);
+		}
+		clip
+	}
+
+	pub fn frame(mut self, src: Rect, duration: f32) -> Clip {
+		self.frames.push(ClipFrame { src, duration });
+		self
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn frames(&self) -> &[ClipFrame] {
+		&self.frames
+	}
+
+	pub fn playback(&self) -> Playback {
+		self.playback
+	}
+
+	// Seconds to play through once (there and back again for PingPong)
+	pub fn duration(&self) -> f32 {
+		let once: f32 = self.frames.iter().map(|f| f.duration).sum();
+		match self.playback {
+			Playback::PingPong if self.frames.len() > 2 => {
+				let ends = self.frames[0].duration + self.frames[self.frames.len() - 1].duration;
+				2.0 * once - ends
+			},
+			_ => once,
+		}
+	}
+}
+
+// Happened during an Animator::update()
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipEvent {
+	// A Loop or PingPong clip started over, this many times
+	Looped(u32),
+	// A Once clip reached the end of its last frame
+	Finished,
+}
+
+// Shortest a frame can last, so a clip of 0-length frames can't spin forever
+const MIN_FRAME: f32 = 0.001;
+
+// Plays a clip back by elapsed time, instead of counting frames
+#[derive(Clone, Debug)]
+pub struct Animator {
+	clip: Clip,
+	index: usize,
+	// Seconds into the current frame
+	elapsed: f32,
+	// Going up the frames, for PingPong
+	forward: bool,
+	finished: bool,
+	speed: f32,
+}
+
+impl Animator {
+	pub fn new(clip: Clip) -> Animator {
+		Animator {
+			clip,
+			index: 0,
+			elapsed: 0.0,
+			forward: true,
+			finished: false,
+			speed: 1.0,
+		}
+	}
+
+	// Switch to `clip`, from its first frame. Playing the clip that's already
+	// playing carries on where it is; use restart() to start it over.
+	pub fn play(&mut self, clip: &Clip) {
+		if self.clip.name != clip.name {
+			self.clip = clip.clone();
+			self.restart();
+		}
+	}
+
+	pub fn restart(&mut self) {
+		self.index = 0;
+		self.elapsed = 0.0;
+		self.forward = true;
+		self.finished = false;
+	}
+
+	pub fn clip(&self) -> &Clip {
+		&self.clip
+	}
+
+	// 1.0 is as authored, 2.0 twice as fast
+	pub fn set_speed(&mut self, speed: f32) {
+		self.speed = speed.max(0.0);
+	}
+
+	pub fn speed(&self) -> f32 {
+		self.speed
+	}
+
+	pub fn frame_index(&self) -> usize {
+		self.index
+	}
+
+	// Only ever true for Once clips
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	// Where the current frame is in the sprite sheet
+	pub fn src(&self) -> Option<Rect> {
+		self.clip.frames.get(self.index).map(|f| f.src)
+	}
+
+	// Move the animation on by dt seconds. If it wrapped around or finished
+	// along the way, says so.
+	pub fn update(&mut self, dt: f32) -> Option<ClipEvent> {
+		let len = self.clip.frames.len();
+		if len == 0 || self.finished {
+			return None;
+		}
+
+		self.elapsed += dt * self.speed;
+
+		let mut loops = 0;
+		loop {
+			let duration = self.clip.frames[self.index].duration.max(MIN_FRAME);
+			if self.elapsed < duration {
+				break;
+			}
+			self.elapsed -= duration;
+
+			match self.clip.playback {
+				Playback::Once => {
+					if self.index + 1 < len {
+						self.index += 1;
+					}
+					else {
+						self.elapsed = 0.0;
+						self.finished = true;
+						return Some(ClipEvent::Finished);
+					}
+				},
+				Playback::Loop => {
+					self.index += 1;
+					if self.index == len {
+						self.index = 0;
+						loops += 1;
+					}
+				},
+				Playback::PingPong => {
+					if len == 1 {
+						loops += 1;
+						continue;
+					}
+
+					if self.forward && self.index + 1 == len {
+						self.forward = false;
+					}
+
+					if self.forward {
+						self.index += 1;
+					}
+					else {
+						self.index -= 1;
+						if self.index == 0 {
+							self.forward = true;
+							loops += 1;
+						}
+					}
+				},
+			}
+		}
+
+		if loops > 0 {
+			Some(ClipEvent::Looped(loops))
+		}
+		else {
+			None
+		}
+	}
+}
+
+// A sprite sheet and the clips cut from it
+pub struct SpriteSheet {
+	texture: TextureHandle,
+	clips: HashMap<String, Clip>,
+}
+
+impl SpriteSheet {
+	pub fn new(texture: TextureHandle) -> SpriteSheet {
+		SpriteSheet {
+			texture,
+			clips: HashMap::new(),
+		}
+	}
+
+	// Loads a sprite sheet exported from Aseprite as JSON (either "Hash" or
+	// "Array" frames), and its image. Each tag becomes a clip, played forward,
+	// in reverse, or ping-pong as tagged; tags with a repeat count of 1 play
+	// once, the rest loop. A sheet without tags gets one looping clip of every
+	// frame, named after the image.
+	pub fn load_aseprite<P: AsRef<Path>>(path: P, assets: &mut AssetManager) -> Result<SpriteSheet, Error> {
+		let path = path.as_ref();
+
+		let file = File::open(path).map_err(|e| Error::asset(path, e))?;
+		let v: Value = serde_json::from_reader(BufReader::new(file))
+			.map_err(|e| Error::asset(path, e))?;
+
+		let image = v.pointer("/meta/image").and_then(Value::as_str)
+			.ok_or_else(|| Error::asset(path, "no meta.image; is this an Aseprite export?"))?;
+		let image = path.parent()
+			.unwrap_or_else(|| Path::new(""))
+			.join(image);
+
+		let frames = aseprite_frames(path, &v)?;
+		let mut sheet = SpriteSheet::new(assets.load_texture(&image)?);
+
+		let tags = v.pointer("/meta/frameTags").and_then(Value::as_array);
+		match tags {
+			Some(tags) if !tags.is_empty() => {
+				for tag in tags {
+					sheet.insert(aseprite_clip(path, tag, &frames)?);
+				}
+			},
+			_ => {
+				let name = image.file_stem()
+					.and_then(|s| s.to_str())
+					.unwrap_or("");
+				let mut clip = Clip::new(name, Playback::Loop);
+				clip.frames = frames;
+				sheet.insert(clip);
+			},
+		}
+
+		Ok(sheet)
+	}
+
+	pub fn texture(&self) -> TextureHandle {
+		self.texture
+	}
+
+	// Adds (or replaces) a clip, by its name
+	pub fn insert(&mut self, clip: Clip) {
+		self.clips.insert(clip.name.clone(), clip);
+	}
+
+	pub fn clip(&self, name: &str) -> Option<&Clip> {
+		self.clips.get(name)
+	}
+
+	pub fn clips(&self) -> impl Iterator<Item = &Clip> {
+		self.clips.values()
+	}
+
+	// An Animator starting on the named clip
+	pub fn animator(&self, name: &str) -> Option<Animator> {
+		self.clip(name).cloned().map(Animator::new)
+	}
+}
+
+fn aseprite_frame(path: &Path, f: &Value) -> Result<ClipFrame, Error> {
+	let rect = f.get("frame")
+		.ok_or_else(|| Error::asset(path, "frame without a \"frame\" rect"))?;
+	let num = |key| rect.get(key).and_then(Value::as_i64).unwrap_or(0);
+
+	Ok(ClipFrame {
+		src: Rect::new(num("x") as i32, num("y") as i32, num("w") as u32, num("h") as u32),
+		// Aseprite durations are in milliseconds
+		duration: f.get("duration").and_then(Value::as_f64).unwrap_or(100.0) as f32 / 1000.0,
+	})
+}
+
+// The number Aseprite puts at the end of each frame's name, e.g.,
+// "walking 3.aseprite"
+fn frame_number(name: &str) -> Option<u32> {
+	let stem = name.rsplit_once('.').map(|(s, _)| s).unwrap_or(name);
+	let start = stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+	stem[start..].parse().ok()
+}
+
+fn aseprite_frames(path: &Path, v: &Value) -> Result<Vec<ClipFrame>, Error> {
+	match v.get("frames") {
+		Some(Value::Array(frames)) => frames.iter()
+			.map(|f| aseprite_frame(path, f))
+			.collect(),
+		// JSON objects aren't kept in order, so go by the frame numbers
+		Some(Value::Object(frames)) => {
+			let mut numbered: Vec<(Option<u32>, &Value)> = frames.iter()
+				.map(|(name, f)| (frame_number(name), f))
+				.collect();
+			numbered.sort_by_key(|(n, _)| *n);
+
+			numbered.into_iter()
+				.map(|(_, f)| aseprite_frame(path, f))
+				.collect()
+		},
+		_ => Err(Error::asset(path, "no frames")),
+	}
+}
+
+fn aseprite_clip(path: &Path, tag: &Value, frames: &[ClipFrame]) -> Result<Clip, Error> {
+	let name = tag.get("name").and_then(Value::as_str).unwrap_or("");
+	let from = tag.get("from").and_then(Value::as_u64).unwrap_or(0) as usize;
+	let to = tag.get("to").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+	if from > to || to >= frames.len() {
+		return Err(Error::asset(path, format!(
+			"tag \"{}\" covers frames {}..={}, but there are {}",
+			name, from, to, frames.len(),
+		)));
+	}
+
+	let direction = tag.get("direction").and_then(Value::as_str).unwrap_or("forward");
+	// Newer Aseprite versions write the repeat count as a string
+	let repeat = match tag.get("repeat") {
+		Some(Value::String(s)) => s.parse().unwrap_or(0),
+		Some(r) => r.as_u64().unwrap_or(0),
+		None => 0,
+	};
+
+	let playback = if repeat == 1 {
+		Playback::Once
+	}
+	else if direction.starts_with("pingpong") {
+		Playback::PingPong
+	}
+	else {
+		Playback::Loop
+	};
+
+	let mut clip = Clip::new(name, playback);
+	clip.frames = frames[from..=to].to_vec();
+	if direction == "reverse" || direction == "pingpong_reverse" {
+		clip.frames.reverse();
+	}
+
+	Ok(clip)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use serde_json::json;
+
+	// Quarter seconds add up exactly in f32
+	const STEP: f32 = 0.25;
+
+	fn clip(playback: Playback, count: u32) -> Clip {
+		Clip::strip("test", 0, 0, 10, 10, count, STEP, playback)
+	}
+
+	#[test]
+	fn durations() {
+		assert_eq!(clip(Playback::Loop, 4).duration(), 1.0);
+		assert_eq!(clip(Playback::Once, 4).duration(), 1.0);
+		// There and back without playing either end twice
+		assert_eq!(clip(Playback::PingPong, 4).duration(), 1.5);
+		assert_eq!(clip(Playback::PingPong, 2).duration(), 0.5);
+		assert_eq!(clip(Playback::PingPong, 1).duration(), 0.25);
+		assert_eq!(Clip::new("empty", Playback::Loop).duration(), 0.0);
+	}
+
+	#[test]
+	fn loop_wraps_and_counts_loops() {
+		let mut a = Animator::new(clip(Playback::Loop, 4));
+		assert_eq!(a.src(), Some(Rect::new(0, 0, 10, 10)));
+
+		assert_eq!(a.update(STEP), None);
+		assert_eq!(a.frame_index(), 1);
+		assert_eq!(a.src(), Some(Rect::new(10, 0, 10, 10)));
+
+		assert_eq!(a.update(0.1), None);
+		assert_eq!(a.frame_index(), 1);
+
+		// 0.35 + 0.65 = one whole loop
+		assert_eq!(a.update(0.65), Some(ClipEvent::Looped(1)));
+		assert_eq!(a.frame_index(), 0);
+
+		// Long frames skip whole loops at once
+		assert_eq!(a.update(2.0 + STEP), Some(ClipEvent::Looped(2)));
+		assert_eq!(a.frame_index(), 1);
+		assert!(!a.is_finished());
+	}
+
+	#[test]
+	fn once_stops_on_the_last_frame() {
+		let mut a = Animator::new(clip(Playback::Once, 3));
+		assert_eq!(a.update(2.0 * STEP), None);
+		assert_eq!(a.frame_index(), 2);
+
+		assert_eq!(a.update(STEP), Some(ClipEvent::Finished));
+		assert_eq!(a.frame_index(), 2);
+		assert!(a.is_finished());
+
+		// Stays put, and only says so once
+		assert_eq!(a.update(10.0), None);
+		assert_eq!(a.frame_index(), 2);
+
+		a.restart();
+		assert!(!a.is_finished());
+		assert_eq!(a.frame_index(), 0);
+		assert_eq!(a.update(10.0), Some(ClipEvent::Finished));
+	}
+
+	#[test]
+	fn ping_pong_goes_there_and_back() {
+		let mut a = Animator::new(clip(Playback::PingPong, 3));
+
+		let mut indices = Vec::new();
+		let mut events = Vec::new();
+		for _ in 0..8 {
+			events.push(a.update(STEP));
+			indices.push(a.frame_index());
+		}
+		assert_eq!(indices, vec![1, 2, 1, 0, 1, 2, 1, 0]);
+
+		let looped: Vec<_> = events.iter().enumerate().filter(|(_, e)| e.is_some()).collect();
+		assert_eq!(looped, vec![(3, &Some(ClipEvent::Looped(1))), (7, &Some(ClipEvent::Looped(1)))]);
+	}
+
+	#[test]
+	fn ping_pong_of_one_frame() {
+		let mut a = Animator::new(clip(Playback::PingPong, 1));
+		assert_eq!(a.update(STEP / 2.0), None);
+		assert_eq!(a.update(STEP / 2.0), Some(ClipEvent::Looped(1)));
+		assert_eq!(a.update(3.0 * STEP), Some(ClipEvent::Looped(3)));
+		assert_eq!(a.frame_index(), 0);
+	}
+
+	#[test]
+	fn zero_length_frames_still_end() {
+		let clip = Clip::new("instant", Playback::Loop)
+			.frame(Rect::new(0, 0, 10, 10), 0.0)
+			.frame(Rect::new(10, 0, 10, 10), 0.0);
+		let mut a = Animator::new(clip);
+
+		// At MIN_FRAME a frame, a second is about 500 loops of 2 frames
+		match a.update(1.0) {
+			Some(ClipEvent::Looped(n)) => assert!((495..=505).contains(&n), "{} loops", n),
+			other => panic!("{:?}", other),
+		}
+
+		let mut empty = Animator::new(Clip::new("empty", Playback::Loop));
+		assert_eq!(empty.update(1.0), None);
+		assert_eq!(empty.src(), None);
+	}
+
+	#[test]
+	fn speed_and_switching_clips() {
+		let walk = clip(Playback::Loop, 4);
+		let mut a = Animator::new(walk.clone());
+		a.set_speed(2.0);
+		a.update(STEP);
+		assert_eq!(a.frame_index(), 2);
+
+		// Same clip carries on
+		a.play(&walk);
+		assert_eq!(a.frame_index(), 2);
+
+		let jump = Clip::strip("jump", 0, 10, 10, 10, 2, STEP, Playback::Once);
+		a.play(&jump);
+		assert_eq!(a.clip().name(), "jump");
+		assert_eq!(a.frame_index(), 0);
+
+		a.set_speed(-1.0);
+		assert_eq!(a.speed(), 0.0);
+		assert_eq!(a.update(STEP), None);
+		assert_eq!(a.frame_index(), 0);
+	}
+
+	fn frame_json(x: i64, duration: i64) -> Value {
+		json!({ "frame": { "x": x, "y": 0, "w": 10, "h": 10 }, "duration": duration })
+	}
+
+	#[test]
+	fn aseprite_hash_frames() {
+		let path = Path::new("images/walking.json");
+		let v: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+		let frames = aseprite_frames(path, &v).unwrap();
+		let xs: Vec<_> = frames.iter().map(|f| f.src.x()).collect();
+		assert_eq!(xs, vec![0, 100, 200, 300]);
+		assert!(frames.iter().all(|f| f.src.size() == (100, 100) && f.duration == 0.1));
+
+		let tag = &v["meta"]["frameTags"][0];
+		let walk = aseprite_clip(path, tag, &frames).unwrap();
+		assert_eq!(walk.name(), "walk");
+		assert_eq!(walk.playback(), Playback::Loop);
+		assert_eq!(walk.frames(), &frames[..]);
+
+		// Keys sort as text, "f 10" before "f 2", so go by the numbers
+		let mut hash = serde_json::Map::new();
+		for i in 0..12 {
+			hash.insert(format!("f {}.aseprite", i), frame_json(i * 10, 50));
+		}
+		let frames = aseprite_frames(path, &json!({ "frames": hash })).unwrap();
+		let xs: Vec<_> = frames.iter().map(|f| f.src.x()).collect();
+		assert_eq!(xs, (0..12).map(|i| i * 10).collect::<Vec<_>>());
+		assert_eq!(frames[0].duration, 0.05);
+	}
+
+	#[test]
+	fn aseprite_array_frames_and_tags() {
+		let path = Path::new("test.json");
+		let v = json!({
+			"frames": [frame_json(0, 100), frame_json(10, 200), frame_json(20, 100), frame_json(30, 100)],
+		});
+		let frames = aseprite_frames(path, &v).unwrap();
+		assert_eq!(frames.len(), 4);
+		assert_eq!(frames[1].duration, 0.2);
+
+		let xs = |clip: &Clip| clip.frames().iter().map(|f| f.src.x()).collect::<Vec<_>>();
+		let tag = |t: Value| aseprite_clip(path, &t, &frames);
+
+		let c = tag(json!({ "name": "fwd", "from": 1, "to": 3, "direction": "forward" })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::Loop, vec![10, 20, 30]));
+
+		let c = tag(json!({ "name": "back", "from": 0, "to": 2, "direction": "reverse" })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::Loop, vec![20, 10, 0]));
+
+		let c = tag(json!({ "name": "pp", "from": 0, "to": 3, "direction": "pingpong" })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::PingPong, vec![0, 10, 20, 30]));
+
+		let c = tag(json!({ "name": "ppr", "from": 0, "to": 3, "direction": "pingpong_reverse" })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::PingPong, vec![30, 20, 10, 0]));
+
+		// Repeat 1 plays once, as a number or (newer versions) a string
+		let c = tag(json!({ "name": "hit", "from": 2, "to": 3, "repeat": "1" })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::Once, vec![20, 30]));
+		let c = tag(json!({ "name": "hit", "from": 2, "to": 3, "direction": "reverse", "repeat": 1 })).unwrap();
+		assert_eq!((c.playback(), xs(&c)), (Playback::Once, vec![30, 20]));
+		let c = tag(json!({ "name": "twice", "from": 0, "to": 1, "repeat": "2" })).unwrap();
+		assert_eq!(c.playback(), Playback::Loop);
+
+		assert!(tag(json!({ "name": "past", "from": 2, "to": 4 })).is_err());
+		assert!(tag(json!({ "name": "backwards", "from": 3, "to": 1 })).is_err());
+		assert!(aseprite_frames(path, &json!({ "meta": {} })).is_err());
+		assert!(aseprite_frames(path, &json!({ "frames": [{ "duration": 100 }] })).is_err());
+	}
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::image::LoadTexture;
+use sdl2::video::WindowContext;
+
+use crate::Error;
+use crate::text::{FontHandle, Text, TextStyle};
+
+// Cheap, copyable stand-in for a loaded texture. Unlike a &Texture, it
+// doesn't tie whatever holds it to the TextureCreator's lifetime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureHandle {
+	index: usize,
+	// Bumped each time a slot is reused, so handles to unloaded textures
+	// don't find whatever was loaded in their place
+	generation: u32,
+}
+
+// For testing things that hold a handle without loading anything
+#[cfg(test)]
+impl TextureHandle {
+	pub(crate) fn unloaded() -> TextureHandle {
+		TextureHandle {
+			index: usize::MAX,
+			generation: 0,
+		}
+	}
+}
+
+struct Slot<'a> {
+	generation: u32,
+	entry: Option<Entry<'a>>,
+}
+
+struct Entry<'a> {
+	path: PathBuf,
+	key: PathBuf,
+	texture: Texture<'a>,
+	bytes: usize,
+	// When the file was last (re)loaded, for hot reloading
+	modified: Option<SystemTime>,
+}
+
+// Loads each texture once, no matter how many times it's asked for.
+// Create one next to SDLCore, from its canvas's TextureCreator:
+//
+//     let texture_creator = self.core.wincan.texture_creator();
+//     let mut assets = AssetManager::new(&texture_creator);
+pub struct AssetManager<'a> {
+	texture_creator: &'a TextureCreator<WindowContext>,
+	slots: Vec<Slot<'a>>,
+	by_path: HashMap<PathBuf, TextureHandle>,
+	// How often to check files for changes, None if not watching
+	watch_interval: Option<Duration>,
+	last_poll: Instant,
+	// Textures whose file changed but couldn't be reloaded
+	reload_errors: HashMap<usize, Error>,
+}
+
+fn bytes_per_pixel(format: PixelFormatEnum) -> usize {
+	match format {
+		// Packed formats SDL can't give a whole byte count for
+		PixelFormatEnum::Unknown
+			| PixelFormatEnum::Index1LSB
+			| PixelFormatEnum::Index1MSB
+			| PixelFormatEnum::Index4LSB
+			| PixelFormatEnum::Index4MSB => 1,
+		f => f.byte_size_per_pixel(),
+	}
+}
+
+// Same file, same key, however the path was spelled
+fn cache_key(path: &Path) -> PathBuf {
+	fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path)
+		.and_then(|m| m.modified())
+		.ok()
+}
+
+fn texture_bytes(texture: &Texture) -> usize {
+	let query = texture.query();
+	(query.width as usize) * (query.height as usize) * bytes_per_pixel(query.format)
+}
+
+impl<'a> AssetManager<'a> {
+	pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> AssetManager<'a> {
+		AssetManager {
+			texture_creator,
+			slots: Vec::new(),
+			by_path: HashMap::new(),
+			watch_interval: None,
+			last_poll: Instant::now(),
+			reload_errors: HashMap::new(),
+		}
+	}
+
+	// Loads the texture at `path`, or hands back the existing handle if it
+	// has already been loaded
+	pub fn load_texture<P: AsRef<Path>>(&mut self, path: P) -> Result<TextureHandle, Error> {
+		let path = path.as_ref();
+		let key = cache_key(path);
+
+		if let Some(&handle) = self.by_path.get(&key) {
+			return Ok(handle);
+		}
+
+		let texture = self.texture_creator.load_texture(path)
+			.map_err(|e| Error::asset(path, e))?;
+
+		let entry = Entry {
+			path: path.to_path_buf(),
+			key: key.clone(),
+			bytes: texture_bytes(&texture),
+			texture,
+			modified: modified(path),
+		};
+
+		// Reuse a slot freed by unload() if there is one
+		let index = match self.slots.iter().position(|s| s.entry.is_none()) {
+			Some(i) => {
+				self.slots[i].generation += 1;
+				self.slots[i].entry = Some(entry);
+				i
+			},
+			None => {
+				self.slots.push(Slot {
+					generation: 0,
+					entry: Some(entry),
+				});
+				self.slots.len() - 1
+			},
+		};
+
+		let handle = TextureHandle {
+			index,
+			generation: self.slots[index].generation,
+		};
+		self.by_path.insert(key, handle);
+
+		Ok(handle)
+	}
+
+	fn entry(&self, handle: TextureHandle) -> Option<&Entry<'a>> {
+		self.slots.get(handle.index)
+			.filter(|s| s.generation == handle.generation)
+			.and_then(|s| s.entry.as_ref())
+	}
+
+	// None if the texture has been unloaded
+	pub fn texture(&self, handle: TextureHandle) -> Option<&Texture<'a>> {
+		self.entry(handle).map(|e| &e.texture)
+	}
+
+	pub fn path(&self, handle: TextureHandle) -> Option<&Path> {
+		self.entry(handle).map(|e| e.path.as_path())
+	}
+
+	// Handle for an already-loaded path, without loading it
+	pub fn handle<P: AsRef<Path>>(&self, path: P) -> Option<TextureHandle> {
+		self.by_path.get(&cache_key(path.as_ref())).copied()
+	}
+
+	// Same as wincan.copy(), but by handle
+	pub fn draw<R1, R2>(
+		&self,
+		wincan: &mut WindowCanvas,
+		handle: TextureHandle,
+		src: R1,
+		dst: R2,
+	) -> Result<(), Error>
+		where
+			R1: Into<Option<Rect>>,
+			R2: Into<Option<Rect>>,
+	{
+		let entry = self.entry(handle)
+			.ok_or_else(|| Error::Other("Drawing a texture that has been unloaded".to_string()))?;
+
+		wincan.copy(&entry.texture, src, dst)?;
+		Ok(())
+	}
+
+	// Frees the texture. Its handle (and any copies of it) stop working.
+	pub fn unload(&mut self, handle: TextureHandle) -> bool {
+		let slot = match self.slots.get_mut(handle.index) {
+			Some(s) if s.generation == handle.generation => s,
+			_ => return false,
+		};
+
+		match slot.entry.take() {
+			Some(entry) => {
+				self.by_path.remove(&entry.key);
+				self.reload_errors.remove(&handle.index);
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn unload_all(&mut self) {
+		for slot in self.slots.iter_mut() {
+			slot.entry = None;
+		}
+		self.by_path.clear();
+		self.reload_errors.clear();
+	}
+
+	// Number of textures currently loaded
+	pub fn len(&self) -> usize {
+		self.by_path.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.by_path.is_empty()
+	}
+
+	// Estimated bytes of texture memory in use, from each texture's size
+	// and pixel format
+	pub fn memory_usage(&self) -> usize {
+		self.slots.iter()
+			.filter_map(|s| s.entry.as_ref())
+			.map(|e| e.bytes)
+			.sum()
+	}
+
+	// Start checking loaded files for changes every `interval` (or stop,
+	// with None). Checks happen in reload_changed().
+	pub fn watch(&mut self, interval: Option<Duration>) {
+		self.watch_interval = interval;
+		self.last_poll = Instant::now();
+	}
+
+	// Call once per frame, between frames. If watching and it's time to
+	// check, re-decodes any texture whose file has changed. Handles stay the
+	// same, they just get the new texture. Returns how many were reloaded.
+	//
+	// A file that fails to decode (e.g., half-saved) keeps its old texture
+	// and shows up in reload_errors() until it loads again.
+	pub fn reload_changed(&mut self) -> usize {
+		let interval = match self.watch_interval {
+			Some(i) => i,
+			None => return 0,
+		};
+		if self.last_poll.elapsed() < interval {
+			return 0;
+		}
+		self.last_poll = Instant::now();
+
+		let mut reloaded = 0;
+		for (index, slot) in self.slots.iter_mut().enumerate() {
+			let entry = match &mut slot.entry {
+				Some(e) => e,
+				None => continue,
+			};
+
+			let current = modified(&entry.path);
+			if current.is_none() || current == entry.modified {
+				continue;
+			}
+			// Don't retry a bad file until it's saved again
+			entry.modified = current;
+
+			match self.texture_creator.load_texture(&entry.path) {
+				Ok(texture) => {
+					entry.bytes = texture_bytes(&texture);
+					entry.texture = texture;
+					self.reload_errors.remove(&index);
+					reloaded += 1;
+				},
+				Err(e) => {
+					self.reload_errors.insert(index, Error::asset(&entry.path, e));
+				},
+			}
+		}
+
+		reloaded
+	}
+
+	pub fn reload_errors(&self) -> impl Iterator<Item = &Error> {
+		self.reload_errors.values()
+	}
+
+	// Outlines the screen in red while any reload has failed, and lists
+	// which files failed and why, so a bad file is noticed without taking the
+	// demo down. Draw it last.
+	pub fn draw_error_overlay(
+		&self,
+		wincan: &mut WindowCanvas,
+		text: &mut Text,
+		font: FontHandle,
+	) -> Result<(), Error> {
+		if self.reload_errors.is_empty() {
+			return Ok(());
+		}
+
+		let (w, h) = wincan.output_size()?;
+		let border = 8;
+		let pad = 6;
+
+		// Slot order, so the list doesn't shuffle from frame to frame
+		let mut errors: Vec<_> = self.reload_errors.iter().collect();
+		errors.sort_by_key(|&(&index, _)| index);
+		let lines: Vec<String> = errors.iter()
+			.map(|(_, e)| match e.source() {
+				Some(source) => format!("{}: {}", e, source),
+				None => e.to_string(),
+			})
+			.collect();
+
+		let style = TextStyle::new(Color::WHITE).wrap(w.saturating_sub(2 * (border + pad)).max(1));
+		let mut panel_h = pad;
+		for line in lines.iter() {
+			panel_h += text.measure(font, line, &style)?.1;
+		}
+		panel_h += pad;
+
+		let old_blend = wincan.blend_mode();
+		wincan.set_blend_mode(BlendMode::Blend);
+		wincan.set_draw_color(Color::RGBA(255, 0, 0, 192));
+		wincan.fill_rects(&[
+			Rect::new(0, 0, w, border),
+			Rect::new(0, h.saturating_sub(border) as i32, w, border),
+			Rect::new(0, 0, border, h),
+			Rect::new(w.saturating_sub(border) as i32, 0, border, h),
+		])?;
+
+		wincan.set_draw_color(Color::RGBA(0, 0, 0, 192));
+		wincan.fill_rect(Rect::new(border as i32, border as i32, w.saturating_sub(2 * border).max(1), panel_h))?;
+		wincan.set_blend_mode(old_blend);
+
+		let mut y = (border + pad) as i32;
+		for line in lines.iter() {
+			let drawn = text.draw(wincan, font, line, (border + pad) as i32, y, &style)?;
+			y += drawn.height() as i32;
+		}
+
+		Ok(())
+	}
+}
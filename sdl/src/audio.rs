@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sdl2::AudioSubsystem;
+use sdl2::mixer;
+use sdl2::mixer::{Channel, Chunk, Group, InitFlag, Sdl2MixerContext, MAX_VOLUME};
+
+use crate::SDLCore;
+use crate::Error;
+
+// Music plays on channels of its own, two so one track can fade out while
+// the next fades in
+const MUSIC_CHANNELS: [i32; 2] = [0, 1];
+// Sound effect channels are tagged with this group
+const EFFECTS: Group = Group(1);
+
+pub const DEFAULT_EFFECT_CHANNELS: usize = 16;
+
+// A loaded sound, for play() or play_music()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundHandle(usize);
+
+// One of the channels sound effects play on, from play()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundChannel(i32);
+
+// A music channel's level, which moves toward target at rate per second
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Fade {
+	level: f32,
+	target: f32,
+	rate: f32,
+}
+
+impl Fade {
+	const SILENT: Fade = Fade { level: 0.0, target: 0.0, rate: 0.0 };
+
+	// Per second to get there in `secs`, or all at once
+	fn rate(secs: f32) -> f32 {
+		if secs > 0.0 { 1.0 / secs } else { f32::INFINITY }
+	}
+
+	// True if the level moved
+	fn step(&mut self, dt: f32) -> bool {
+		if self.level == self.target {
+			return false;
+		}
+
+		let step = self.rate * dt;
+		self.level = if self.level < self.target {
+			(self.level + step).min(self.target)
+		}
+		else {
+			(self.level - step).max(self.target)
+		};
+		true
+	}
+}
+
+// Levels of the two music channels, and which one has the current track.
+// Just the arithmetic; Audio does the playing and halting.
+#[derive(Clone, Debug, PartialEq)]
+struct Crossfade {
+	// Index into MUSIC_CHANNELS
+	current: usize,
+	fades: [Fade; 2],
+}
+
+impl Crossfade {
+	fn new() -> Crossfade {
+		Crossfade {
+			current: 0,
+			fades: [Fade::SILENT; 2],
+		}
+	}
+
+	fn level(&self, i: usize) -> f32 {
+		self.fades[i].level
+	}
+
+	// Whether the current track is meant to be heard, i.e. not fading out
+	fn is_on(&self) -> bool {
+		self.fades[self.current].target > 0.0
+	}
+
+	// Starts the current track fading out over `secs`. True if that cut it
+	// off straight away, so its channel should be halted now.
+	fn fade_out(&mut self, secs: f32) -> bool {
+		let current = &mut self.fades[self.current];
+		current.target = 0.0;
+		current.rate = Fade::rate(secs);
+
+		if secs <= 0.0 {
+			current.level = 0.0;
+		}
+		secs <= 0.0
+	}
+
+	// Makes the other channel current, fading in from silence over `secs`.
+	// Returns the new current channel. Call fade_out() first.
+	fn fade_in(&mut self, secs: f32) -> usize {
+		self.current = 1 - self.current;
+		self.fades[self.current] = Fade {
+			level: if secs > 0.0 { 0.0 } else { 1.0 },
+			target: 1.0,
+			rate: Fade::rate(secs),
+		};
+		self.current
+	}
+
+	// Moves both fades on by dt seconds. Says which channels' levels
+	// changed.
+	fn update(&mut self, dt: f32) -> [bool; 2] {
+		if dt <= 0.0 {
+			return [false; 2];
+		}
+
+		[self.fades[0].step(dt), self.fades[1].step(dt)]
+	}
+}
+
+// Same file, same key, however the path was spelled
+fn cache_key(path: &Path) -> PathBuf {
+	fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// 0.0 - 1.0 to SDL_mixer's 0 - 128
+fn mixer_volume(v: f32) -> i32 {
+	(v.clamp(0.0, 1.0) * MAX_VOLUME as f32).round() as i32
+}
+
+// Plays sound effects and music through SDL_mixer. Create one after SDLCore:
+//
+//     let mut audio = Audio::new(&self.core)?;
+//     let blip = audio.load("sounds/blip.wav")?;
+//     audio.play(blip)?;
+//
+// Headless SDLCores use SDL's dummy audio driver, so everything works (in
+// silence) without a sound card. Call update() once a frame for music fades.
+pub struct Audio {
+	sounds: Vec<Chunk>,
+	by_path: HashMap<PathBuf, SoundHandle>,
+	// Volume asked for on each effect channel, before master and mute
+	channel_volumes: HashMap<i32, f32>,
+	master: f32,
+	music_volume: f32,
+	muted: bool,
+	music: Crossfade,
+	// Only dropped after the mixer is closed, in Drop
+	_mixer: Sdl2MixerContext,
+	_subsystem: AudioSubsystem,
+}
+
+impl Audio {
+	pub fn new(core: &SDLCore) -> Result<Audio, Error> {
+		Audio::with_channels(core, DEFAULT_EFFECT_CHANNELS)
+	}
+
+	// `effect_channels` is how many sound effects can play at once. Past
+	// that, the oldest one is cut off.
+	pub fn with_channels(core: &SDLCore, effect_channels: usize) -> Result<Audio, Error> {
+		let subsystem = core.sdl_cxt.audio().map_err(Error::init)?;
+
+		// Just WAV is always there; OGG and friends only if SDL_mixer was
+		// built with them, and failing to find them is fine
+		let mixer = mixer::init(InitFlag::OGG).or_else(|_| mixer::init(InitFlag::empty()))
+			.map_err(Error::audio)?;
+		mixer::open_audio(mixer::DEFAULT_FREQUENCY, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, 1024)
+			.map_err(Error::audio)?;
+
+		let total = MUSIC_CHANNELS.len() as i32 + effect_channels.max(1) as i32;
+		mixer::allocate_channels(total);
+		// Keeps Channel::all() from ever picking a music channel
+		mixer::reserve_channels(MUSIC_CHANNELS.len() as i32);
+		EFFECTS.add_channels_range(MUSIC_CHANNELS.len() as i32, total - 1);
+
+		let audio = Audio {
+			sounds: Vec::new(),
+			by_path: HashMap::new(),
+			channel_volumes: HashMap::new(),
+			master: 1.0,
+			music_volume: 1.0,
+			muted: false,
+			music: Crossfade::new(),
+			_mixer: mixer,
+			_subsystem: subsystem,
+		};
+		audio.apply_volumes();
+
+		Ok(audio)
+	}
+
+	// Loads the sound at `path` (WAV always, other formats if SDL_mixer
+	// supports them), or hands back the existing handle if it has already
+	// been loaded. Music is loaded the same way.
+	pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<SoundHandle, Error> {
+		let path = path.as_ref();
+		let key = cache_key(path);
+
+		if let Some(&handle) = self.by_path.get(&key) {
+			return Ok(handle);
+		}
+
+		let chunk = Chunk::from_file(path).map_err(|e| Error::asset(path, e))?;
+		let handle = SoundHandle(self.sounds.len());
+		self.sounds.push(chunk);
+		self.by_path.insert(key, handle);
+
+		Ok(handle)
+	}
+
+	fn chunk(&self, sound: SoundHandle) -> Result<&Chunk, Error> {
+		self.sounds.get(sound.0)
+			.ok_or_else(|| Error::Other(format!("No sound loaded for {:?}", sound)))
+	}
+
+	// Plays a sound effect once, at full volume, centered
+	pub fn play(&mut self, sound: SoundHandle) -> Result<SoundChannel, Error> {
+		self.play_with(sound, 1.0, 0.0)
+	}
+
+	// Volume 0.0 - 1.0, and pan from -1.0 (left) through 0.0 to 1.0 (right)
+	pub fn play_with(&mut self, sound: SoundHandle, volume: f32, pan: f32) -> Result<SoundChannel, Error> {
+		// Cut off the oldest effect if they're all busy
+		let channel = EFFECTS.find_available()
+			.or_else(|| EFFECTS.find_oldest())
+			.unwrap_or(Channel(MUSIC_CHANNELS.len() as i32));
+
+		let channel = SoundChannel(channel.0);
+		self.set_volume(channel, volume);
+		self.set_pan(channel, pan)?;
+
+		Channel(channel.0).play(self.chunk(sound)?, 0).map_err(Error::audio)?;
+		Ok(channel)
+	}
+
+	pub fn set_volume(&mut self, channel: SoundChannel, volume: f32) {
+		let volume = volume.clamp(0.0, 1.0);
+		self.channel_volumes.insert(channel.0, volume);
+		Channel(channel.0).set_volume(mixer_volume(self.effective(volume)));
+	}
+
+	pub fn set_pan(&mut self, channel: SoundChannel, pan: f32) -> Result<(), Error> {
+		let pan = pan.clamp(-1.0, 1.0);
+		let left = (255.0 * (1.0 - pan).min(1.0)).round() as u8;
+		let right = (255.0 * (1.0 + pan).min(1.0)).round() as u8;
+
+		Channel(channel.0).set_panning(left, right).map_err(Error::audio)
+	}
+
+	pub fn stop(&mut self, channel: SoundChannel) {
+		Channel(channel.0).halt();
+	}
+
+	pub fn is_playing(&self, channel: SoundChannel) -> bool {
+		Channel(channel.0).is_playing()
+	}
+
+	// Switches to a new music track, fading it in and whatever was playing
+	// out over `fade` seconds (0.0 to cut straight over). Loops forever if
+	// `looping`, otherwise plays once.
+	pub fn play_music(&mut self, sound: SoundHandle, looping: bool, fade: f32) -> Result<(), Error> {
+		self.stop_music(fade);
+
+		let channel = Channel(MUSIC_CHANNELS[self.music.fade_in(fade)]);
+		channel.halt();
+		self.apply_volumes();
+
+		channel.play(self.chunk(sound)?, if looping { -1 } else { 0 }).map_err(Error::audio)?;
+		Ok(())
+	}
+
+	// Fades the current track out over `fade` seconds
+	pub fn stop_music(&mut self, fade: f32) {
+		if self.music.fade_out(fade) {
+			Channel(MUSIC_CHANNELS[self.music.current]).halt();
+		}
+		self.apply_volumes();
+	}
+
+	pub fn is_music_playing(&self) -> bool {
+		self.music.is_on() && Channel(MUSIC_CHANNELS[self.music.current]).is_playing()
+	}
+
+	pub fn set_music_volume(&mut self, volume: f32) {
+		self.music_volume = volume.clamp(0.0, 1.0);
+		self.apply_volumes();
+	}
+
+	pub fn music_volume(&self) -> f32 {
+		self.music_volume
+	}
+
+	// Scales every sound and the music
+	pub fn set_master_volume(&mut self, volume: f32) {
+		self.master = volume.clamp(0.0, 1.0);
+		self.apply_volumes();
+	}
+
+	pub fn master_volume(&self) -> f32 {
+		self.master
+	}
+
+	// Silences everything without losing the volume settings
+	pub fn set_muted(&mut self, muted: bool) {
+		self.muted = muted;
+		self.apply_volumes();
+	}
+
+	pub fn is_muted(&self) -> bool {
+		self.muted
+	}
+
+	// Moves music fades on by dt seconds
+	pub fn update(&mut self, dt: f32) {
+		let changed = self.music.update(dt);
+
+		for (i, &c) in changed.iter().enumerate() {
+			// Faded all the way out, so stop it for real
+			if c && self.music.level(i) == 0.0 {
+				Channel(MUSIC_CHANNELS[i]).halt();
+			}
+		}
+
+		if changed.contains(&true) {
+			self.apply_volumes();
+		}
+	}
+
+	fn effective(&self, volume: f32) -> f32 {
+		if self.muted {
+			0.0
+		}
+		else {
+			volume * self.master
+		}
+	}
+
+	fn apply_volumes(&self) {
+		for (i, &ch) in MUSIC_CHANNELS.iter().enumerate() {
+			let v = self.effective(self.music.level(i) * self.music_volume);
+			Channel(ch).set_volume(mixer_volume(v));
+		}
+
+		for (&ch, &v) in self.channel_volumes.iter() {
+			Channel(ch).set_volume(mixer_volume(self.effective(v)));
+		}
+	}
+}
+
+impl Drop for Audio {
+	fn drop(&mut self) {
+		// Chunks can't be freed while they're playing, and the device has
+		// to close before SDL_mixer quits
+		Channel::all().halt();
+		self.sounds.clear();
+		mixer::close_audio();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Steps until nothing changes, returning how many steps it took and how
+	// many times each channel finished fading out
+	fn settle(music: &mut Crossfade, dt: f32) -> (usize, [usize; 2]) {
+		let mut steps = 0;
+		let mut stopped = [0; 2];
+
+		loop {
+			let changed = music.update(dt);
+			if !changed.contains(&true) {
+				return (steps, stopped);
+			}
+			steps += 1;
+
+			for i in 0..2 {
+				if changed[i] && music.level(i) == 0.0 {
+					stopped[i] += 1;
+				}
+				assert!((0.0..=1.0).contains(&music.level(i)), "{:?}", music);
+			}
+			assert!(steps < 10_000, "never settled: {:?}", music);
+		}
+	}
+
+	#[test]
+	fn fades_in_to_full_volume() {
+		let mut music = Crossfade::new();
+		music.fade_out(2.0);
+		let ch = music.fade_in(2.0);
+		assert_eq!(music.level(ch), 0.0);
+		assert!(music.is_on());
+
+		music.update(0.5);
+		assert_eq!(music.level(ch), 0.25);
+
+		// Uneven steps still land exactly on 1.0, without overshooting
+		let (steps, _) = settle(&mut music, 0.3);
+		assert_eq!(steps, 5);
+		assert_eq!(music.level(ch), 1.0);
+	}
+
+	#[test]
+	fn crossfade_swaps_channels_once() {
+		let mut music = Crossfade::new();
+		music.fade_out(0.0);
+		let first = music.fade_in(0.0);
+		assert_eq!(music.level(first), 1.0);
+
+		// Second track: the first fades out while it fades in
+		assert!(!music.fade_out(1.0));
+		let second = music.fade_in(1.0);
+		assert_ne!(first, second);
+		assert_eq!(music.current, second);
+
+		music.update(0.5);
+		assert_eq!((music.level(first), music.level(second)), (0.5, 0.5));
+
+		let (_, stopped) = settle(&mut music, 0.1);
+		assert_eq!((music.level(first), music.level(second)), (0.0, 1.0));
+		// Only the old track is stopped, and only once
+		assert_eq!(stopped[first], 1);
+		assert_eq!(stopped[second], 0);
+		// Updating never moves the current track
+		assert_eq!(music.current, second);
+
+		// A third goes back to the first channel
+		music.fade_out(1.0);
+		assert_eq!(music.fade_in(1.0), first);
+	}
+
+	#[test]
+	fn cuts_straight_over_without_a_fade() {
+		let mut music = Crossfade::new();
+		music.fade_in(0.0);
+
+		// Tells Audio to halt the old track now rather than in update()
+		let old = music.current;
+		assert!(music.fade_out(0.0));
+		let new = music.fade_in(0.0);
+		assert_eq!((music.level(old), music.level(new)), (0.0, 1.0));
+		assert_eq!(music.update(1.0 / 60.0), [false; 2]);
+	}
+
+	#[test]
+	fn stop_fades_out_without_swapping() {
+		let mut music = Crossfade::new();
+		let ch = music.fade_in(0.0);
+
+		assert!(!music.fade_out(0.5));
+		assert!(!music.is_on());
+		let (_, stopped) = settle(&mut music, 0.2);
+		assert_eq!(music.level(ch), 0.0);
+		assert_eq!(stopped[ch], 1);
+		assert_eq!(music.current, ch);
+	}
+
+	#[test]
+	fn no_time_no_change() {
+		let mut music = Crossfade::new();
+		music.fade_in(1.0);
+		assert_eq!(music.update(0.0), [false; 2]);
+		assert_eq!(music.update(-1.0), [false; 2]);
+		assert_eq!(music.level(music.current), 0.0);
+	}
+
+	#[test]
+	fn volumes_map_to_mixer_range() {
+		assert_eq!(mixer_volume(0.0), 0);
+		assert_eq!(mixer_volume(0.5), 64);
+		assert_eq!(mixer_volume(1.0), MAX_VOLUME);
+		assert_eq!(mixer_volume(2.0), MAX_VOLUME);
+		assert_eq!(mixer_volume(-1.0), 0);
+	}
+}
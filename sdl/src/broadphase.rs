@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasherDefault, Hash};
+
+use crate::collision::Aabb;
+
+// Same hash every run, so the same inserts and moves hand back pairs in the
+// same order, and replays stay deterministic
+type Fixed = BuildHasherDefault<DefaultHasher>;
+
+// Cells from (x0, y0) to (x1, y1), inclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CellRange {
+	x0: i32,
+	y0: i32,
+	x1: i32,
+	y1: i32,
+}
+
+impl CellRange {
+	fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+		(self.y0..=self.y1).flat_map(move |y| (self.x0..=self.x1).map(move |x| (x, y)))
+	}
+
+	// The first cell both ranges cover. Something found through more than
+	// one cell only counts in this one, so it isn't reported twice.
+	fn first_shared(self, other: CellRange) -> (i32, i32) {
+		(self.x0.max(other.x0), self.y0.max(other.y0))
+	}
+}
+
+struct Entry {
+	aabb: Aabb,
+	range: CellRange,
+}
+
+// Bounds that touch count here, so nothing that might be colliding is missed
+fn overlaps(a: &Aabb, b: &Aabb) -> bool {
+	a.left() <= b.right() && a.right() >= b.left() && a.top() <= b.bottom() && a.bottom() >= b.top()
+}
+
+// A uniform grid of square cells, hashed so the world can be any size. Each
+// collider is filed under every cell its bounds touch, so finding what's near
+// something only looks at a few cells instead of every collider: use pairs()
+// for everything that might be colliding, or query() for what's in an area,
+// then test those with collision:: for real.
+//
+// Keys are whatever identifies colliders in the game, like an index into a
+// Vec of entities. Cells work best about the size of a typical collider.
+pub struct SpatialHash<K> {
+	cell_size: f32,
+	cells: HashMap<(i32, i32), Vec<K>, Fixed>,
+	entries: HashMap<K, Entry, Fixed>,
+}
+
+impl<K: Copy + Eq + Hash> SpatialHash<K> {
+	pub fn new(cell_size: f32) -> SpatialHash<K> {
+		SpatialHash {
+			cell_size: cell_size.max(1.0),
+			cells: HashMap::default(),
+			entries: HashMap::default(),
+		}
+	}
+
+	pub fn cell_size(&self) -> f32 {
+		self.cell_size
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.cells.clear();
+		self.entries.clear();
+	}
+
+	fn range(&self, aabb: &Aabb) -> CellRange {
+		let cell = |v: f32| (v / self.cell_size).floor() as i32;
+		CellRange {
+			x0: cell(aabb.left()),
+			y0: cell(aabb.top()),
+			x1: cell(aabb.right()),
+			y1: cell(aabb.bottom()),
+		}
+	}
+
+	fn file(&mut self, key: K, range: CellRange) {
+		for c in range.cells() {
+			self.cells.entry(c).or_default().push(key);
+		}
+	}
+
+	fn unfile(&mut self, key: K, range: CellRange) {
+		for c in range.cells() {
+			if let Some(keys) = self.cells.get_mut(&c) {
+				if let Some(i) = keys.iter().position(|&k| k == key) {
+					keys.swap_remove(i);
+				}
+				if keys.is_empty() {
+					self.cells.remove(&c);
+				}
+			}
+		}
+	}
+
+	// Adds a collider, or moves it if the key is already in
+	pub fn insert(&mut self, key: K, aabb: Aabb) {
+		if self.entries.contains_key(&key) {
+			self.update(key, aabb);
+			return;
+		}
+
+		let range = self.range(&aabb);
+		self.file(key, range);
+		self.entries.insert(key, Entry { aabb, range });
+	}
+
+	// For colliders that moved. Only touches the cells if it moved into
+	// different ones. False if the key isn't in.
+	pub fn update(&mut self, key: K, aabb: Aabb) -> bool {
+		let range = self.range(&aabb);
+
+		let old = match self.entries.get_mut(&key) {
+			Some(entry) => {
+				entry.aabb = aabb;
+				std::mem::replace(&mut entry.range, range)
+			},
+			None => return false,
+		};
+
+		if old != range {
+			self.unfile(key, old);
+			self.file(key, range);
+		}
+		true
+	}
+
+	pub fn remove(&mut self, key: K) -> bool {
+		match self.entries.remove(&key) {
+			Some(entry) => {
+				self.unfile(key, entry.range);
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn contains(&self, key: K) -> bool {
+		self.entries.contains_key(&key)
+	}
+
+	pub fn aabb(&self, key: K) -> Option<Aabb> {
+		self.entries.get(&key).map(|e| e.aabb)
+	}
+
+	// Every collider whose bounds overlap `area`, once each
+	pub fn query(&self, area: &Aabb) -> Vec<K> {
+		let range = self.range(area);
+
+		let mut found = Vec::new();
+		for c in range.cells() {
+			for &key in self.cells.get(&c).into_iter().flatten() {
+				let entry = &self.entries[&key];
+				if entry.range.first_shared(range) == c && overlaps(&entry.aabb, area) {
+					found.push(key);
+				}
+			}
+		}
+		found
+	}
+
+	pub fn query_point(&self, p: (f32, f32)) -> Vec<K> {
+		self.query(&Aabb::new(p.0, p.1, 0.0, 0.0))
+	}
+
+	// Every pair of colliders whose bounds overlap, once each, in no
+	// particular order. Their shapes may still miss; that's for the
+	// narrowphase (collision::) to say.
+	pub fn pairs(&self) -> Vec<(K, K)> {
+		let mut pairs = Vec::new();
+
+		for (&c, keys) in self.cells.iter() {
+			for (i, &a) in keys.iter().enumerate() {
+				let ea = &self.entries[&a];
+				for &b in &keys[i + 1..] {
+					let eb = &self.entries[&b];
+					if ea.range.first_shared(eb.range) == c && overlaps(&ea.aabb, &eb.aabb) {
+						pairs.push((a, b));
+					}
+				}
+			}
+		}
+		pairs
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use rand::{Rng, SeedableRng};
+	use rand::rngs::StdRng;
+
+	// Both ways round, sorted, so they compare as sets
+	fn sorted(pairs: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+		let mut pairs: Vec<_> = pairs.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect();
+		pairs.sort();
+		pairs
+	}
+
+	fn brute_force(boxes: &HashMap<usize, Aabb>) -> Vec<(usize, usize)> {
+		let mut pairs = Vec::new();
+		for (&a, aabb_a) in boxes.iter() {
+			for (&b, aabb_b) in boxes.iter() {
+				if a < b && overlaps(aabb_a, aabb_b) {
+					pairs.push((a, b));
+				}
+			}
+		}
+		sorted(pairs)
+	}
+
+	fn check(hash: &SpatialHash<usize>, boxes: &HashMap<usize, Aabb>) {
+		let expected = brute_force(boxes);
+		let found = sorted(hash.pairs());
+
+		let mut unique = found.clone();
+		unique.dedup();
+		assert_eq!(found, unique, "a pair was reported twice");
+		assert_eq!(found, expected);
+	}
+
+	fn random_box(rng: &mut StdRng) -> Aabb {
+		// Up to several cells across, some of them off the top left
+		let w = rng.gen_range(1.0..80.0);
+		let h = rng.gen_range(1.0..80.0);
+		Aabb::new(rng.gen_range(-100.0..300.0), rng.gen_range(-100.0..300.0), w, h)
+	}
+
+	#[test]
+	fn pairs_match_brute_force() {
+		let mut rng = StdRng::seed_from_u64(20);
+		let mut hash = SpatialHash::new(16.0);
+		let mut boxes = HashMap::new();
+
+		for i in 0..300 {
+			let aabb = random_box(&mut rng);
+			hash.insert(i, aabb);
+			boxes.insert(i, aabb);
+		}
+		check(&hash, &boxes);
+
+		// Moved, some far and some within their cells
+		for i in 0..300 {
+			let aabb = if i % 2 == 0 {
+				random_box(&mut rng)
+			}
+			else {
+				let a = boxes[&i];
+				Aabb::new(a.x + rng.gen_range(-2.0..2.0), a.y + rng.gen_range(-2.0..2.0), a.w, a.h)
+			};
+			assert!(hash.update(i, aabb));
+			boxes.insert(i, aabb);
+		}
+		check(&hash, &boxes);
+
+		for i in (0..300).step_by(3) {
+			assert!(hash.remove(i));
+			boxes.remove(&i);
+		}
+		check(&hash, &boxes);
+	}
+
+	#[test]
+	fn boxes_sharing_several_cells_pair_once() {
+		let mut hash = SpatialHash::new(10.0);
+		// Both cover the same 3x3 cells, and a third sits inside them
+		hash.insert(0, Aabb::new(1.0, 1.0, 25.0, 25.0));
+		hash.insert(1, Aabb::new(4.0, 4.0, 25.0, 25.0));
+		hash.insert(2, Aabb::new(12.0, 12.0, 2.0, 2.0));
+		// Touching 0's bottom right corner, which still counts
+		hash.insert(3, Aabb::new(26.0, 26.0, 5.0, 5.0));
+
+		assert_eq!(sorted(hash.pairs()), vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3)]);
+
+		let mut found = hash.query(&Aabb::new(0.0, 0.0, 40.0, 40.0));
+		found.sort();
+		assert_eq!(found, vec![0, 1, 2, 3]);
+	}
+}
@@ -0,0 +1,309 @@
+use std::env;
+use std::path::PathBuf;
+
+use sdl2::hint;
+use sdl2::surface::Surface;
+use sdl2::image::LoadSurface;
+
+use crate::SDLCore;
+use crate::Camera;
+use crate::Error;
+use crate::capture;
+use crate::pacer::FramePacer;
+use crate::replay;
+use crate::replay::{Recording, RECORD_VAR, REPLAY_VAR};
+use crate::HEADLESS_VAR;
+use crate::profiler;
+use crate::profiler::PROFILE_VAR;
+
+// Which of SDL's renderers to draw with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Renderer {
+	Accelerated,
+	Software,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fullscreen {
+	Off,
+	// Change the display mode to match the window size
+	Exclusive,
+	// Borderless window covering the desktop at its current resolution
+	Desktop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+	// Let the window manager decide
+	Default,
+	Centered,
+	At(i32, i32),
+}
+
+// Collects window and renderer settings, then builds an SDLCore from them.
+// Start one with SDLCore::builder().
+pub struct SDLCoreBuilder {
+	title: String,
+	width: u32,
+	height: u32,
+	vsync: bool,
+	frame_limit: Option<u32>,
+	renderer: Renderer,
+	headless: bool,
+	fullscreen: Fullscreen,
+	resizable: bool,
+	borderless: bool,
+	high_dpi: bool,
+	position: Position,
+	logical_size: Option<(u32, u32)>,
+	icon: Option<PathBuf>,
+	record: Option<PathBuf>,
+	replay: Option<PathBuf>,
+	profile: bool,
+	profile_to: Option<PathBuf>,
+}
+
+impl SDLCoreBuilder {
+	pub fn new(title: &str, width: u32, height: u32) -> SDLCoreBuilder {
+		SDLCoreBuilder {
+			title: title.to_string(),
+			width,
+			height,
+			vsync: false,
+			frame_limit: None,
+			renderer: Renderer::Accelerated,
+			headless: false,
+			fullscreen: Fullscreen::Off,
+			resizable: false,
+			borderless: false,
+			high_dpi: false,
+			position: Position::Default,
+			logical_size: None,
+			icon: None,
+			record: None,
+			replay: None,
+			profile: false,
+			profile_to: None,
+		}
+	}
+
+	pub fn vsync(mut self, vsync: bool) -> SDLCoreBuilder {
+		self.vsync = vsync;
+		self
+	}
+
+	// Hold present() to `fps` frames a second in software, with vsync off or
+	// as a cap below the display's refresh rate. See pacer::FramePacer.
+	// Kept but not waited on when headless, see SDLCore::set_frame_limit.
+	pub fn frame_limit(mut self, fps: u32) -> SDLCoreBuilder {
+		self.frame_limit = Some(fps);
+		self
+	}
+
+	pub fn renderer(mut self, renderer: Renderer) -> SDLCoreBuilder {
+		self.renderer = renderer;
+		self
+	}
+
+	// Dummy video driver and software renderer, see SDLCore::init_headless.
+	// The drivers are picked with SDL hints, which last for the rest of the
+	// process and can't be unset: every SDLCore built after a headless one
+	// is headless too, whatever its builder says. Keep headless runs in
+	// their own process (each cargo test binary is one) or build them last.
+	pub fn headless(mut self, headless: bool) -> SDLCoreBuilder {
+		self.headless = headless;
+		self
+	}
+
+	pub fn fullscreen(mut self, fullscreen: Fullscreen) -> SDLCoreBuilder {
+		self.fullscreen = fullscreen;
+		self
+	}
+
+	pub fn resizable(mut self, resizable: bool) -> SDLCoreBuilder {
+		self.resizable = resizable;
+		self
+	}
+
+	pub fn borderless(mut self, borderless: bool) -> SDLCoreBuilder {
+		self.borderless = borderless;
+		self
+	}
+
+	pub fn high_dpi(mut self, high_dpi: bool) -> SDLCoreBuilder {
+		self.high_dpi = high_dpi;
+		self
+	}
+
+	pub fn position(mut self, position: Position) -> SDLCoreBuilder {
+		self.position = position;
+		self
+	}
+
+	// Draw as if the window were width x height, and let SDL scale the
+	// result to the real window size
+	pub fn logical_size(mut self, width: u32, height: u32) -> SDLCoreBuilder {
+		self.logical_size = Some((width, height));
+		self
+	}
+
+	pub fn icon<P: Into<PathBuf>>(mut self, path: P) -> SDLCoreBuilder {
+		self.icon = Some(path.into());
+		self
+	}
+
+	// Save this session's input to `path` when the SDLCore is dropped
+	pub fn record<P: Into<PathBuf>>(mut self, path: P) -> SDLCoreBuilder {
+		self.record = Some(path.into());
+		self
+	}
+
+	// Play back input recorded to `path`, instead of reading real input.
+	// Wins over record().
+	pub fn replay<P: Into<PathBuf>>(mut self, path: P) -> SDLCoreBuilder {
+		self.replay = Some(path.into());
+		self
+	}
+
+	// Time every frame, and print a summary of where the time went when the
+	// SDLCore is dropped. See profiler::Profiler.
+	pub fn profile(mut self, profile: bool) -> SDLCoreBuilder {
+		self.profile = profile;
+		self
+	}
+
+	// Same as profile(true), and also save every frame's timings to `path`:
+	// a Chrome trace if it ends in .json, CSV otherwise
+	pub fn profile_to<P: Into<PathBuf>>(mut self, path: P) -> SDLCoreBuilder {
+		self.profile = true;
+		self.profile_to = Some(path.into());
+		self
+	}
+
+	pub fn build(self) -> Result<SDLCore, Error> {
+		// Loaded first, so a bad recording fails before any window opens
+		let replay_with = match replay::take_pending() {
+			Some(recording) => Some(recording),
+			None => match self.replay.or_else(|| env::var_os(REPLAY_VAR).map(PathBuf::from)) {
+				Some(path) => Some(Recording::load(path)?),
+				None => None,
+			},
+		};
+		let record_to = self.record.or_else(|| env::var_os(RECORD_VAR).map(PathBuf::from));
+
+		let profile_var = env::var_os(PROFILE_VAR).map(PathBuf::from);
+		let profiling = if self.profile || profile_var.is_some() {
+			let export = self.profile_to.or_else(|| profile_var.as_deref().and_then(profiler::export_path));
+			Some(profiler::Profiling::new(export))
+		}
+		else {
+			None
+		};
+
+		// Lets CI machines run any Demo without touching its init code
+		let headless = self.headless
+			|| env::var_os(HEADLESS_VAR).is_some()
+			|| capture::script_active();
+
+		if headless {
+			// Has to be set before the video subsystem starts, and must win
+			// over any SDL_VIDEODRIVER already in the environment. Stays set
+			// for the whole process, see headless().
+			hint::set_with_priority("SDL_VIDEODRIVER", "dummy", &hint::Hint::Override);
+			// Same for audio::Audio, so it works without a sound card
+			hint::set_with_priority("SDL_AUDIODRIVER", "dummy", &hint::Hint::Override);
+		}
+
+		let sdl_cxt = sdl2::init().map_err(Error::init)?;
+		let video_subsys = sdl_cxt.video().map_err(Error::init)?;
+
+		let mut window_builder = video_subsys.window(&self.title, self.width, self.height);
+
+		match self.position {
+			Position::Default => {},
+			Position::Centered => { window_builder.position_centered(); },
+			Position::At(x, y) => { window_builder.position(x, y); },
+		}
+
+		match self.fullscreen {
+			Fullscreen::Off => {},
+			Fullscreen::Exclusive => { window_builder.fullscreen(); },
+			Fullscreen::Desktop => { window_builder.fullscreen_desktop(); },
+		}
+
+		if self.resizable {
+			window_builder.resizable();
+		}
+		if self.borderless {
+			window_builder.borderless();
+		}
+		if self.high_dpi {
+			window_builder.allow_highdpi();
+		}
+		if headless {
+			window_builder.hidden();
+		}
+
+		let mut window = window_builder.build()?;
+
+		if let Some(path) = &self.icon {
+			let icon = Surface::from_file(path)
+				.map_err(|e| Error::asset(path, e))?;
+			window.set_icon(icon);
+		}
+
+		let wincan = window.into_canvas();
+
+		// No GPU to accelerate and no display to sync to when headless
+		let wincan = if headless || self.renderer == Renderer::Software {
+			wincan.software()
+		}
+		else {
+			wincan.accelerated()
+		};
+
+		let wincan = if self.vsync && !headless {
+			wincan.present_vsync()
+		}
+		else {
+			wincan
+		};
+
+		let mut wincan = wincan.build()?;
+
+		// The camera covers whatever size we're drawing at
+		let (cam_w, cam_h) = match self.logical_size {
+			Some((w, h)) => {
+				wincan.set_logical_size(w, h)?;
+				(w, h)
+			},
+			None => (self.width, self.height),
+		};
+
+		let event_pump = sdl_cxt.event_pump().map_err(Error::init)?;
+
+		let cam = Camera::new(cam_w, cam_h);
+
+		let pacer = self.frame_limit.map(FramePacer::new);
+
+		let session = match replay_with {
+			Some(recording) => replay::Session::replay(recording),
+			None => match record_to {
+				Some(path) => replay::Session::record(path),
+				None => replay::Session::live(),
+			},
+		};
+
+		Ok(SDLCore{
+			sdl_cxt,
+			wincan,
+			event_pump,
+			cam,
+			frames: 0,
+			session,
+			profiling,
+			pacer,
+			headless,
+		})
+	}
+}
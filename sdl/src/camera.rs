@@ -0,0 +1,490 @@
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Texture;
+
+use crate::SDLCore;
+use crate::Error;
+
+// How the camera chases its target in Camera::follow()
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Follow {
+	// Stay where it's put
+	Fixed,
+	// Keep the target dead center
+	Centered,
+	// Only move once the target's center leaves a w x h box in the middle of
+	// the view. SDL13's "thirds" is DeadZone{ w: CAM_W / 3, h: CAM_H }.
+	DeadZone {
+		w: u32,
+		h: u32,
+	},
+	// Center on a point `distance` pixels ahead of the way the target is
+	// moving, so the player can see what they're heading toward
+	LookAhead {
+		distance: f32,
+	},
+	// Ease toward centered instead of snapping. Higher rate is snappier,
+	// about 1/rate seconds to cover most of the gap.
+	Smoothed {
+		rate: f32,
+	},
+}
+
+// How fast look-ahead swings around when the target changes direction
+const LOOK_AHEAD_RATE: f32 = 4.0;
+
+// The part of the world that's on screen
+#[derive(Clone, Debug)]
+pub struct Camera {
+	// Top-left corner, in world coordinates
+	x: f32,
+	y: f32,
+	width: u32,
+	height: u32,
+	// The camera never shows anything outside these, if set
+	bounds: Option<Rect>,
+	mode: Follow,
+	// Where the target was last follow(), and the current look-ahead
+	last_target: Option<(f32, f32)>,
+	look_ahead: (f32, f32),
+}
+
+fn center(r: Rect) -> (f32, f32) {
+	(
+		r.x() as f32 + (r.width() as f32) / 2.0,
+		r.y() as f32 + (r.height() as f32) / 2.0,
+	)
+}
+
+// Fraction of the remaining distance to cover this step when easing at
+// `rate`, independent of frame rate
+fn ease(rate: f32, dt: f32) -> f32 {
+	1.0 - (-rate * dt).exp()
+}
+
+impl Camera {
+	pub fn new(width: u32, height: u32) -> Camera {
+		Camera {
+			x: 0.0,
+			y: 0.0,
+			width,
+			height,
+			bounds: None,
+			mode: Follow::Fixed,
+			last_target: None,
+			look_ahead: (0.0, 0.0),
+		}
+	}
+
+	pub fn x(&self) -> i32 {
+		self.x.round() as i32
+	}
+
+	pub fn y(&self) -> i32 {
+		self.y.round() as i32
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	// The visible part of the world
+	pub fn view(&self) -> Rect {
+		Rect::new(self.x(), self.y(), self.width, self.height)
+	}
+
+	pub fn bounds(&self) -> Option<Rect> {
+		self.bounds
+	}
+
+	// e.g., Rect::new(0, 0, LEVEL_LEN, CAM_H) for a side scroller
+	pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+		self.bounds = bounds;
+		self.clamp();
+	}
+
+	pub fn mode(&self) -> Follow {
+		self.mode
+	}
+
+	pub fn set_mode(&mut self, mode: Follow) {
+		self.mode = mode;
+		self.look_ahead = (0.0, 0.0);
+	}
+
+	// For when the window or logical size changes
+	pub fn set_size(&mut self, width: u32, height: u32) {
+		self.width = width;
+		self.height = height;
+		self.clamp();
+	}
+
+	// Move the top-left corner to (x, y), within bounds
+	pub fn move_to(&mut self, x: i32, y: i32) {
+		self.x = x as f32;
+		self.y = y as f32;
+		self.clamp();
+	}
+
+	pub fn center_on(&mut self, x: i32, y: i32) {
+		self.x = x as f32 - (self.width as f32) / 2.0;
+		self.y = y as f32 - (self.height as f32) / 2.0;
+		self.clamp();
+	}
+
+	// Move toward `target` (in world coordinates) according to the follow
+	// mode. Call once per update, dt in seconds.
+	pub fn follow(&mut self, target: Rect, dt: f32) {
+		let (tx, ty) = center(target);
+		let (half_w, half_h) = ((self.width as f32) / 2.0, (self.height as f32) / 2.0);
+
+		match self.mode {
+			Follow::Fixed => {},
+			Follow::Centered => {
+				self.x = tx - half_w;
+				self.y = ty - half_h;
+			},
+			Follow::DeadZone{ w, h } => {
+				let (half_dw, half_dh) = ((w as f32) / 2.0, (h as f32) / 2.0);
+				let (cx, cy) = (self.x + half_w, self.y + half_h);
+
+				if tx > cx + half_dw {
+					self.x = tx - half_dw - half_w;
+				}
+				else if tx < cx - half_dw {
+					self.x = tx + half_dw - half_w;
+				}
+
+				if ty > cy + half_dh {
+					self.y = ty - half_dh - half_h;
+				}
+				else if ty < cy - half_dh {
+					self.y = ty + half_dh - half_h;
+				}
+			},
+			Follow::LookAhead{ distance } => {
+				let (dx, dy) = match self.last_target {
+					Some((lx, ly)) => (tx - lx, ty - ly),
+					None => (0.0, 0.0),
+				};
+
+				// Keep looking the same way while stopped
+				let goal_x = if dx != 0.0 { dx.signum() * distance } else { self.look_ahead.0 };
+				let goal_y = if dy != 0.0 { dy.signum() * distance } else { self.look_ahead.1 };
+
+				let t = ease(LOOK_AHEAD_RATE, dt);
+				self.look_ahead.0 += (goal_x - self.look_ahead.0) * t;
+				self.look_ahead.1 += (goal_y - self.look_ahead.1) * t;
+
+				self.x = tx + self.look_ahead.0 - half_w;
+				self.y = ty + self.look_ahead.1 - half_h;
+			},
+			Follow::Smoothed{ rate } => {
+				let t = ease(rate, dt);
+				self.x += (tx - half_w - self.x) * t;
+				self.y += (ty - half_h - self.y) * t;
+			},
+		}
+
+		self.last_target = Some((tx, ty));
+		self.clamp();
+	}
+
+	// Keep the view inside the bounds, or centered on them if the bounds
+	// are smaller than the view
+	fn clamp(&mut self) {
+		let b = match self.bounds {
+			Some(b) => b,
+			None => return,
+		};
+
+		self.x = if b.width() <= self.width {
+			b.x() as f32 - ((self.width - b.width()) as f32) / 2.0
+		}
+		else {
+			self.x.clamp(b.x() as f32, (b.right() - self.width as i32) as f32)
+		};
+
+		self.y = if b.height() <= self.height {
+			b.y() as f32 - ((self.height - b.height()) as f32) / 2.0
+		}
+		else {
+			self.y.clamp(b.y() as f32, (b.bottom() - self.height as i32) as f32)
+		};
+	}
+
+	pub fn world_to_screen(&self, p: Point) -> Point {
+		Point::new(p.x() - self.x(), p.y() - self.y())
+	}
+
+	pub fn screen_to_world(&self, p: Point) -> Point {
+		Point::new(p.x() + self.x(), p.y() + self.y())
+	}
+
+	pub fn rect_to_screen(&self, r: Rect) -> Rect {
+		Rect::new(r.x() - self.x(), r.y() - self.y(), r.width(), r.height())
+	}
+
+	pub fn rect_to_world(&self, r: Rect) -> Rect {
+		Rect::new(r.x() + self.x(), r.y() + self.y(), r.width(), r.height())
+	}
+
+	// Whether any of `r` (in world coordinates) is on screen
+	pub fn can_see(&self, r: Rect) -> bool {
+		self.view().has_intersection(r)
+	}
+}
+
+// Drawing in world coordinates. Each converts through the camera and skips
+// anything off screen.
+impl SDLCore {
+	pub fn copy_world<R: Into<Option<Rect>>>(
+		&mut self,
+		texture: &Texture,
+		src: R,
+		dst: Rect,
+	) -> Result<(), Error>
+	{
+		if self.cam.can_see(dst) {
+			self.wincan.copy(texture, src, self.cam.rect_to_screen(dst))?;
+		}
+
+		Ok(())
+	}
+
+	pub fn copy_world_ex<R: Into<Option<Rect>>>(
+		&mut self,
+		texture: &Texture,
+		src: R,
+		dst: Rect,
+		flip_horizontal: bool,
+		flip_vertical: bool,
+	) -> Result<(), Error>
+	{
+		self.copy_world_rotated(texture, src, dst, 0.0, flip_horizontal, flip_vertical)
+	}
+
+	// Flipped first, then turned `angle` degrees clockwise about the middle
+	// of `dst`
+	pub fn copy_world_rotated<R: Into<Option<Rect>>>(
+		&mut self,
+		texture: &Texture,
+		src: R,
+		dst: Rect,
+		angle: f64,
+		flip_horizontal: bool,
+		flip_vertical: bool,
+	) -> Result<(), Error>
+	{
+		if self.cam.can_see(dst) {
+			let dst = self.cam.rect_to_screen(dst);
+			self.wincan.copy_ex(texture, src, dst, angle, None, flip_horizontal, flip_vertical)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn fill_world_rect(&mut self, r: Rect) -> Result<(), Error> {
+		if self.cam.can_see(r) {
+			self.wincan.fill_rect(self.cam.rect_to_screen(r))?;
+		}
+
+		Ok(())
+	}
+
+	pub fn draw_world_rect(&mut self, r: Rect) -> Result<(), Error> {
+		if self.cam.can_see(r) {
+			self.wincan.draw_rect(self.cam.rect_to_screen(r))?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const CAM_W: u32 = 640;
+	const CAM_H: u32 = 480;
+	const LEVEL_LEN: u32 = 2000;
+	const TILE_SIZE: u32 = 100;
+
+	// SDL13's scrolling from before it had a Camera
+	fn thirds(px: i32, scroll_offset: i32) -> i32 {
+		let lthird = ((CAM_W as i32) / 3) - (TILE_SIZE as i32)/2;
+		let rthird = ((CAM_W as i32) * 2 / 3) - (TILE_SIZE as i32)/2;
+
+		if px > scroll_offset + rthird {
+			(px - rthird).clamp(0, (LEVEL_LEN - CAM_W) as i32)
+		}
+		else if px < scroll_offset + lthird {
+			(px - lthird).clamp(0, (LEVEL_LEN - CAM_W) as i32)
+		}
+		else {
+			scroll_offset
+		}
+	}
+
+	fn side_scroller() -> Camera {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.set_bounds(Some(Rect::new(0, 0, LEVEL_LEN, CAM_H)));
+		cam.set_mode(Follow::DeadZone{ w: CAM_W / 3, h: CAM_H });
+		cam
+	}
+
+	fn player(px: i32) -> Rect {
+		Rect::new(px, (CAM_H - 2*TILE_SIZE) as i32, TILE_SIZE, TILE_SIZE)
+	}
+
+	#[test]
+	fn dead_zone_matches_the_old_thirds() {
+		let mut cam = side_scroller();
+		let mut scroll_offset = 0;
+		let mut px = (CAM_W/2 - TILE_SIZE/2) as i32;
+
+		// Right to the end of the level, back to the start, and some
+		// turning around in between
+		let mut steps = vec![5; 400];
+		steps.extend(vec![-5; 500]);
+		steps.extend([3, 3, -2, 7, -7, -1, 4].iter().cycle().take(300));
+		steps.extend(vec![-5; 100]);
+
+		for step in steps {
+			px = (px + step).clamp(0, (LEVEL_LEN - TILE_SIZE) as i32);
+			scroll_offset = thirds(px, scroll_offset);
+			cam.follow(player(px), 1.0 / 60.0);
+			assert_eq!(cam.x(), scroll_offset, "player at {}: camera at {}, thirds at {}", px, cam.x(), scroll_offset);
+		}
+	}
+
+	#[test]
+	fn dead_zone_holds_still_inside_the_middle_third() {
+		let mut cam = side_scroller();
+		cam.move_to(500, 0);
+
+		for px in [700, 750, 800, 850].iter() {
+			cam.follow(player(*px), 1.0 / 60.0);
+			assert_eq!(cam.x(), 500);
+		}
+
+		cam.follow(player(1000), 1.0 / 60.0);
+		assert!(cam.x() > 500);
+	}
+
+	#[test]
+	fn clamps_at_the_level_end() {
+		let mut cam = side_scroller();
+
+		cam.follow(player((LEVEL_LEN - TILE_SIZE) as i32), 1.0 / 60.0);
+		assert_eq!(cam.x(), (LEVEL_LEN - CAM_W) as i32);
+		assert_eq!(cam.y(), 0);
+
+		cam.follow(player(0), 1.0 / 60.0);
+		assert_eq!(cam.x(), 0);
+
+		cam.move_to(5000, 5000);
+		assert_eq!((cam.x(), cam.y()), ((LEVEL_LEN - CAM_W) as i32, 0));
+		cam.move_to(-5000, -5000);
+		assert_eq!((cam.x(), cam.y()), (0, 0));
+	}
+
+	#[test]
+	fn centers_a_level_smaller_than_the_view() {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.set_mode(Follow::Centered);
+		cam.set_bounds(Some(Rect::new(100, 50, 400, 300)));
+		assert_eq!((cam.x(), cam.y()), (100 - 120, 50 - 90));
+
+		// Wherever the target goes
+		cam.follow(Rect::new(480, 320, 10, 10), 1.0 / 60.0);
+		assert_eq!((cam.x(), cam.y()), (-20, -40));
+
+		// Smaller on one axis only clamps the other as usual
+		cam.set_bounds(Some(Rect::new(0, 0, 2000, 300)));
+		cam.follow(Rect::new(1990, 0, 10, 10), 1.0 / 60.0);
+		assert_eq!((cam.x(), cam.y()), (2000 - CAM_W as i32, -90));
+	}
+
+	#[test]
+	fn centered_and_fixed() {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.follow(Rect::new(1000, 1000, 10, 10), 1.0 / 60.0);
+		assert_eq!((cam.x(), cam.y()), (0, 0));
+
+		cam.set_mode(Follow::Centered);
+		cam.follow(Rect::new(1000, 1000, 10, 10), 1.0 / 60.0);
+		assert_eq!((cam.x(), cam.y()), (1005 - 320, 1005 - 240));
+	}
+
+	#[test]
+	fn look_ahead_leads_and_keeps_looking_when_stopped() {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.set_mode(Follow::LookAhead{ distance: 100.0 });
+
+		// Moving right for a few seconds settles 100px ahead
+		let mut x = 0;
+		for _ in 0..300 {
+			x += 2;
+			cam.follow(Rect::new(x, 0, 10, 10), 1.0 / 60.0);
+		}
+		let centered = x + 5 - 320;
+		assert!((cam.x() - (centered + 100)).abs() <= 1, "{} vs {}", cam.x(), centered + 100);
+
+		// Still ahead after stopping
+		for _ in 0..300 {
+			cam.follow(Rect::new(x, 0, 10, 10), 1.0 / 60.0);
+		}
+		assert!((cam.x() - (centered + 100)).abs() <= 1);
+
+		// Turning around swings to the other side, but not all at once
+		x -= 2;
+		cam.follow(Rect::new(x, 0, 10, 10), 1.0 / 60.0);
+		let centered = x + 5 - 320;
+		assert!(cam.x() > centered);
+		for _ in 0..300 {
+			x -= 2;
+			cam.follow(Rect::new(x, 0, 10, 10), 1.0 / 60.0);
+		}
+		let centered = x + 5 - 320;
+		assert!((cam.x() - (centered - 100)).abs() <= 1, "{} vs {}", cam.x(), centered - 100);
+	}
+
+	#[test]
+	fn smoothed_closes_in() {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.set_mode(Follow::Smoothed{ rate: 5.0 });
+
+		cam.follow(Rect::new(1000, 0, 10, 10), 0.1);
+		let first = cam.x();
+		assert!(first > 0 && first < 1005 - 320);
+
+		for _ in 0..100 {
+			cam.follow(Rect::new(1000, 0, 10, 10), 0.1);
+		}
+		assert_eq!(cam.x(), 1005 - 320);
+	}
+
+	#[test]
+	fn screen_and_world_convert_both_ways() {
+		let mut cam = Camera::new(CAM_W, CAM_H);
+		cam.move_to(300, 200);
+
+		let world = Point::new(350, 260);
+		let screen = cam.world_to_screen(world);
+		assert_eq!(screen, Point::new(50, 60));
+		assert_eq!(cam.screen_to_world(screen), world);
+
+		let r = Rect::new(310, 190, 20, 30);
+		assert_eq!(cam.rect_to_screen(r), Rect::new(10, -10, 20, 30));
+		assert_eq!(cam.rect_to_world(cam.rect_to_screen(r)), r);
+
+		assert!(cam.can_see(r));
+		assert!(cam.can_see(Rect::new(0, 0, 301, 201)));
+		assert!(!cam.can_see(Rect::new(0, 0, 300, 200)));
+		assert!(!cam.can_see(Rect::new(300 + CAM_W as i32, 200, 10, 10)));
+	}
+}
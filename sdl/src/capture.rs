@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+use std::path::Path;
+
+use sdl2::event::Event;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use sdl2::image::{LoadSurface, SaveSurface};
+
+use crate::SDLCore;
+use crate::Error;
+use crate::profiler::Phase;
+
+// A copy of what was drawn, 4 bytes per pixel in R, G, B, A order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+	width: u32,
+	height: u32,
+	pixels: Vec<u8>,
+}
+
+// How far apart two frames are
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameDiff {
+	// Pixels with some channel differing by more than the tolerance
+	pub mismatched: usize,
+	pub total: usize,
+	// Largest difference seen in any single channel
+	pub max_delta: u8,
+}
+
+impl FrameDiff {
+	pub fn mismatched_fraction(&self) -> f64 {
+		if self.total == 0 {
+			0.0
+		}
+		else {
+			(self.mismatched as f64) / (self.total as f64)
+		}
+	}
+}
+
+impl Frame {
+	pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Frame, Error> {
+		if pixels.len() != (width * height * 4) as usize {
+			return Err(Error::Other(format!(
+				"{}x{} frame needs {} bytes, got {}",
+				width, height, width * height * 4, pixels.len(),
+			)));
+		}
+
+		Ok(Frame {
+			width,
+			height,
+			pixels,
+		})
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	// RGBA of the pixel at (x, y)
+	pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+		if x >= self.width || y >= self.height {
+			return None;
+		}
+
+		let i = ((y * self.width + x) * 4) as usize;
+		Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]])
+	}
+
+	pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		// Surface wants a mutable buffer even though saving only reads it
+		let mut pixels = self.pixels.clone();
+		let surface = Surface::from_data(
+			&mut pixels,
+			self.width,
+			self.height,
+			self.width * 4,
+			PixelFormatEnum::RGBA32,
+		)?;
+
+		surface.save(&path)
+			.map_err(|e| Error::asset(&path, e))
+	}
+
+	pub fn load_png<P: AsRef<Path>>(path: P) -> Result<Frame, Error> {
+		let surface = Surface::from_file(&path)
+			.and_then(|s| s.convert_format(PixelFormatEnum::RGBA32))
+			.map_err(|e| Error::asset(&path, e))?;
+
+		let (width, height) = surface.size();
+		let pitch = surface.pitch() as usize;
+		let row_len = (width * 4) as usize;
+
+		// Rows may be padded out to the surface's pitch, so copy row by row
+		let pixels = surface.with_lock(|data| {
+			(0..(height as usize))
+				.flat_map(|row| data[row * pitch..row * pitch + row_len].iter().copied())
+				.collect()
+		});
+
+		Frame::new(width, height, pixels)
+	}
+
+	// Compares channel by channel, ignoring differences up to `tolerance`
+	pub fn diff(&self, other: &Frame, tolerance: u8) -> Result<FrameDiff, Error> {
+		if self.width != other.width || self.height != other.height {
+			return Err(Error::Other(format!(
+				"Frame sizes differ: {}x{} vs {}x{}",
+				self.width, self.height, other.width, other.height,
+			)));
+		}
+
+		let mut mismatched = 0;
+		let mut max_delta = 0;
+		for (a, b) in self.pixels.chunks(4).zip(other.pixels.chunks(4)) {
+			let delta = a.iter()
+				.zip(b.iter())
+				.map(|(x, y)| x.abs_diff(*y))
+				.max()
+				.unwrap_or(0);
+
+			if delta > tolerance {
+				mismatched += 1;
+			}
+			max_delta = max_delta.max(delta);
+		}
+
+		Ok(FrameDiff {
+			mismatched,
+			total: (self.width * self.height) as usize,
+			max_delta,
+		})
+	}
+}
+
+// Frames to grab as a Demo runs, set up by golden::capture_demo
+struct CaptureScript {
+	frames: Vec<u32>,
+	captured: Vec<Frame>,
+}
+
+thread_local! {
+	static SCRIPT: RefCell<Option<CaptureScript>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn start_script(frames: &[u32]) {
+	let mut frames = frames.to_vec();
+	frames.sort_unstable();
+	frames.dedup();
+
+	SCRIPT.with(|s| {
+		*s.borrow_mut() = Some(CaptureScript {
+			frames,
+			captured: Vec::new(),
+		});
+	});
+}
+
+pub(crate) fn finish_script() -> Vec<Frame> {
+	SCRIPT.with(|s| {
+		s.borrow_mut()
+			.take()
+			.map(|script| script.captured)
+			.unwrap_or_default()
+	})
+}
+
+// Scripted runs are always headless
+pub(crate) fn script_active() -> bool {
+	SCRIPT.with(|s| s.borrow().is_some())
+}
+
+impl SDLCore {
+	// Reads back the current contents of the canvas
+	pub fn capture(&self) -> Result<Frame, Error> {
+		let (width, height) = self.wincan.output_size()?;
+		let pixels = self.wincan.read_pixels(None, PixelFormatEnum::RGBA32)?;
+
+		Frame::new(width, height, pixels)
+	}
+
+	// Use instead of wincan.present() so golden tests can see each frame
+	pub fn present(&mut self) -> Result<(), Error> {
+		let frame = self.frames;
+		self.frames += 1;
+
+		// Has to be read before present(), after that the back buffer is undefined
+		let (wanted, done) = SCRIPT.with(|s| {
+			match &*s.borrow() {
+				Some(script) => (
+					script.frames.contains(&frame),
+					script.frames.last().map_or(frame == 0, |&last| frame == last),
+				),
+				None => (false, false),
+			}
+		});
+
+		if wanted {
+			let captured = self.capture()?;
+			SCRIPT.with(|s| {
+				if let Some(script) = &mut *s.borrow_mut() {
+					script.captured.push(captured);
+				}
+			});
+		}
+
+		self.begin_phase(Phase::Present);
+		self.wincan.present();
+		self.end_phase(Phase::Present);
+		self.pace_frame();
+		self.end_profiled_frame();
+
+		let replay_over = self.next_replay_frame();
+
+		// Got everything we came for, ask the demo to shut down
+		if done || replay_over {
+			self.push_event(Event::Quit{ timestamp: 0 })?;
+		}
+
+		Ok(())
+	}
+
+	// Number of times present() has been called
+	pub fn frames(&self) -> u32 {
+		self.frames
+	}
+}
@@ -0,0 +1,687 @@
+use sdl2::rect::Rect;
+
+// Axis-aligned box, in f32 world coordinates. Converts from and to Rect.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+	// Top-left corner
+	pub x: f32,
+	pub y: f32,
+	pub w: f32,
+	pub h: f32,
+}
+
+impl Aabb {
+	pub fn new(x: f32, y: f32, w: f32, h: f32) -> Aabb {
+		Aabb { x, y, w, h }
+	}
+
+	pub fn left(&self) -> f32 {
+		self.x
+	}
+
+	pub fn right(&self) -> f32 {
+		self.x + self.w
+	}
+
+	pub fn top(&self) -> f32 {
+		self.y
+	}
+
+	pub fn bottom(&self) -> f32 {
+		self.y + self.h
+	}
+
+	pub fn center(&self) -> (f32, f32) {
+		(self.x + self.w / 2.0, self.y + self.h / 2.0)
+	}
+
+	// Rounded to the nearest pixel
+	pub fn to_rect(&self) -> Rect {
+		Rect::new(self.x.round() as i32, self.y.round() as i32, self.w.round() as u32, self.h.round() as u32)
+	}
+}
+
+impl From<Rect> for Aabb {
+	fn from(r: Rect) -> Aabb {
+		Aabb::new(r.x() as f32, r.y() as f32, r.width() as f32, r.height() as f32)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+	// Center
+	pub x: f32,
+	pub y: f32,
+	pub r: f32,
+}
+
+impl Circle {
+	pub fn new(x: f32, y: f32, r: f32) -> Circle {
+		Circle { x, y, r }
+	}
+
+	// The smallest box around the circle
+	pub fn bounds(&self) -> Aabb {
+		Aabb::new(self.x - self.r, self.y - self.r, 2.0 * self.r, 2.0 * self.r)
+	}
+}
+
+// A line from start to end, e.g., a bullet's path this frame or a line of
+// sight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+	pub start: (f32, f32),
+	pub end: (f32, f32),
+}
+
+impl Segment {
+	pub fn new(start: (f32, f32), end: (f32, f32)) -> Segment {
+		Segment { start, end }
+	}
+
+	// The point `t` of the way along, 0.0 at start and 1.0 at end
+	pub fn at(&self, t: f32) -> (f32, f32) {
+		(
+			self.start.0 + (self.end.0 - self.start.0) * t,
+			self.start.1 + (self.end.1 - self.start.1) * t,
+		)
+	}
+}
+
+// How two shapes overlap. Moving the first shape `depth` along `normal` (a
+// unit vector) separates them, just touching.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+	pub normal: (f32, f32),
+	pub depth: f32,
+}
+
+impl Contact {
+	// How far to move the first shape to get it out
+	pub fn push(&self) -> (f32, f32) {
+		(self.normal.0 * self.depth, self.normal.1 * self.depth)
+	}
+
+	// The same overlap, seen from the second shape
+	pub fn flip(self) -> Contact {
+		Contact {
+			normal: (-self.normal.0, -self.normal.1),
+			depth: self.depth,
+		}
+	}
+}
+
+// Where a segment first meets a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+	// How far along the segment, 0.0 - 1.0
+	pub t: f32,
+	pub point: (f32, f32),
+	// Facing out of the surface that was hit, back toward the start
+	pub normal: (f32, f32),
+}
+
+fn length(v: (f32, f32)) -> f32 {
+	v.0.hypot(v.1)
+}
+
+fn dot(a: (f32, f32), b: (f32, f32)) -> f32 {
+	a.0 * b.0 + a.1 * b.1
+}
+
+fn cross(a: (f32, f32), b: (f32, f32)) -> f32 {
+	a.0 * b.1 - a.1 * b.0
+}
+
+fn sub(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+	(a.0 - b.0, a.1 - b.1)
+}
+
+fn normalize(v: (f32, f32)) -> (f32, f32) {
+	let len = length(v);
+	if len > 0.0 {
+		(v.0 / len, v.1 / len)
+	}
+	else {
+		(0.0, 0.0)
+	}
+}
+
+// Shapes that only touch along an edge don't count as overlapping, so a box
+// pushed out by a Contact isn't still colliding.
+pub fn aabb_aabb(a: &Aabb, b: &Aabb) -> Option<Contact> {
+	let overlap_x = a.right().min(b.right()) - a.left().max(b.left());
+	let overlap_y = a.bottom().min(b.bottom()) - a.top().max(b.top());
+	if overlap_x <= 0.0 || overlap_y <= 0.0 {
+		return None;
+	}
+
+	// Out the shortest way
+	let (ac, bc) = (a.center(), b.center());
+	if overlap_x < overlap_y {
+		let dir = if ac.0 < bc.0 { -1.0 } else { 1.0 };
+		Some(Contact{ normal: (dir, 0.0), depth: overlap_x })
+	}
+	else {
+		let dir = if ac.1 < bc.1 { -1.0 } else { 1.0 };
+		Some(Contact{ normal: (0.0, dir), depth: overlap_y })
+	}
+}
+
+pub fn circle_circle(a: &Circle, b: &Circle) -> Option<Contact> {
+	let d = sub((a.x, a.y), (b.x, b.y));
+	let dist = length(d);
+	let radii = a.r + b.r;
+	if dist >= radii {
+		return None;
+	}
+
+	// Right on top of each other, so any way out will do: up
+	let normal = if dist > 0.0 { (d.0 / dist, d.1 / dist) } else { (0.0, -1.0) };
+	Some(Contact{ normal, depth: radii - dist })
+}
+
+pub fn circle_aabb(c: &Circle, b: &Aabb) -> Option<Contact> {
+	let closest = (c.x.clamp(b.left(), b.right()), c.y.clamp(b.top(), b.bottom()));
+	let d = sub((c.x, c.y), closest);
+	let dist = length(d);
+
+	if dist > 0.0 {
+		if dist >= c.r {
+			return None;
+		}
+		return Some(Contact{ normal: (d.0 / dist, d.1 / dist), depth: c.r - dist });
+	}
+
+	// The center is inside the box, so push out through the nearest side
+	point_aabb((c.x, c.y), b).map(|contact| Contact {
+		normal: contact.normal,
+		depth: contact.depth + c.r,
+	})
+}
+
+pub fn aabb_circle(b: &Aabb, c: &Circle) -> Option<Contact> {
+	circle_aabb(c, b).map(Contact::flip)
+}
+
+pub fn point_in_aabb(p: (f32, f32), b: &Aabb) -> bool {
+	p.0 > b.left() && p.0 < b.right() && p.1 > b.top() && p.1 < b.bottom()
+}
+
+pub fn point_in_circle(p: (f32, f32), c: &Circle) -> bool {
+	length(sub(p, (c.x, c.y))) < c.r
+}
+
+// The Contact for the point: out through the nearest side
+pub fn point_aabb(p: (f32, f32), b: &Aabb) -> Option<Contact> {
+	if !point_in_aabb(p, b) {
+		return None;
+	}
+
+	let sides = [
+		((-1.0, 0.0), p.0 - b.left()),
+		((1.0, 0.0), b.right() - p.0),
+		((0.0, -1.0), p.1 - b.top()),
+		((0.0, 1.0), b.bottom() - p.1),
+	];
+
+	sides.iter()
+		.min_by(|a, b| a.1.total_cmp(&b.1))
+		.map(|&(normal, depth)| Contact{ normal, depth })
+}
+
+pub fn point_circle(p: (f32, f32), c: &Circle) -> Option<Contact> {
+	circle_circle(&Circle::new(p.0, p.1, 0.0), c)
+}
+
+// Slab test. A segment starting inside the box hits it at t = 0.0, with the
+// normal pointing back along the segment.
+pub fn segment_aabb(s: &Segment, b: &Aabb) -> Option<Hit> {
+	let d = sub(s.end, s.start);
+
+	let mut t_enter = f32::NEG_INFINITY;
+	let mut t_exit = f32::INFINITY;
+	let mut normal = (0.0, 0.0);
+
+	let slabs = [
+		(s.start.0, d.0, b.left(), b.right(), (-1.0, 0.0)),
+		(s.start.1, d.1, b.top(), b.bottom(), (0.0, -1.0)),
+	];
+	for &(start, dir, min, max, n) in slabs.iter() {
+		if dir == 0.0 {
+			// Parallel to this slab, so it has to be between the sides
+			if start < min || start > max {
+				return None;
+			}
+			continue;
+		}
+
+		let (mut near, mut far) = ((min - start) / dir, (max - start) / dir);
+		// Coming in through the min side faces min-ward, and vice versa
+		let mut n = n;
+		if near > far {
+			std::mem::swap(&mut near, &mut far);
+			n = (-n.0, -n.1);
+		}
+
+		if near > t_enter {
+			t_enter = near;
+			normal = n;
+		}
+		t_exit = t_exit.min(far);
+	}
+
+	if t_enter > t_exit || t_exit < 0.0 || t_enter > 1.0 {
+		return None;
+	}
+
+	if t_enter < 0.0 {
+		let back = normalize(d);
+		return Some(Hit{ t: 0.0, point: s.start, normal: (-back.0, -back.1) });
+	}
+
+	Some(Hit{ t: t_enter, point: s.at(t_enter), normal })
+}
+
+// A segment starting inside the circle hits it at t = 0.0, with the normal
+// pointing back along the segment.
+pub fn segment_circle(s: &Segment, c: &Circle) -> Option<Hit> {
+	let d = sub(s.end, s.start);
+	let f = sub(s.start, (c.x, c.y));
+
+	let a = dot(d, d);
+	let b = 2.0 * dot(f, d);
+	let cc = dot(f, f) - c.r * c.r;
+
+	if cc < 0.0 {
+		let back = normalize(d);
+		return Some(Hit{ t: 0.0, point: s.start, normal: (-back.0, -back.1) });
+	}
+	if a == 0.0 {
+		return None;
+	}
+
+	let disc = b * b - 4.0 * a * cc;
+	if disc < 0.0 {
+		return None;
+	}
+
+	let t = (-b - disc.sqrt()) / (2.0 * a);
+	if !(0.0..=1.0).contains(&t) {
+		return None;
+	}
+
+	let point = s.at(t);
+	Some(Hit{ t, point, normal: normalize(sub(point, (c.x, c.y))) })
+}
+
+// Where segment `a` crosses segment `b`. Parallel segments never do, even if
+// they overlap.
+pub fn segment_segment(a: &Segment, b: &Segment) -> Option<Hit> {
+	let r = sub(a.end, a.start);
+	let s = sub(b.end, b.start);
+
+	let denom = cross(r, s);
+	if denom == 0.0 {
+		return None;
+	}
+
+	let qp = sub(b.start, a.start);
+	let t = cross(qp, s) / denom;
+	let u = cross(qp, r) / denom;
+	if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+		return None;
+	}
+
+	// Whichever side of b that a came from
+	let mut normal = normalize((-s.1, s.0));
+	if dot(normal, r) > 0.0 {
+		normal = (-normal.0, -normal.1);
+	}
+
+	Some(Hit{ t, point: a.at(t), normal })
+}
+
+// Either shape, for code that handles both, like what a
+// broadphase::SpatialHash turns up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+	Aabb(Aabb),
+	Circle(Circle),
+}
+
+impl Shape {
+	pub fn bounds(&self) -> Aabb {
+		match self {
+			Shape::Aabb(a) => *a,
+			Shape::Circle(c) => c.bounds(),
+		}
+	}
+
+	pub fn contact(&self, other: &Shape) -> Option<Contact> {
+		match (self, other) {
+			(Shape::Aabb(a), Shape::Aabb(b)) => aabb_aabb(a, b),
+			(Shape::Aabb(a), Shape::Circle(b)) => aabb_circle(a, b),
+			(Shape::Circle(a), Shape::Aabb(b)) => circle_aabb(a, b),
+			(Shape::Circle(a), Shape::Circle(b)) => circle_circle(a, b),
+		}
+	}
+
+	pub fn contains(&self, p: (f32, f32)) -> bool {
+		match self {
+			Shape::Aabb(a) => point_in_aabb(p, a),
+			Shape::Circle(c) => point_in_circle(p, c),
+		}
+	}
+
+	pub fn raycast(&self, s: &Segment) -> Option<Hit> {
+		match self {
+			Shape::Aabb(a) => segment_aabb(s, a),
+			Shape::Circle(c) => segment_circle(s, c),
+		}
+	}
+}
+
+// When box `a` moving by `motion` first touches box `b`, as a Hit on the
+// segment of a's top-left corner. Boxes already overlapping hit at t = 0.0.
+pub fn sweep_aabb(a: &Aabb, motion: (f32, f32), b: &Aabb) -> Option<Hit> {
+	// Grow b by a's size, and the box becomes a point
+	let grown = Aabb::new(b.x - a.w, b.y - a.h, b.w + a.w, b.h + a.h);
+	let path = Segment::new((a.x, a.y), (a.x + motion.0, a.y + motion.1));
+
+	segment_aabb(&path, &grown)
+}
+
+// Floating point leaves boxes that were moved up against each other a hair
+// apart or a hair inside; within this counts as touching
+const SKIN: f32 = 0.001;
+
+// Where move_aabb() left a box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Moved {
+	pub aabb: Aabb,
+	// The collider, by index, that stopped the box on each axis
+	pub hit_x: Option<usize>,
+	pub hit_y: Option<usize>,
+}
+
+// How far along one axis a box can go toward `distance` before running into
+// a collider, and which one it ran into
+fn sweep_axis(a: &Aabb, distance: f32, horizontal: bool, colliders: &[Aabb]) -> (f32, Option<usize>) {
+	let mut allowed = distance;
+	let mut hit = None;
+
+	for (i, c) in colliders.iter().enumerate() {
+		// Has to overlap on the other axis, not just touch, or boxes sliding
+		// along a floor would catch on the seams between its tiles
+		let (across, (a_min, a_max), (c_min, c_max)) = if horizontal {
+			(a.top() < c.bottom() && a.bottom() > c.top(), (a.left(), a.right()), (c.left(), c.right()))
+		}
+		else {
+			(a.left() < c.right() && a.right() > c.left(), (a.top(), a.bottom()), (c.top(), c.bottom()))
+		};
+		if !across {
+			continue;
+		}
+
+		// Only what's ahead can block. Anything the box is already inside
+		// is ignored, so it can always move back out.
+		let gap = if distance > 0.0 && c_min >= a_max - SKIN {
+			(c_min - a_max).max(0.0)
+		}
+		else if distance < 0.0 && c_max <= a_min + SKIN {
+			-(a_min - c_max).max(0.0)
+		}
+		else {
+			continue;
+		};
+
+		if gap.abs() < allowed.abs() {
+			allowed = gap;
+			hit = Some(i);
+		}
+	}
+
+	(allowed, hit)
+}
+
+// Moves box `a` by `motion`, stopping at the first collider in the way on
+// each axis and sliding along it with the rest of the motion. x goes first,
+// then y. Each axis is swept over its whole distance, so nothing is tunneled
+// through however far it goes in one step.
+pub fn move_aabb(a: &Aabb, motion: (f32, f32), colliders: &[Aabb]) -> Moved {
+	let mut aabb = *a;
+
+	let (dx, hit_x) = sweep_axis(&aabb, motion.0, true, colliders);
+	aabb.x += dx;
+
+	let (dy, hit_y) = sweep_axis(&aabb, motion.1, false, colliders);
+	aabb.y += dy;
+
+	Moved { aabb, hit_x, hit_y }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn close(a: f32, b: f32) -> bool {
+		(a - b).abs() < 1e-4
+	}
+
+	fn assert_contact(c: Option<Contact>, normal: (f32, f32), depth: f32) {
+		let c = c.expect("no contact");
+		assert!(close(c.normal.0, normal.0) && close(c.normal.1, normal.1), "normal {:?}, expected {:?}", c.normal, normal);
+		assert!(close(c.depth, depth), "depth {}, expected {}", c.depth, depth);
+	}
+
+	fn assert_hit(h: Option<Hit>, t: f32, point: (f32, f32), normal: (f32, f32)) {
+		let h = h.expect("no hit");
+		assert!(close(h.t, t), "t {}, expected {}", h.t, t);
+		assert!(close(h.point.0, point.0) && close(h.point.1, point.1), "point {:?}, expected {:?}", h.point, point);
+		assert!(close(h.normal.0, normal.0) && close(h.normal.1, normal.1), "normal {:?}, expected {:?}", h.normal, normal);
+	}
+
+	#[test]
+	fn aabb_aabb_pushes_out_the_shortest_way() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		// b overlaps a's right side by 2
+		let b = Aabb::new(8.0, 2.0, 10.0, 10.0);
+		assert_contact(aabb_aabb(&a, &b), (-1.0, 0.0), 2.0);
+		assert_contact(aabb_aabb(&b, &a), (1.0, 0.0), 2.0);
+
+		// b overlaps a's bottom by 3
+		let b = Aabb::new(1.0, 7.0, 10.0, 10.0);
+		assert_contact(aabb_aabb(&a, &b), (0.0, -1.0), 3.0);
+		assert_contact(aabb_aabb(&b, &a), (0.0, 1.0), 3.0);
+	}
+
+	#[test]
+	fn aabb_aabb_touching_edges_dont_collide() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		assert_eq!(aabb_aabb(&a, &Aabb::new(10.0, 0.0, 10.0, 10.0)), None);
+		assert_eq!(aabb_aabb(&a, &Aabb::new(0.0, 10.0, 10.0, 10.0)), None);
+		assert_eq!(aabb_aabb(&a, &Aabb::new(10.0, 10.0, 10.0, 10.0)), None);
+		assert_eq!(aabb_aabb(&a, &Aabb::new(30.0, 0.0, 10.0, 10.0)), None);
+	}
+
+	#[test]
+	fn aabb_aabb_contact_separates() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		let b = Aabb::new(6.0, 3.0, 10.0, 10.0);
+
+		let (px, py) = aabb_aabb(&a, &b).unwrap().push();
+		let moved = Aabb::new(a.x + px, a.y + py, a.w, a.h);
+		assert_eq!(aabb_aabb(&moved, &b), None);
+	}
+
+	#[test]
+	fn circle_aabb_from_outside() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		// Overlapping the right side by 1
+		assert_contact(circle_aabb(&Circle::new(14.0, 5.0, 5.0), &b), (1.0, 0.0), 1.0);
+		// Overlapping the top
+		assert_contact(circle_aabb(&Circle::new(5.0, -3.0, 5.0), &b), (0.0, -1.0), 2.0);
+
+		// Off the bottom right corner, pushed out diagonally
+		let r = 5.0;
+		let dist = 8.0f32.sqrt();
+		let s = 0.5f32.sqrt();
+		assert_contact(circle_aabb(&Circle::new(12.0, 12.0, r), &b), (s, s), r - dist);
+
+		assert_contact(aabb_circle(&b, &Circle::new(14.0, 5.0, 5.0)), (-1.0, 0.0), 1.0);
+	}
+
+	#[test]
+	fn circle_aabb_touching_edges_dont_collide() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		assert_eq!(circle_aabb(&Circle::new(15.0, 5.0, 5.0), &b), None);
+		assert_eq!(circle_aabb(&Circle::new(5.0, -5.0, 5.0), &b), None);
+		// Just off the corner
+		assert_eq!(circle_aabb(&Circle::new(14.0, 14.0, 5.0), &b), None);
+	}
+
+	#[test]
+	fn circle_aabb_center_inside() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 20.0);
+
+		// Out through the nearest side, far enough to clear the radius
+		assert_contact(circle_aabb(&Circle::new(2.0, 10.0, 1.0), &b), (-1.0, 0.0), 3.0);
+		assert_contact(circle_aabb(&Circle::new(7.0, 10.0, 1.0), &b), (1.0, 0.0), 4.0);
+		assert_contact(circle_aabb(&Circle::new(5.0, 1.0, 2.0), &b), (0.0, -1.0), 3.0);
+		assert_contact(circle_aabb(&Circle::new(5.0, 18.0, 2.0), &b), (0.0, 1.0), 4.0);
+	}
+
+	#[test]
+	fn segment_aabb_hits_the_near_side() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		assert_hit(segment_aabb(&Segment::new((-10.0, 5.0), (20.0, 5.0)), &b), 1.0 / 3.0, (0.0, 5.0), (-1.0, 0.0));
+		assert_hit(segment_aabb(&Segment::new((20.0, 5.0), (-10.0, 5.0)), &b), 1.0 / 3.0, (10.0, 5.0), (1.0, 0.0));
+		assert_hit(segment_aabb(&Segment::new((5.0, -10.0), (5.0, 20.0)), &b), 1.0 / 3.0, (5.0, 0.0), (0.0, -1.0));
+		assert_hit(segment_aabb(&Segment::new((5.0, 20.0), (5.0, -10.0)), &b), 1.0 / 3.0, (5.0, 10.0), (0.0, 1.0));
+
+		// Diagonal, in through the top
+		assert_hit(segment_aabb(&Segment::new((0.0, -5.0), (10.0, 5.0)), &b), 0.5, (5.0, 0.0), (0.0, -1.0));
+	}
+
+	#[test]
+	fn segment_aabb_misses() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		// Stops short
+		assert_eq!(segment_aabb(&Segment::new((-10.0, 5.0), (-1.0, 5.0)), &b), None);
+		// Starts past it
+		assert_eq!(segment_aabb(&Segment::new((11.0, 5.0), (20.0, 5.0)), &b), None);
+		// Passes beside it
+		assert_eq!(segment_aabb(&Segment::new((-10.0, 11.0), (20.0, 11.0)), &b), None);
+		// Passes by the corner
+		assert_eq!(segment_aabb(&Segment::new((5.0, -10.0), (20.0, 4.0)), &b), None);
+	}
+
+	#[test]
+	fn segment_aabb_starting_inside() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		assert_hit(segment_aabb(&Segment::new((5.0, 5.0), (25.0, 5.0)), &b), 0.0, (5.0, 5.0), (-1.0, 0.0));
+		assert_hit(segment_aabb(&Segment::new((5.0, 5.0), (5.0, -5.0)), &b), 0.0, (5.0, 5.0), (0.0, 1.0));
+	}
+
+	#[test]
+	fn segment_aabb_zero_length() {
+		let b = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		// No direction to point back along
+		assert_hit(segment_aabb(&Segment::new((5.0, 5.0), (5.0, 5.0)), &b), 0.0, (5.0, 5.0), (0.0, 0.0));
+		assert_eq!(segment_aabb(&Segment::new((-5.0, 5.0), (-5.0, 5.0)), &b), None);
+		assert_eq!(segment_aabb(&Segment::new((5.0, 15.0), (5.0, 15.0)), &b), None);
+	}
+
+	#[test]
+	fn move_aabb_stops_at_a_wall_and_slides_along_it() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		let wall = Aabb::new(20.0, -100.0, 10.0, 200.0);
+
+		let moved = move_aabb(&a, (30.0, 5.0), &[wall]);
+		assert_eq!(moved.aabb, Aabb::new(10.0, 5.0, 10.0, 10.0));
+		assert_eq!(moved.hit_x, Some(0));
+		assert_eq!(moved.hit_y, None);
+
+		// Back the other way is never blocked
+		let back = move_aabb(&moved.aabb, (-5.0, 0.0), &[wall]);
+		assert_eq!(back.aabb, Aabb::new(5.0, 5.0, 10.0, 10.0));
+		assert_eq!(back.hit_x, None);
+	}
+
+	#[test]
+	fn move_aabb_slides_over_tile_seams() {
+		// Resting on a floor of three tiles
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		let floor = [
+			Aabb::new(0.0, 10.0, 10.0, 10.0),
+			Aabb::new(10.0, 10.0, 10.0, 10.0),
+			Aabb::new(20.0, 10.0, 10.0, 10.0),
+		];
+
+		let moved = move_aabb(&a, (15.0, 3.0), &floor);
+		assert_eq!(moved.aabb, Aabb::new(15.0, 0.0, 10.0, 10.0));
+		assert_eq!(moved.hit_x, None);
+		assert_eq!(moved.hit_y, Some(1));
+	}
+
+	#[test]
+	fn move_aabb_stops_at_the_nearest_collider() {
+		let a = Aabb::new(30.0, 0.0, 10.0, 10.0);
+		let walls = [Aabb::new(0.0, 0.0, 5.0, 10.0), Aabb::new(10.0, 0.0, 5.0, 10.0)];
+
+		let moved = move_aabb(&a, (-50.0, 0.0), &walls);
+		assert_eq!(moved.aabb.x, 15.0);
+		assert_eq!(moved.hit_x, Some(1));
+	}
+
+	#[test]
+	fn move_aabb_doesnt_tunnel() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		let thin = Aabb::new(50.0, 0.0, 1.0, 10.0);
+
+		let moved = move_aabb(&a, (1000.0, 0.0), &[thin]);
+		assert_eq!(moved.aabb.x, 40.0);
+		assert_eq!(moved.hit_x, Some(0));
+	}
+
+	#[test]
+	fn move_aabb_within_skin_counts_as_touching() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+
+		// A hair inside the wall: blocked, not pushed out or let through
+		let wall = Aabb::new(10.0 - SKIN / 2.0, 0.0, 10.0, 10.0);
+		let moved = move_aabb(&a, (5.0, 0.0), &[wall]);
+		assert_eq!(moved.aabb.x, 0.0);
+		assert_eq!(moved.hit_x, Some(0));
+
+		// A hair short of it: moves just that far
+		let wall = Aabb::new(10.0 + SKIN / 2.0, 0.0, 10.0, 10.0);
+		let moved = move_aabb(&a, (5.0, 0.0), &[wall]);
+		assert!(close(moved.aabb.x, SKIN / 2.0));
+		assert_eq!(moved.hit_x, Some(0));
+
+		// Further in than SKIN: already inside, so it doesn't block
+		let wall = Aabb::new(10.0 - 2.0 * SKIN, 0.0, 10.0, 10.0);
+		let moved = move_aabb(&a, (5.0, 0.0), &[wall]);
+		assert_eq!(moved.aabb.x, 5.0);
+		assert_eq!(moved.hit_x, None);
+	}
+
+	#[test]
+	fn move_aabb_zero_length() {
+		let a = Aabb::new(0.0, 0.0, 10.0, 10.0);
+		let walls = [Aabb::new(10.0, 0.0, 10.0, 10.0), Aabb::new(0.0, 10.0, 10.0, 10.0)];
+
+		let moved = move_aabb(&a, (0.0, 0.0), &walls);
+		assert_eq!(moved, Moved{ aabb: a, hit_x: None, hit_y: None });
+
+		let moved = move_aabb(&a, (0.0, 0.0), &[]);
+		assert_eq!(moved.aabb, a);
+	}
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use sdl2::GameControllerSubsystem;
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+
+use crate::SDLCore;
+use crate::Error;
+use crate::SdlError;
+
+// Ids from here up belong to VirtualControllers, well past any real joystick
+const VIRTUAL_BASE: u32 = 0x4000_0000;
+
+// A controller coming or going, from Controllers::handle_event()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControllerChange {
+	Connected {
+		id: u32,
+		name: String,
+	},
+	Disconnected {
+		id: u32,
+	},
+}
+
+// Keeps every plugged-in game controller open, so their buttons and sticks
+// show up as events for Input. Controllers can be plugged in or pulled out
+// at any time; pass each event to handle_event() to keep up.
+pub struct Controllers {
+	subsystem: GameControllerSubsystem,
+	// By instance id, the `which` in controller events
+	open: HashMap<u32, GameController>,
+	virtual_ids: Vec<u32>,
+	// Controllers that were plugged in at startup but wouldn't open
+	startup_errors: Vec<Error>,
+}
+
+impl Controllers {
+	pub fn new(core: &SDLCore) -> Result<Controllers, Error> {
+		let subsystem = core.sdl_cxt.game_controller().map_err(Error::init)?;
+
+		let mut controllers = Controllers {
+			subsystem,
+			open: HashMap::new(),
+			virtual_ids: Vec::new(),
+			startup_errors: Vec::new(),
+		};
+
+		// SDL also sends ControllerDeviceAdded for these, which is harmless.
+		// One that won't open is skipped, like in handle_event(), rather
+		// than keeping the game from starting.
+		let count = controllers.subsystem.num_joysticks().map_err(Error::init)?;
+		for index in 0..count {
+			if let Err(e) = controllers.open_index(index) {
+				controllers.startup_errors.push(e);
+			}
+		}
+
+		Ok(controllers)
+	}
+
+	// None if the device isn't a game controller (e.g. a joystick SDL has
+	// no mapping for) or is already open
+	fn open_index(&mut self, index: u32) -> Result<Option<ControllerChange>, Error> {
+		if !self.subsystem.is_game_controller(index) {
+			return Ok(None);
+		}
+
+		let c = self.subsystem.open(index)
+			.map_err(|e| Error::Sdl(SdlError(format!("could not open controller {}: {}", index, e))))?;
+		let id = c.instance_id();
+		let name = c.name();
+		// Already open if we saw it at startup
+		if self.open.insert(id, c).is_some() {
+			return Ok(None);
+		}
+		Ok(Some(ControllerChange::Connected{ id, name }))
+	}
+
+	// Opens and closes controllers as they're plugged in and pulled out.
+	// Errors if a controller was plugged in but couldn't be opened; the
+	// rest keep working, so it's fine to report it and carry on.
+	pub fn handle_event(&mut self, event: &Event) -> Result<Option<ControllerChange>, Error> {
+		let change = match event {
+			Event::ControllerDeviceAdded{ which, .. } if *which >= VIRTUAL_BASE => {
+				if self.virtual_ids.contains(which) {
+					return Ok(None);
+				}
+				self.virtual_ids.push(*which);
+				Some(ControllerChange::Connected {
+					id: *which,
+					name: format!("Virtual controller {}", which - VIRTUAL_BASE),
+				})
+			},
+			// For added, `which` is the device index; for removed, the
+			// instance id
+			Event::ControllerDeviceAdded{ which, .. } => return self.open_index(*which),
+			Event::ControllerDeviceRemoved{ which, .. } => {
+				let was_virtual = self.virtual_ids.contains(which);
+				self.virtual_ids.retain(|id| id != which);
+
+				if self.open.remove(which).is_some() || was_virtual {
+					Some(ControllerChange::Disconnected{ id: *which })
+				}
+				else {
+					None
+				}
+			},
+			_ => None,
+		};
+		Ok(change)
+	}
+
+	// Why any controllers plugged in before new() were skipped
+	pub fn startup_errors(&self) -> &[Error] {
+		&self.startup_errors
+	}
+
+	// Connected controllers, real and virtual
+	pub fn len(&self) -> usize {
+		self.open.len() + self.virtual_ids.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+		self.open.keys().copied().chain(self.virtual_ids.iter().copied())
+	}
+
+	pub fn name(&self, id: u32) -> Option<String> {
+		self.open.get(&id).map(GameController::name)
+	}
+
+	// Low and high frequency motors, 0 - 0xFFFF, for `ms` milliseconds.
+	// Does nothing for controllers that can't.
+	pub fn rumble(&mut self, id: u32, low: u16, high: u16, ms: u32) {
+		if let Some(c) = self.open.get_mut(&id) {
+			let _ = c.set_rumble(low, high, ms);
+		}
+	}
+}
+
+// A pretend controller, for driving Input without hardware (tests, demos,
+// replays). Its methods build the same events a real controller would send;
+// hand them to Input::handle_event() directly, or SDLCore::push_event() to
+// go through the event queue like everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VirtualController {
+	id: u32,
+}
+
+impl VirtualController {
+	// `n` tells virtual controllers apart, 0 for the first one
+	pub fn new(n: u32) -> VirtualController {
+		VirtualController {
+			id: VIRTUAL_BASE + n,
+		}
+	}
+
+	pub fn id(&self) -> u32 {
+		self.id
+	}
+
+	pub fn connect(&self) -> Event {
+		Event::ControllerDeviceAdded{ timestamp: 0, which: self.id }
+	}
+
+	pub fn disconnect(&self) -> Event {
+		Event::ControllerDeviceRemoved{ timestamp: 0, which: self.id }
+	}
+
+	pub fn press(&self, button: Button) -> Event {
+		Event::ControllerButtonDown{ timestamp: 0, which: self.id, button }
+	}
+
+	pub fn release(&self, button: Button) -> Event {
+		Event::ControllerButtonUp{ timestamp: 0, which: self.id, button }
+	}
+
+	// value is -1.0 to 1.0 (0.0 to 1.0 for triggers)
+	pub fn move_axis(&self, axis: Axis, value: f32) -> Event {
+		Event::ControllerAxisMotion {
+			timestamp: 0,
+			which: self.id,
+			axis,
+			value: (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::input::{AxisBinding, Binding, Input, DEFAULT_DEAD_ZONE};
+
+	fn input() -> Input {
+		let mut input = Input::new();
+		input.bind("jump", Binding::Button(Button::A));
+		input.bind("right", Binding::AxisPositive(Axis::LeftX));
+		input.bind_axis("move_x", AxisBinding::new().analog(Axis::LeftX));
+		input
+	}
+
+	#[test]
+	fn virtual_controller_buttons() {
+		let pad = VirtualController::new(0);
+		let mut input = input();
+
+		input.update(&[pad.connect()]);
+		assert!(!input.pressed("jump"));
+
+		input.update(&[pad.press(Button::A)]);
+		assert!(input.just_pressed("jump"));
+
+		input.update(&[]);
+		assert!(input.pressed("jump"));
+		assert!(!input.just_pressed("jump"));
+
+		input.update(&[pad.release(Button::A)]);
+		assert!(!input.pressed("jump"));
+		assert!(input.just_released("jump"));
+
+		// Not bound to anything
+		input.update(&[pad.press(Button::B)]);
+		assert!(!input.pressed("jump"));
+	}
+
+	#[test]
+	fn virtual_controller_sticks_and_dead_zone() {
+		let pad = VirtualController::new(0);
+		let mut input = input();
+		input.update(&[pad.connect()]);
+
+		// Inside the dead zone counts as centered
+		input.update(&[pad.move_axis(Axis::LeftX, DEFAULT_DEAD_ZONE / 2.0)]);
+		assert_eq!(input.axis("move_x"), 0.0);
+		assert!(!input.pressed("right"));
+
+		input.update(&[pad.move_axis(Axis::LeftX, -DEFAULT_DEAD_ZONE)]);
+		assert_eq!(input.axis("move_x"), 0.0);
+
+		// Past it, stretched so the edge of the dead zone is 0.0
+		input.update(&[pad.move_axis(Axis::LeftX, 0.9)]);
+		let expected = (0.9 - DEFAULT_DEAD_ZONE) / (1.0 - DEFAULT_DEAD_ZONE);
+		assert!((input.axis("move_x") - expected).abs() < 0.001, "{}", input.axis("move_x"));
+		assert!(input.just_pressed("right"));
+
+		input.update(&[pad.move_axis(Axis::LeftX, -1.0)]);
+		assert_eq!(input.axis("move_x"), -1.0);
+		assert!(input.just_released("right"));
+
+		input.update(&[pad.move_axis(Axis::LeftX, 0.0)]);
+		assert_eq!(input.axis("move_x"), 0.0);
+	}
+
+	#[test]
+	fn disconnecting_releases_what_was_held() {
+		let pad = VirtualController::new(0);
+		let mut input = input();
+
+		input.update(&[pad.connect(), pad.press(Button::A), pad.move_axis(Axis::LeftX, 1.0)]);
+		assert!(input.pressed("jump"));
+		assert!(input.pressed("right"));
+		assert_eq!(input.axis("move_x"), 1.0);
+
+		input.update(&[pad.disconnect()]);
+		assert!(!input.pressed("jump"));
+		assert!(input.just_released("jump"));
+		assert!(!input.pressed("right"));
+		assert_eq!(input.axis("move_x"), 0.0);
+	}
+
+	#[test]
+	fn controllers_dont_undo_each_other() {
+		let (one, two) = (VirtualController::new(0), VirtualController::new(1));
+		assert_ne!(one.id(), two.id());
+		let mut input = input();
+
+		input.update(&[one.connect(), two.connect(), one.press(Button::A), two.press(Button::A)]);
+		input.update(&[one.release(Button::A)]);
+		assert!(input.pressed("jump"));
+
+		// The other stick is still pushed
+		input.update(&[one.move_axis(Axis::LeftX, 1.0), two.move_axis(Axis::LeftX, 1.0)]);
+		input.update(&[one.disconnect()]);
+		assert_eq!(input.axis("move_x"), 1.0);
+		assert!(input.pressed("jump"));
+
+		input.update(&[two.disconnect()]);
+		assert!(!input.pressed("jump"));
+		assert_eq!(input.axis("move_x"), 0.0);
+	}
+}
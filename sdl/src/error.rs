@@ -0,0 +1,138 @@
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use sdl2::IntegerOrSdlError;
+use sdl2::video::WindowBuildError;
+
+// SDL reports most failures as a bare message, this lets them be an error
+// source like any other
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdlError(pub String);
+
+impl fmt::Display for SdlError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl error::Error for SdlError {}
+
+#[derive(Debug)]
+pub enum Error {
+	// Starting SDL itself or one of its subsystems
+	Init(SdlError),
+	Window(WindowBuildError),
+	Renderer(IntegerOrSdlError),
+	// An image, sound, font, etc. that couldn't be read or decoded
+	Asset {
+		path: PathBuf,
+		source: Box<dyn error::Error + Send + Sync>,
+	},
+	Audio(SdlError),
+	Font(SdlError),
+	// Any other SDL call that failed, e.g. drawing to the canvas
+	Sdl(SdlError),
+	// Anything not from SDL
+	Other(String),
+}
+
+impl Error {
+	pub fn asset<P, E>(path: P, source: E) -> Error
+		where
+			P: AsRef<Path>,
+			E: Into<Box<dyn error::Error + Send + Sync>>,
+	{
+		Error::Asset {
+			path: path.as_ref().to_path_buf(),
+			source: source.into(),
+		}
+	}
+
+	pub fn init(e: String) -> Error {
+		Error::Init(SdlError(e))
+	}
+
+	pub fn audio(e: String) -> Error {
+		Error::Audio(SdlError(e))
+	}
+
+	pub fn font(e: String) -> Error {
+		Error::Font(SdlError(e))
+	}
+}
+
+// Display says what we were doing, source() says what went wrong
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Init(_) => write!(f, "Could not initialize SDL"),
+			Error::Window(_) => write!(f, "Could not create window"),
+			Error::Renderer(_) => write!(f, "Could not set up renderer"),
+			Error::Asset{ path, .. } => write!(f, "Could not load {}", path.display()),
+			Error::Audio(_) => write!(f, "Audio error"),
+			Error::Font(_) => write!(f, "Font error"),
+			Error::Sdl(_) => write!(f, "SDL error"),
+			Error::Other(msg) => write!(f, "{}", msg),
+		}
+	}
+}
+
+impl error::Error for Error {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match self {
+			Error::Init(e) | Error::Audio(e) | Error::Font(e) | Error::Sdl(e) => Some(e),
+			Error::Window(e) => Some(e),
+			Error::Renderer(e) => Some(e),
+			Error::Asset{ source, .. } => Some(source.as_ref()),
+			Error::Other(_) => None,
+		}
+	}
+}
+
+// Most sdl2 calls return Result<_, String>, this lets `?` work on them
+impl From<String> for Error {
+	fn from(e: String) -> Error {
+		Error::Sdl(SdlError(e))
+	}
+}
+
+impl From<WindowBuildError> for Error {
+	fn from(e: WindowBuildError) -> Error {
+		Error::Window(e)
+	}
+}
+
+impl From<IntegerOrSdlError> for Error {
+	fn from(e: IntegerOrSdlError) -> Error {
+		Error::Renderer(e)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::io;
+
+	fn boxed<E: error::Error + Send + Sync + 'static>(e: E) -> Box<dyn error::Error + Send + Sync> {
+		Box::new(e)
+	}
+
+	#[test]
+	fn errors_can_cross_threads() {
+		let e = Error::asset("images/missing.png", io::Error::new(io::ErrorKind::NotFound, "no such file"));
+		let e = std::thread::spawn(move || e).join().unwrap();
+
+		// So it converts into the usual boxed error (and anyhow's, etc.)
+		let b = boxed(e);
+		assert_eq!(b.to_string(), "Could not load images/missing.png");
+		assert_eq!(b.source().unwrap().to_string(), "no such file");
+	}
+
+	#[test]
+	fn asset_takes_messages_too() {
+		let e = Error::asset("config/input.json", "unknown key \"Jumpy\"");
+		assert_eq!(error::Error::source(&e).unwrap().to_string(), "unknown key \"Jumpy\"");
+	}
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use sdl2::event::Event;
+
+use crate::SDLCore;
+use crate::Error;
+use crate::profiler::Phase;
+
+// Returned from an update callback to keep going or leave the loop
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+	Continue,
+	Quit,
+}
+
+// Runs game logic at a fixed rate, no matter how fast frames are drawn.
+//
+// Each frame, the real time since the last frame is added to an accumulator,
+// and update is called once per whole step it holds. Render then gets the
+// leftover fraction of a step (alpha, 0.0 - 1.0) so it can interpolate
+// between the previous and current state.
+pub struct GameLoop {
+	step: Duration,
+	max_frame_time: Duration,
+}
+
+impl GameLoop {
+	pub fn new(updates_per_sec: u32) -> GameLoop {
+		GameLoop {
+			step: Duration::from_secs(1) / updates_per_sec.max(1),
+			// A slow frame could otherwise queue up more updates than we can
+			// run in a frame, making the next frame slower still (the
+			// "spiral of death"), so drop time past this
+			max_frame_time: Duration::from_millis(250),
+		}
+	}
+
+	pub fn max_frame_time(mut self, max_frame_time: Duration) -> GameLoop {
+		self.max_frame_time = max_frame_time;
+		self
+	}
+
+	pub fn step(&self) -> Duration {
+		self.step
+	}
+
+	// Runs until update returns Flow::Quit or the window is closed.
+	//
+	// update gets the game state, the events polled since the previous
+	// update, and the step length in seconds. render gets the state and
+	// alpha, and the frame is presented after it returns.
+	pub fn run<S, U, R>(
+		&self,
+		core: &mut SDLCore,
+		state: &mut S,
+		mut update: U,
+		mut render: R,
+	) -> Result<(), Error>
+		where
+			U: FnMut(&mut SDLCore, &mut S, &[Event], f32) -> Result<Flow, Error>,
+			R: FnMut(&mut SDLCore, &S, f32) -> Result<(), Error>,
+	{
+		let dt = self.step.as_secs_f32();
+
+		// Events wait here until an update gets to see them
+		let mut events = Vec::new();
+		let mut accumulator = Duration::from_secs(0);
+
+		'gameloop: loop {
+			for event in core.poll_events() {
+				if let Event::Quit{..} = event {
+					break 'gameloop;
+				}
+				events.push(event);
+			}
+
+			// Goes by SDLCore's frame times, so replays run the same
+			// number of updates
+			accumulator += Duration::from_secs_f32(core.delta()).min(self.max_frame_time);
+
+			while accumulator >= self.step {
+				core.begin_phase(Phase::Update);
+				let flow = update(core, state, &events, dt)?;
+				core.end_phase(Phase::Update);
+
+				if flow == Flow::Quit {
+					break 'gameloop;
+				}
+				events.clear();
+
+				accumulator -= self.step;
+			}
+
+			let alpha = accumulator.as_secs_f32() / dt;
+			core.begin_phase(Phase::Render);
+			render(core, state, alpha)?;
+			core.end_phase(Phase::Render);
+
+			core.present()?;
+		}
+
+		Ok(())
+	}
+}
+
+// Blend between the last two update states for drawing
+pub fn lerp(prev: f32, cur: f32, alpha: f32) -> f32 {
+	prev + (cur - prev) * alpha
+}
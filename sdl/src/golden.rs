@@ -0,0 +1,151 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::capture;
+use crate::capture::Frame;
+use crate::Demo;
+use crate::Error;
+
+// Set this environment variable to (re)write golden images from the current
+// output instead of comparing against them
+pub const UPDATE_VAR: &str = "SDL_RUST_UPDATE_GOLDEN";
+
+// How close a capture has to be to its golden image to pass
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+	// Per-channel difference still counted as a match
+	pub channel: u8,
+	// Fraction of pixels (0.0 - 1.0) allowed to mismatch
+	pub pixels: f64,
+}
+
+impl Tolerance {
+	pub fn exact() -> Tolerance {
+		Tolerance {
+			channel: 0,
+			pixels: 0.0,
+		}
+	}
+}
+
+impl Default for Tolerance {
+	// Forgives rounding differences between software renderer versions
+	fn default() -> Tolerance {
+		Tolerance {
+			channel: 2,
+			pixels: 0.001,
+		}
+	}
+}
+
+// Runs a Demo headlessly, grabbing the given frames (counted from 0 by
+// SDLCore::present) and quitting after the last one. The demo must present
+// through SDLCore::present and leave its loop on Event::Quit.
+//
+// Only one SDL event pump can exist at a time, so don't run these in
+// parallel (e.g., use `cargo test -- --test-threads=1`).
+pub fn capture_demo<F, D>(initter: F, frames: &[u32]) -> Result<Vec<Frame>, Error>
+	where
+		F: Fn() -> Result<D, Error>,
+		D: Demo,
+{
+	capture::start_script(frames);
+
+	let result = initter().and_then(|mut d| d.run());
+	let captured = capture::finish_script();
+	result?;
+
+	let mut wanted = frames.to_vec();
+	wanted.sort_unstable();
+	wanted.dedup();
+
+	if captured.len() != wanted.len() {
+		return Err(Error::Other(format!(
+			"Demo exited after {} of {} requested frames",
+			captured.len(),
+			wanted.len(),
+		)));
+	}
+
+	Ok(captured)
+}
+
+fn golden_path(dir: &Path, name: &str, frame: u32) -> PathBuf {
+	dir.join(format!("{}_{:04}.png", name, frame))
+}
+
+// Compares captured frames against `<dir>/<name>_<frame>.png`. Mismatches,
+// and frames with no golden yet, are saved alongside the golden as
+// `.actual.png` for inspection.
+pub fn check_frames(
+	dir: &Path,
+	name: &str,
+	frames: &[u32],
+	captured: &[Frame],
+	tolerance: Tolerance,
+) -> Result<(), Error>
+{
+	let mut frames = frames.to_vec();
+	frames.sort_unstable();
+	frames.dedup();
+
+	let update = env::var_os(UPDATE_VAR).is_some();
+	if update {
+		fs::create_dir_all(dir).map_err(|e| Error::asset(dir, e))?;
+	}
+
+	let mut failures = Vec::new();
+	for (&frame, actual) in frames.iter().zip(captured.iter()) {
+		let path = golden_path(dir, name, frame);
+
+		if update {
+			actual.save_png(&path)?;
+			continue;
+		}
+
+		// Not made yet: still a failure, but one that says what to do
+		if !path.exists() {
+			actual.save_png(path.with_extension("actual.png"))?;
+			failures.push(format!(
+				"{}: no golden image; run with {}=1 to create it, then check it by eye",
+				path.display(), UPDATE_VAR,
+			));
+			continue;
+		}
+
+		let expected = Frame::load_png(&path)?;
+
+		let diff = actual.diff(&expected, tolerance.channel)?;
+		if diff.mismatched_fraction() > tolerance.pixels {
+			actual.save_png(path.with_extension("actual.png"))?;
+			failures.push(format!(
+				"{}: {} of {} pixels differ (max channel delta {})",
+				path.display(), diff.mismatched, diff.total, diff.max_delta,
+			));
+		}
+	}
+
+	if failures.is_empty() {
+		Ok(())
+	}
+	else {
+		Err(Error::Other(failures.join("\n")))
+	}
+}
+
+// capture_demo followed by check_frames, for use in tests
+pub fn check_demo<F, D>(
+	dir: &Path,
+	name: &str,
+	initter: F,
+	frames: &[u32],
+	tolerance: Tolerance,
+) -> Result<(), Error>
+	where
+		F: Fn() -> Result<D, Error>,
+		D: Demo,
+{
+	let captured = capture_demo(initter, frames)?;
+	check_frames(dir, name, frames, &captured, tolerance)
+}
@@ -0,0 +1,674 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use sdl2::controller::{Axis, Button};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseButton;
+use serde_json::{json, Value};
+
+use crate::Error;
+
+// How far a stick or trigger has to go to count as pressed, for bindings
+// that treat half an axis as a button
+const AXIS_THRESHOLD: f32 = 0.5;
+
+// Sticks rarely rest at exactly 0.0, so anything this close to center counts
+// as centered
+pub const DEFAULT_DEAD_ZONE: f32 = 0.2;
+
+// One physical input that can trigger an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+	// By position on the keyboard, so WASD stays put on other layouts
+	Key(Scancode),
+	Mouse(MouseButton),
+	Button(Button),
+	// Half of a stick or trigger, pressed past AXIS_THRESHOLD
+	AxisPositive(Axis),
+	AxisNegative(Axis),
+}
+
+pub(crate) fn mouse_name(b: MouseButton) -> &'static str {
+	match b {
+		MouseButton::Left => "left",
+		MouseButton::Middle => "middle",
+		MouseButton::Right => "right",
+		MouseButton::X1 => "x1",
+		MouseButton::X2 => "x2",
+		MouseButton::Unknown => "unknown",
+	}
+}
+
+pub(crate) fn mouse_from_name(name: &str) -> Option<MouseButton> {
+	match name {
+		"left" => Some(MouseButton::Left),
+		"middle" => Some(MouseButton::Middle),
+		"right" => Some(MouseButton::Right),
+		"x1" => Some(MouseButton::X1),
+		"x2" => Some(MouseButton::X2),
+		_ => None,
+	}
+}
+
+// As written in config files: "key:W", "mouse:left", "button:a",
+// "axis:leftx+" or "axis:lefty-"
+impl fmt::Display for Binding {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Binding::Key(s) => write!(f, "key:{}", s.name()),
+			Binding::Mouse(b) => write!(f, "mouse:{}", mouse_name(*b)),
+			Binding::Button(b) => write!(f, "button:{}", b.string()),
+			Binding::AxisPositive(a) => write!(f, "axis:{}+", a.string()),
+			Binding::AxisNegative(a) => write!(f, "axis:{}-", a.string()),
+		}
+	}
+}
+
+impl FromStr for Binding {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Binding, String> {
+		let (kind, name) = s.split_once(':')
+			.ok_or_else(|| format!("\"{}\" should look like \"key:W\"", s))?;
+
+		let binding = match kind {
+			"key" => Scancode::from_name(name).map(Binding::Key),
+			"mouse" => mouse_from_name(name).map(Binding::Mouse),
+			"button" => Button::from_string(name).map(Binding::Button),
+			"axis" => {
+				if let Some(axis) = name.strip_suffix('+') {
+					Axis::from_string(axis).map(Binding::AxisPositive)
+				}
+				else if let Some(axis) = name.strip_suffix('-') {
+					Axis::from_string(axis).map(Binding::AxisNegative)
+				}
+				else {
+					return Err(format!("\"{}\" needs a + or - for which half of the axis", s));
+				}
+			},
+			_ => return Err(format!("unknown input kind \"{}\" in \"{}\"", kind, s)),
+		};
+
+		binding.ok_or_else(|| format!("unknown {} \"{}\"", kind, name))
+	}
+}
+
+// What drives a named axis. Buttons count as -1.0 or 1.0 while held, and
+// the analog axes give whatever the stick reads; the strongest input wins.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxisBinding {
+	negative: Vec<Binding>,
+	positive: Vec<Binding>,
+	analog: Vec<Axis>,
+}
+
+impl AxisBinding {
+	pub fn new() -> AxisBinding {
+		AxisBinding::default()
+	}
+
+	pub fn negative(mut self, b: Binding) -> AxisBinding {
+		self.negative.push(b);
+		self
+	}
+
+	pub fn positive(mut self, b: Binding) -> AxisBinding {
+		self.positive.push(b);
+		self
+	}
+
+	pub fn analog(mut self, a: Axis) -> AxisBinding {
+		self.analog.push(a);
+		self
+	}
+}
+
+// Maps keys, mouse buttons and controller inputs to named actions ("jump")
+// and axes ("move_x"), so games can ask about those instead of Keycodes.
+//
+// Feed it every event, once per update:
+//
+//     input.update(&events);
+//     if input.just_pressed("jump") { ... }
+//     let dx = input.axis("move_x") * SPEED * dt;
+//
+// Controllers need to be opened to send anything; see controller::Controllers.
+#[derive(Clone, Debug)]
+pub struct Input {
+	actions: HashMap<String, Vec<Binding>>,
+	axes: HashMap<String, AxisBinding>,
+	// Held now, held at the start of this update, and pressed during it
+	held: HashSet<Binding>,
+	held_before: HashSet<Binding>,
+	pressed_now: HashSet<Binding>,
+	// Per controller (by instance id), so two controllers don't undo each
+	// other's input
+	buttons: HashMap<u32, HashSet<Button>>,
+	axis_values: HashMap<(u32, Axis), f32>,
+	dead_zone: f32,
+	axis_dead_zones: HashMap<Axis, f32>,
+	// Action waiting for the next input to be bound to it
+	listening: Option<String>,
+}
+
+impl Default for Input {
+	fn default() -> Input {
+		Input::new()
+	}
+}
+
+impl Input {
+	pub fn new() -> Input {
+		Input {
+			actions: HashMap::new(),
+			axes: HashMap::new(),
+			held: HashSet::new(),
+			held_before: HashSet::new(),
+			pressed_now: HashSet::new(),
+			buttons: HashMap::new(),
+			axis_values: HashMap::new(),
+			dead_zone: DEFAULT_DEAD_ZONE,
+			axis_dead_zones: HashMap::new(),
+			listening: None,
+		}
+	}
+
+	// Starts a new update: everything pressed from here on is "just" pressed
+	pub fn begin_frame(&mut self) {
+		self.held_before = self.held.clone();
+		self.pressed_now.clear();
+	}
+
+	// Same as begin_frame(), then handle_event() for each event
+	pub fn update(&mut self, events: &[Event]) {
+		self.begin_frame();
+		for e in events {
+			self.handle_event(e);
+		}
+	}
+
+	pub fn handle_event(&mut self, event: &Event) {
+		match event {
+			// Key repeat isn't a new press
+			Event::KeyDown{ scancode: Some(s), repeat: false, .. } => self.press(Binding::Key(*s)),
+			Event::KeyUp{ scancode: Some(s), .. } => self.release(Binding::Key(*s)),
+			Event::MouseButtonDown{ mouse_btn, .. } => self.press(Binding::Mouse(*mouse_btn)),
+			Event::MouseButtonUp{ mouse_btn, .. } => self.release(Binding::Mouse(*mouse_btn)),
+			Event::ControllerButtonDown{ which, button, .. } => {
+				self.buttons.entry(*which).or_default().insert(*button);
+				self.press(Binding::Button(*button));
+			},
+			Event::ControllerButtonUp{ which, button, .. } => {
+				if let Some(held) = self.buttons.get_mut(which) {
+					held.remove(button);
+				}
+				self.update_button(*button);
+			},
+			Event::ControllerAxisMotion{ which, axis, value, .. } => {
+				let value = (*value as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+				self.axis_values.insert((*which, *axis), self.apply_dead_zone(*axis, value));
+				self.update_axis(*axis);
+			},
+			// Let go of everything an unplugged controller was holding
+			Event::ControllerDeviceRemoved{ which, .. } => {
+				let buttons = self.buttons.remove(which).unwrap_or_default();
+				for b in buttons {
+					self.update_button(b);
+				}
+
+				let axes: Vec<Axis> = self.axis_values.keys()
+					.filter(|(id, _)| id == which)
+					.map(|&(_, a)| a)
+					.collect();
+				for a in axes {
+					self.axis_values.remove(&(*which, a));
+					self.update_axis(a);
+				}
+			},
+			// We won't hear about anything released while we're not focused
+			Event::Window{ win_event: WindowEvent::FocusLost, .. } => self.reset(),
+			_ => {},
+		}
+	}
+
+	fn press(&mut self, b: Binding) {
+		// Sticks send motion the whole time they're held, only count the
+		// first time past the threshold
+		if !self.held.insert(b) {
+			return;
+		}
+		self.pressed_now.insert(b);
+
+		if let Some(action) = self.listening.take() {
+			self.actions.insert(action, vec![b]);
+		}
+	}
+
+	fn release(&mut self, b: Binding) {
+		self.held.remove(&b);
+	}
+
+	// Held if any controller is still holding it
+	fn update_button(&mut self, button: Button) {
+		if !self.buttons.values().any(|held| held.contains(&button)) {
+			self.release(Binding::Button(button));
+		}
+	}
+
+	// Re-checks both halves of `axis` against every controller
+	fn update_axis(&mut self, axis: Axis) {
+		let value = self.analog(axis);
+
+		for (b, on) in [
+			(Binding::AxisPositive(axis), value >= AXIS_THRESHOLD),
+			(Binding::AxisNegative(axis), value <= -AXIS_THRESHOLD),
+		] {
+			if on {
+				self.press(b);
+			}
+			else {
+				self.release(b);
+			}
+		}
+	}
+
+	// Inside the dead zone is 0.0, and the rest is stretched back out to
+	// 1.0 so there's no jump at its edge
+	fn apply_dead_zone(&self, axis: Axis, value: f32) -> f32 {
+		let dz = self.dead_zone(axis);
+		if value.abs() <= dz || dz >= 1.0 {
+			0.0
+		}
+		else {
+			value.signum() * (value.abs() - dz) / (1.0 - dz)
+		}
+	}
+
+	// Strongest reading of `axis` across all controllers
+	fn analog(&self, axis: Axis) -> f32 {
+		self.axis_values.iter()
+			.filter(|((_, a), _)| *a == axis)
+			.map(|(_, &v)| v)
+			.fold(0.0, |best: f32, v| if v.abs() > best.abs() { v } else { best })
+	}
+
+	// Forget everything held, e.g., when switching scenes
+	pub fn reset(&mut self) {
+		self.held.clear();
+		self.pressed_now.clear();
+		self.buttons.clear();
+		self.axis_values.clear();
+	}
+
+	// For every axis, 0.0 - 1.0. Readings already taken keep the old one
+	// until the stick moves again.
+	pub fn set_dead_zone(&mut self, dead_zone: f32) {
+		self.dead_zone = dead_zone.clamp(0.0, 1.0);
+	}
+
+	// Override for one axis, e.g., a smaller one for triggers
+	pub fn set_axis_dead_zone(&mut self, axis: Axis, dead_zone: f32) {
+		self.axis_dead_zones.insert(axis, dead_zone.clamp(0.0, 1.0));
+	}
+
+	pub fn dead_zone(&self, axis: Axis) -> f32 {
+		self.axis_dead_zones.get(&axis).copied().unwrap_or(self.dead_zone)
+	}
+
+	fn any<'b, I>(bindings: I, set: &HashSet<Binding>) -> bool
+		where I: IntoIterator<Item = &'b Binding>
+	{
+		bindings.into_iter().any(|b| set.contains(b))
+	}
+
+	fn bindings_for(&self, action: &str) -> &[Binding] {
+		self.actions.get(action).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	// Held down right now
+	pub fn pressed(&self, action: &str) -> bool {
+		Input::any(self.bindings_for(action), &self.held)
+	}
+
+	// Went down this update (even if it's already back up)
+	pub fn just_pressed(&self, action: &str) -> bool {
+		let bindings = self.bindings_for(action);
+		!Input::any(bindings, &self.held_before) && Input::any(bindings, &self.pressed_now)
+	}
+
+	// Went up this update
+	pub fn just_released(&self, action: &str) -> bool {
+		let bindings = self.bindings_for(action);
+		let was_down = Input::any(bindings, &self.held_before) || Input::any(bindings, &self.pressed_now);
+		was_down && !Input::any(bindings, &self.held)
+	}
+
+	// -1.0 to 1.0
+	pub fn axis(&self, name: &str) -> f32 {
+		let a = match self.axes.get(name) {
+			Some(a) => a,
+			None => return 0.0,
+		};
+
+		let mut value: f32 = 0.0;
+		if Input::any(&a.negative, &self.held) {
+			value -= 1.0;
+		}
+		if Input::any(&a.positive, &self.held) {
+			value += 1.0;
+		}
+
+		for &axis in &a.analog {
+			let v = self.analog(axis);
+			if v.abs() > value.abs() {
+				value = v;
+			}
+		}
+
+		value
+	}
+
+	pub fn bind(&mut self, action: &str, b: Binding) {
+		let bindings = self.actions.entry(action.to_string()).or_default();
+		if !bindings.contains(&b) {
+			bindings.push(b);
+		}
+	}
+
+	pub fn unbind(&mut self, action: &str, b: Binding) -> bool {
+		match self.actions.get_mut(action) {
+			Some(bindings) => {
+				let before = bindings.len();
+				bindings.retain(|&x| x != b);
+				bindings.len() != before
+			},
+			None => false,
+		}
+	}
+
+	pub fn clear(&mut self, action: &str) {
+		self.actions.remove(action);
+	}
+
+	pub fn bindings(&self, action: &str) -> &[Binding] {
+		self.bindings_for(action)
+	}
+
+	pub fn bind_axis(&mut self, name: &str, a: AxisBinding) {
+		self.axes.insert(name.to_string(), a);
+	}
+
+	pub fn axis_binding(&self, name: &str) -> Option<&AxisBinding> {
+		self.axes.get(name)
+	}
+
+	// The next key, button or stick pressed replaces whatever `action` was
+	// bound to, for "press a key for Jump" menus
+	pub fn listen(&mut self, action: &str) {
+		self.listening = Some(action.to_string());
+	}
+
+	pub fn cancel_listen(&mut self) {
+		self.listening = None;
+	}
+
+	pub fn listening(&self) -> Option<&str> {
+		self.listening.as_deref()
+	}
+
+	// Bindings from a JSON file like:
+	//
+	//     {
+	//       "actions": { "jump": ["key:Space", "button:a"] },
+	//       "axes": {
+	//         "move_x": { "negative": ["key:A"], "positive": ["key:D"], "analog": ["leftx"] }
+	//       },
+	//       "dead_zones": { "default": 0.2, "lefttrigger": 0.05 }
+	//     }
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Input, Error> {
+		let path = path.as_ref();
+
+		let text = fs::read_to_string(path).map_err(|e| Error::asset(path, e))?;
+		let v: Value = serde_json::from_str(&text).map_err(|e| Error::asset(path, e))?;
+
+		let bindings = |v: Option<&Value>| -> Result<Vec<Binding>, Error> {
+			v.and_then(Value::as_array)
+				.into_iter()
+				.flatten()
+				.map(|b| b.as_str().unwrap_or("").parse().map_err(|e: String| Error::asset(path, e)))
+				.collect()
+		};
+
+		let mut input = Input::new();
+
+		for (name, list) in v.get("actions").and_then(Value::as_object).into_iter().flatten() {
+			input.actions.insert(name.clone(), bindings(Some(list))?);
+		}
+
+		for (name, a) in v.get("axes").and_then(Value::as_object).into_iter().flatten() {
+			let analog = a.get("analog").and_then(Value::as_array)
+				.into_iter()
+				.flatten()
+				.map(|s| {
+					let s = s.as_str().unwrap_or("");
+					Axis::from_string(s).ok_or_else(|| Error::asset(path, format!("unknown axis \"{}\"", s)))
+				})
+				.collect::<Result<_, _>>()?;
+
+			input.axes.insert(name.clone(), AxisBinding {
+				negative: bindings(a.get("negative"))?,
+				positive: bindings(a.get("positive"))?,
+				analog,
+			});
+		}
+
+		for (name, dz) in v.get("dead_zones").and_then(Value::as_object).into_iter().flatten() {
+			let dz = dz.as_f64()
+				.ok_or_else(|| Error::asset(path, format!("dead zone for \"{}\" isn't a number", name)))? as f32;
+
+			if name == "default" {
+				input.set_dead_zone(dz);
+			}
+			else {
+				let axis = Axis::from_string(name)
+					.ok_or_else(|| Error::asset(path, format!("unknown axis \"{}\"", name)))?;
+				input.set_axis_dead_zone(axis, dz);
+			}
+		}
+
+		Ok(input)
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let path = path.as_ref();
+
+		let strings = |bs: &[Binding]| bs.iter().map(Binding::to_string).collect::<Vec<_>>();
+
+		// Sorted, so saved files diff nicely
+		let actions: BTreeMap<_, _> = self.actions.iter()
+			.map(|(name, bs)| (name.clone(), strings(bs)))
+			.collect();
+		let axes: BTreeMap<_, _> = self.axes.iter()
+			.map(|(name, a)| (name.clone(), json!({
+				"negative": strings(&a.negative),
+				"positive": strings(&a.positive),
+				"analog": a.analog.iter().map(|x| x.string()).collect::<Vec<_>>(),
+			})))
+			.collect();
+
+		let mut dead_zones: BTreeMap<_, _> = self.axis_dead_zones.iter()
+			.map(|(a, &dz)| (a.string(), dz))
+			.collect();
+		dead_zones.insert("default".to_string(), self.dead_zone);
+
+		let text = serde_json::to_string_pretty(&json!({
+			"actions": actions,
+			"axes": axes,
+			"dead_zones": dead_zones,
+		})).map_err(|e| Error::asset(path, e))?;
+
+		fs::write(path, text + "\n").map_err(|e| Error::asset(path, e))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use sdl2::keyboard::{Keycode, Mod};
+
+	fn key_down(s: Scancode) -> Event {
+		Event::KeyDown {
+			timestamp: 0,
+			window_id: 0,
+			keycode: Keycode::from_scancode(s),
+			scancode: Some(s),
+			keymod: Mod::NOMOD,
+			repeat: false,
+		}
+	}
+
+	fn key_up(s: Scancode) -> Event {
+		Event::KeyUp {
+			timestamp: 0,
+			window_id: 0,
+			keycode: Keycode::from_scancode(s),
+			scancode: Some(s),
+			keymod: Mod::NOMOD,
+			repeat: false,
+		}
+	}
+
+	#[test]
+	fn bindings_round_trip() {
+		let cases = [
+			(Binding::Key(Scancode::W), "key:W"),
+			(Binding::Key(Scancode::Space), "key:Space"),
+			(Binding::Key(Scancode::Left), "key:Left"),
+			(Binding::Mouse(MouseButton::Left), "mouse:left"),
+			(Binding::Mouse(MouseButton::Middle), "mouse:middle"),
+			(Binding::Mouse(MouseButton::Right), "mouse:right"),
+			(Binding::Mouse(MouseButton::X1), "mouse:x1"),
+			(Binding::Mouse(MouseButton::X2), "mouse:x2"),
+			(Binding::Button(Button::A), "button:a"),
+			(Binding::Button(Button::Start), "button:start"),
+			(Binding::Button(Button::DPadUp), "button:dpup"),
+			(Binding::AxisPositive(Axis::LeftX), "axis:leftx+"),
+			(Binding::AxisNegative(Axis::LeftY), "axis:lefty-"),
+			(Binding::AxisPositive(Axis::TriggerRight), "axis:righttrigger+"),
+		];
+
+		for (binding, text) in cases.iter() {
+			assert_eq!(binding.to_string(), *text);
+			assert_eq!(text.parse::<Binding>(), Ok(*binding), "parsing {}", text);
+		}
+	}
+
+	#[test]
+	fn bad_bindings_dont_parse() {
+		for text in ["W", "key:NotAKey", "mouse:thumb", "button:z", "axis:leftx", "axis:sideways+", "pedal:left"].iter() {
+			assert!(text.parse::<Binding>().is_err(), "{} parsed", text);
+		}
+	}
+
+	#[test]
+	fn just_pressed_and_released_last_one_frame() {
+		let mut input = Input::new();
+		input.bind("jump", Binding::Key(Scancode::Space));
+
+		input.update(&[key_down(Scancode::Space)]);
+		assert!(input.pressed("jump"));
+		assert!(input.just_pressed("jump"));
+		assert!(!input.just_released("jump"));
+
+		// Still held, but no longer new
+		input.update(&[]);
+		assert!(input.pressed("jump"));
+		assert!(!input.just_pressed("jump"));
+		assert!(!input.just_released("jump"));
+
+		input.update(&[key_up(Scancode::Space)]);
+		assert!(!input.pressed("jump"));
+		assert!(!input.just_pressed("jump"));
+		assert!(input.just_released("jump"));
+
+		input.update(&[]);
+		assert!(!input.just_released("jump"));
+	}
+
+	#[test]
+	fn tap_within_one_frame_is_pressed_and_released() {
+		let mut input = Input::new();
+		input.bind("jump", Binding::Key(Scancode::Space));
+
+		input.update(&[key_down(Scancode::Space), key_up(Scancode::Space)]);
+		assert!(!input.pressed("jump"));
+		assert!(input.just_pressed("jump"));
+		assert!(input.just_released("jump"));
+	}
+
+	fn temp_path(name: &str) -> std::path::PathBuf {
+		std::env::temp_dir().join(format!("sdl_rust_input_{}_{}.json", name, std::process::id()))
+	}
+
+	#[test]
+	fn save_and_load_round_trip() {
+		let mut input = Input::new();
+		input.bind("jump", Binding::Key(Scancode::Space));
+		input.bind("jump", Binding::Button(Button::A));
+		input.bind("fire", Binding::Mouse(MouseButton::Left));
+		input.bind("fire", Binding::AxisPositive(Axis::TriggerRight));
+		input.bind_axis("move_x", AxisBinding::new()
+			.negative(Binding::Key(Scancode::A))
+			.negative(Binding::Button(Button::DPadLeft))
+			.positive(Binding::Key(Scancode::D))
+			.analog(Axis::LeftX));
+		input.set_dead_zone(0.3);
+		input.set_axis_dead_zone(Axis::TriggerRight, 0.05);
+
+		let path = temp_path("round_trip");
+		input.save(&path).unwrap();
+		let loaded = Input::load(&path);
+		let _ = fs::remove_file(&path);
+		let loaded = loaded.unwrap();
+
+		assert_eq!(loaded.actions, input.actions);
+		assert_eq!(loaded.axes, input.axes);
+		assert_eq!(loaded.dead_zone(Axis::LeftX), 0.3);
+		assert_eq!(loaded.dead_zone(Axis::TriggerRight), 0.05);
+
+		// Loaded bindings work, not just compare equal
+		let mut loaded = loaded;
+		loaded.update(&[key_down(Scancode::Space)]);
+		assert!(loaded.just_pressed("jump"));
+	}
+
+	#[test]
+	fn unknown_names_dont_load() {
+		let files = [
+			("action", r#"{ "actions": { "jump": ["key:Space", "key:Jumpy"] } }"#, "Jumpy"),
+			("axis_binding", r#"{ "axes": { "move_x": { "negative": ["button:boost"] } } }"#, "boost"),
+			("analog", r#"{ "axes": { "move_x": { "analog": ["sidewaysx"] } } }"#, "sidewaysx"),
+			("dead_zone", r#"{ "dead_zones": { "leftz": 0.1 } }"#, "leftz"),
+		];
+
+		for (name, json, unknown) in files.iter() {
+			let path = temp_path(name);
+			fs::write(&path, json).unwrap();
+			let loaded = Input::load(&path);
+			let _ = fs::remove_file(&path);
+
+			match loaded {
+				Err(Error::Asset{ source, .. }) => {
+					assert!(source.to_string().contains(unknown), "{}: {}", name, source);
+				},
+				Err(e) => panic!("{}: {}", name, e),
+				Ok(_) => panic!("{} loaded", name),
+			}
+		}
+	}
+}
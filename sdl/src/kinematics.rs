@@ -0,0 +1,352 @@
+use sdl2::rect::Rect;
+
+use crate::collision::{Aabb, Contact, Moved};
+use crate::collision;
+
+// Something that moves: position, velocity and acceleration in f32, so slow
+// movement isn't lost to rounding. Only rect() rounds to whole pixels, for
+// drawing and for tile lookups.
+//
+// Units are pixels and seconds: velocity in px/s, acceleration in px/s².
+#[derive(Clone, Debug, PartialEq)]
+pub struct Body {
+	// Top-left corner
+	pos: (f32, f32),
+	vel: (f32, f32),
+	accel: (f32, f32),
+	width: u32,
+	height: u32,
+	max_speed: Option<f32>,
+	// Speed lost per second, px/s², like sliding friction
+	linear_drag: f32,
+	// Fraction of speed lost per second, like air resistance
+	drag: f32,
+}
+
+impl Body {
+	pub fn new(x: f32, y: f32, width: u32, height: u32) -> Body {
+		Body {
+			pos: (x, y),
+			vel: (0.0, 0.0),
+			accel: (0.0, 0.0),
+			width,
+			height,
+			max_speed: None,
+			linear_drag: 0.0,
+			drag: 0.0,
+		}
+	}
+
+	// Never goes faster than this, in any direction
+	pub fn max_speed(mut self, max_speed: f32) -> Body {
+		self.max_speed = Some(max_speed.max(0.0));
+		self
+	}
+
+	// Slows down by a fixed amount each second, and stops dead rather than
+	// reversing. Always on, even while accelerating, so the demos push at
+	// twice this to speed up as quickly as they stop.
+	pub fn linear_drag(mut self, linear_drag: f32) -> Body {
+		self.linear_drag = linear_drag.max(0.0);
+		self
+	}
+
+	// Slows down in proportion to speed: at 2.0, speed falls to 1/e (about
+	// a third) every half second. Never quite stops on its own.
+	pub fn drag(mut self, drag: f32) -> Body {
+		self.drag = drag.max(0.0);
+		self
+	}
+
+	pub fn pos(&self) -> (f32, f32) {
+		self.pos
+	}
+
+	pub fn set_pos(&mut self, x: f32, y: f32) {
+		self.pos = (x, y);
+	}
+
+	pub fn vel(&self) -> (f32, f32) {
+		self.vel
+	}
+
+	pub fn set_vel(&mut self, x: f32, y: f32) {
+		self.vel = (x, y);
+	}
+
+	pub fn accel(&self) -> (f32, f32) {
+		self.accel
+	}
+
+	// Stays until changed, so set it every frame from input (0.0 to coast)
+	pub fn set_accel(&mut self, x: f32, y: f32) {
+		self.accel = (x, y);
+	}
+
+	pub fn size(&self) -> (u32, u32) {
+		(self.width, self.height)
+	}
+
+	pub fn speed(&self) -> f32 {
+		self.vel.0.hypot(self.vel.1)
+	}
+
+	pub fn center(&self) -> (f32, f32) {
+		(
+			self.pos.0 + (self.width as f32) / 2.0,
+			self.pos.1 + (self.height as f32) / 2.0,
+		)
+	}
+
+	// Where to draw, rounded to the nearest pixel
+	pub fn rect(&self) -> Rect {
+		Rect::new(self.pos.0.round() as i32, self.pos.1.round() as i32, self.width, self.height)
+	}
+
+	// Zero velocity and acceleration
+	pub fn stop(&mut self) {
+		self.vel = (0.0, 0.0);
+		self.accel = (0.0, 0.0);
+	}
+
+	pub fn aabb(&self) -> Aabb {
+		Aabb::new(self.pos.0, self.pos.1, self.width as f32, self.height as f32)
+	}
+
+	// Keeps the body inside area, stopping it on any side it runs into
+	pub fn clamp_to(&mut self, area: Rect) {
+		let max_x = (area.right() - self.width as i32) as f32;
+		let max_y = (area.bottom() - self.height as i32) as f32;
+
+		let x = self.pos.0.clamp(area.x() as f32, max_x.max(area.x() as f32));
+		if x != self.pos.0 {
+			self.pos.0 = x;
+			self.vel.0 = 0.0;
+		}
+
+		let y = self.pos.1.clamp(area.y() as f32, max_y.max(area.y() as f32));
+		if y != self.pos.1 {
+			self.pos.1 = y;
+			self.vel.1 = 0.0;
+		}
+	}
+
+	// Velocity and position after dt seconds. Velocity is updated first
+	// (semi-implicit Euler), which stays stable at uneven frame times.
+	pub fn update(&mut self, dt: f32) {
+		self.update_vel(dt);
+
+		self.pos.0 += self.vel.0 * dt;
+		self.pos.1 += self.vel.1 * dt;
+	}
+
+	// Like update(), but stops at the first of `colliders` in the way on
+	// each axis instead of moving into it, keeps sliding along it, and loses
+	// velocity on the axis that hit. See collision::move_aabb.
+	pub fn move_and_slide(&mut self, dt: f32, colliders: &[Aabb]) -> Moved {
+		self.update_vel(dt);
+
+		let moved = collision::move_aabb(&self.aabb(), (self.vel.0 * dt, self.vel.1 * dt), colliders);
+		self.pos = (moved.aabb.x, moved.aabb.y);
+
+		if moved.hit_x.is_some() {
+			self.vel.0 = 0.0;
+		}
+		if moved.hit_y.is_some() {
+			self.vel.1 = 0.0;
+		}
+
+		moved
+	}
+
+	// Pushes the body out of whatever `contact` came from (with the body as
+	// the first shape), and cancels any velocity into it so it slides along
+	// instead
+	pub fn resolve(&mut self, contact: &Contact) {
+		let push = contact.push();
+		self.pos.0 += push.0;
+		self.pos.1 += push.1;
+
+		let into = self.vel.0 * contact.normal.0 + self.vel.1 * contact.normal.1;
+		if into < 0.0 {
+			self.vel.0 -= into * contact.normal.0;
+			self.vel.1 -= into * contact.normal.1;
+		}
+	}
+
+	fn update_vel(&mut self, dt: f32) {
+		self.vel.0 += self.accel.0 * dt;
+		self.vel.1 += self.accel.1 * dt;
+
+		// Exponential, so the result doesn't depend on frame rate
+		if self.drag > 0.0 {
+			let keep = (-self.drag * dt).exp();
+			self.vel.0 *= keep;
+			self.vel.1 *= keep;
+		}
+
+		let speed = self.speed();
+		if self.linear_drag > 0.0 && speed > 0.0 {
+			let slower = (speed - self.linear_drag * dt).max(0.0);
+			self.scale_vel(slower / speed);
+		}
+
+		if let Some(max) = self.max_speed {
+			let speed = self.speed();
+			if speed > max {
+				self.scale_vel(max / speed);
+			}
+		}
+	}
+
+	fn scale_vel(&mut self, by: f32) {
+		self.vel.0 *= by;
+		self.vel.1 *= by;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn close(a: f32, b: f32) -> bool {
+		(a - b).abs() < 1e-3
+	}
+
+	#[test]
+	fn drag_is_exponential_and_frame_rate_independent() {
+		let mut b = Body::new(0.0, 0.0, 10, 10).drag(2.0);
+		b.set_vel(100.0, 0.0);
+		b.update(0.5);
+		assert!(close(b.vel().0, 100.0 * (-1.0f32).exp()), "{:?}", b.vel());
+
+		let mut steps = Body::new(0.0, 0.0, 10, 10).drag(2.0);
+		steps.set_vel(100.0, 0.0);
+		for _ in 0..5 {
+			steps.update(0.1);
+		}
+		assert!(close(steps.vel().0, b.vel().0), "{:?} vs {:?}", steps.vel(), b.vel());
+
+		// Slows, but never stops on its own
+		for _ in 0..100 {
+			b.update(0.1);
+		}
+		assert!(b.vel().0 > 0.0);
+	}
+
+	#[test]
+	fn linear_drag_stops_at_zero() {
+		let mut b = Body::new(0.0, 0.0, 10, 10).linear_drag(100.0);
+		b.set_vel(150.0, 0.0);
+		b.update(1.0);
+		assert_eq!(b.vel(), (50.0, 0.0));
+		assert_eq!(b.pos(), (50.0, 0.0));
+
+		// Would be -50 without the stop, and then slide backwards
+		b.update(1.0);
+		assert_eq!(b.vel(), (0.0, 0.0));
+		b.update(1.0);
+		assert_eq!(b.vel(), (0.0, 0.0));
+		assert_eq!(b.pos(), (50.0, 0.0));
+	}
+
+	#[test]
+	fn linear_drag_slows_along_the_direction_of_travel() {
+		let mut b = Body::new(0.0, 0.0, 10, 10).linear_drag(25.0);
+		b.set_vel(30.0, 40.0);
+		b.update(1.0);
+		assert!(close(b.vel().0, 15.0) && close(b.vel().1, 20.0), "{:?}", b.vel());
+	}
+
+	#[test]
+	fn max_speed_limits_the_magnitude() {
+		let mut b = Body::new(0.0, 0.0, 10, 10).max_speed(100.0);
+		b.set_accel(1000.0, 1000.0);
+		b.update(1.0);
+
+		// Not 100 on each axis, which would be ~141 on the diagonal
+		assert!(close(b.speed(), 100.0), "{}", b.speed());
+		assert!(close(b.vel().0, b.vel().1));
+
+		b.set_accel(0.0, 0.0);
+		b.set_vel(50.0, 0.0);
+		b.update(1.0);
+		assert_eq!(b.vel(), (50.0, 0.0));
+	}
+
+	#[test]
+	fn clamp_to_stops_at_the_edges() {
+		let area = Rect::new(0, 0, 100, 100);
+
+		let mut b = Body::new(-5.0, 95.0, 10, 10);
+		b.set_vel(-50.0, 50.0);
+		b.clamp_to(area);
+		assert_eq!(b.pos(), (0.0, 90.0));
+		assert_eq!(b.vel(), (0.0, 0.0));
+
+		// Only the axis that was out loses its velocity
+		let mut b = Body::new(50.0, 120.0, 10, 10);
+		b.set_vel(20.0, 20.0);
+		b.clamp_to(area);
+		assert_eq!(b.pos(), (50.0, 90.0));
+		assert_eq!(b.vel(), (20.0, 0.0));
+
+		// Inside, nothing changes
+		let mut b = Body::new(10.0, 10.0, 10, 10);
+		b.set_vel(20.0, 20.0);
+		b.clamp_to(area);
+		assert_eq!(b.pos(), (10.0, 10.0));
+		assert_eq!(b.vel(), (20.0, 20.0));
+	}
+
+	#[test]
+	fn clamp_to_an_area_smaller_than_the_body() {
+		let mut b = Body::new(30.0, 30.0, 10, 10);
+		b.clamp_to(Rect::new(20, 20, 5, 5));
+		assert_eq!(b.pos(), (20.0, 20.0));
+	}
+
+	#[test]
+	fn rect_rounds_to_the_nearest_pixel() {
+		let mut b = Body::new(1.4, 1.6, 10, 20);
+		assert_eq!(b.rect(), Rect::new(1, 2, 10, 20));
+
+		b.set_pos(-0.6, 2.5);
+		assert_eq!(b.rect(), Rect::new(-1, 3, 10, 20));
+	}
+
+	#[test]
+	fn move_and_slide_reports_hits() {
+		let wall = Aabb::new(20.0, -5.0, 10.0, 30.0);
+		let floor = Aabb::new(-5.0, 20.0, 30.0, 10.0);
+
+		let mut b = Body::new(0.0, 0.0, 10, 10);
+		b.set_vel(100.0, 0.0);
+		let moved = b.move_and_slide(1.0, &[wall, floor]);
+		assert_eq!((moved.hit_x, moved.hit_y), (Some(0), None));
+		assert_eq!(b.pos(), (10.0, 0.0));
+		assert_eq!(b.vel(), (0.0, 0.0));
+
+		let mut b = Body::new(0.0, 0.0, 10, 10);
+		b.set_vel(0.0, 100.0);
+		let moved = b.move_and_slide(1.0, &[wall, floor]);
+		assert_eq!((moved.hit_x, moved.hit_y), (None, Some(1)));
+		assert_eq!(b.pos(), (0.0, 10.0));
+
+		// Into the corner: stops on both, and keeps nothing
+		let mut b = Body::new(0.0, 0.0, 10, 10);
+		b.set_vel(100.0, 100.0);
+		let moved = b.move_and_slide(1.0, &[wall, floor]);
+		assert_eq!((moved.hit_x, moved.hit_y), (Some(0), Some(1)));
+		assert_eq!(b.pos(), (10.0, 10.0));
+		assert_eq!(b.vel(), (0.0, 0.0));
+
+		// Nothing in the way moves like update()
+		let mut b = Body::new(0.0, 0.0, 10, 10);
+		b.set_vel(5.0, 0.0);
+		let moved = b.move_and_slide(1.0, &[wall, floor]);
+		assert_eq!((moved.hit_x, moved.hit_y), (None, None));
+		assert_eq!(b.pos(), (5.0, 0.0));
+		assert_eq!(b.vel(), (5.0, 0.0));
+	}
+}
@@ -1,12 +1,58 @@
 extern crate sdl2;
 
-use sdl2::rect::Rect;
+use std::error::Error as StdError;
+use std::path::Path;
+
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::image::LoadTexture;
+
+mod error;
+mod builder;
+mod capture;
+pub mod golden;
+pub mod game_loop;
+pub mod scene;
+pub mod assets;
+pub mod camera;
+pub mod parallax;
+pub mod tilemap;
+pub mod animation;
+pub mod input;
+pub mod controller;
+pub mod replay;
+pub mod kinematics;
+pub mod collision;
+pub mod broadphase;
+pub mod audio;
+pub mod text;
+pub mod overlay;
+pub mod profiler;
+pub mod pacer;
+
+pub use error::Error;
+pub use error::SdlError;
+pub use builder::SDLCoreBuilder;
+pub use builder::Renderer;
+pub use builder::Fullscreen;
+pub use builder::Position;
+pub use capture::Frame;
+pub use capture::FrameDiff;
+pub use camera::Camera;
+
+// Set this environment variable to force every SDLCore into headless mode
+pub const HEADLESS_VAR: &str = "SDL_RUST_HEADLESS";
 
 pub struct SDLCore {
 	sdl_cxt: sdl2::Sdl,
 	pub wincan: sdl2::render::WindowCanvas,
 	pub event_pump: sdl2::EventPump,
-	pub cam: Rect,
+	pub cam: Camera,
+	frames: u32,
+	session: replay::Session,
+	profiling: Option<profiler::Profiling>,
+	pacer: Option<pacer::FramePacer>,
+	// Never waits on the pacer, see set_frame_limit()
+	headless: bool,
 }
 
 impl SDLCore {
@@ -15,61 +61,97 @@ impl SDLCore {
 		vsync: bool,
 		width: u32,
 		height: u32,
-	) -> Result<SDLCore, String>
+	) -> Result<SDLCore, Error>
 	{
-		let sdl_cxt = sdl2::init()?;
-		let video_subsys = sdl_cxt.video()?;
+		SDLCore::builder(title, width, height)
+			.vsync(vsync)
+			.build()
+	}
 
-		let window = video_subsys.window(title, width, height)
+	// Same as init, but needs no display or GPU: uses SDL's dummy video
+	// driver and draws with the software renderer. The dummy driver stays
+	// selected for the rest of the process; see SDLCoreBuilder::headless.
+	pub fn init_headless(
+		title: &str,
+		width: u32,
+		height: u32,
+	) -> Result<SDLCore, Error>
+	{
+		SDLCore::builder(title, width, height)
+			.headless(true)
 			.build()
-			.map_err(|e| e.to_string())?;
-
-		let wincan = window.into_canvas().accelerated();
-
-		// Check if we should lock to vsync
-		let wincan = if vsync {
-			wincan.present_vsync()
-		}
-		else {
-			wincan
-		};
-		
-		let wincan = wincan.build()
-			.map_err(|e| e.to_string())?;
-
-		let event_pump = sdl_cxt.event_pump()?;
-
-		let cam = Rect::new(0, 0, width, height);
-
-		Ok(SDLCore{
-			sdl_cxt,
-			wincan,
-			event_pump,
-			cam,
-		})
+	}
+
+	// For anything beyond a plain, fixed-size window
+	pub fn builder(title: &str, width: u32, height: u32) -> SDLCoreBuilder {
+		SDLCoreBuilder::new(title, width, height)
+	}
+
+	// Queue up an event as if it had really happened, e.g., from a
+	// controller::VirtualController. It comes out of event_pump in order.
+	pub fn push_event(&self, event: sdl2::event::Event) -> Result<(), Error> {
+		self.sdl_cxt.event().map_err(Error::init)?.push_event(event)?;
+		Ok(())
 	}
 }
 
+// Like LoadTexture::load_texture, but the error says which file it was
+pub fn load_texture<'a, T, P>(
+	texture_creator: &'a TextureCreator<T>,
+	path: P,
+) -> Result<Texture<'a>, Error>
+	where
+		P: AsRef<Path>,
+{
+	texture_creator.load_texture(&path)
+		.map_err(|e| Error::asset(&path, e))
+}
+
 pub trait Demo {
-	fn init() -> Result<Self, String> where Self: Sized;
-	fn run(&mut self) -> Result<(), String>;
+	fn init() -> Result<Self, Error> where Self: Sized;
+	fn run(&mut self) -> Result<(), Error>;
+}
+
+// Prints the error, then everything that caused it
+fn report(e: &Error) {
+	println!("{}", e);
+
+	let mut source = e.source();
+	while let Some(s) = source {
+		println!("\t\t\tcaused by: {}", s);
+		source = s.source();
+	}
+
+	match e {
+		Error::Asset{..} => println!("\t\t(Asset paths are relative to where you run cargo from)"),
+		Error::Init(_) | Error::Window(_) | Error::Renderer(_) => {
+			println!("\t\t(No display? Try setting {})", HEADLESS_VAR)
+		},
+		_ => {},
+	}
 }
 
 pub fn runner<F, D>(desc: &str, initter: F)
 	where
-		F: Fn() -> Result<D, String>,
+		F: Fn() -> Result<D, Error>,
 		D: Demo,
 {
 	println!("\nRunning {}:", desc);
 	print!("\tInitting...");
 	match initter() {
-		Err(e) => println!("\n\t\tFailed to init: {}", e),
+		Err(e) => {
+			print!("\n\t\tFailed to init: ");
+			report(&e);
+		},
 		Ok(mut d) => {
 			println!("DONE");
 
 			print!("\tRunning...");
 			match d.run() {
-				Err(e) => println!("\n\t\tEncountered error while running: {}", e),
+				Err(e) => {
+					print!("\n\t\tEncountered error while running: ");
+					report(&e);
+				},
 				Ok(_) => println!("DONE\nExiting cleanly"),
 			};
 		},
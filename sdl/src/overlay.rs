@@ -0,0 +1,295 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, TextureCreator};
+use sdl2::video::WindowContext;
+
+use crate::SDLCore;
+use crate::Error;
+use crate::camera::Follow;
+use crate::collision::Aabb;
+use crate::text;
+use crate::text::{FontHandle, Text, TextStyle};
+
+// Frames shown in the frame time graph, one pixel wide each
+const GRAPH_FRAMES: usize = 180;
+const GRAPH_H: u32 = 60;
+// Milliseconds at the top of the graph
+const GRAPH_MAX_MS: f32 = 50.0;
+// 60 and 30 FPS
+const GOOD_MS: f32 = 1000.0 / 60.0;
+const OK_MS: f32 = 1000.0 / 30.0;
+
+// Seconds of frames the average FPS is over
+const AVERAGE_WINDOW: f32 = 1.0;
+
+const FONT_SIZE: u16 = 14;
+const MARGIN: i32 = 8;
+const PANEL_W: u32 = GRAPH_FRAMES as u32 + 2 * MARGIN as u32;
+
+// Frame rate, a frame time graph, and whatever a demo wants to keep an eye
+// on, drawn over the top of the game. Colliders and the camera's limits can
+// be drawn in the world too. Create one next to SDLCore:
+//
+//     let texture_creator = self.core.wincan.texture_creator();
+//     let mut overlay = DebugOverlay::new(&texture_creator)?;
+//
+// then each frame, pass it events (F3 shows and hides it), call frame() with
+// the frame's dt, watch() anything interesting, and draw() it last, just
+// before present().
+pub struct DebugOverlay<'a> {
+	text: Text<'a>,
+	font: FontHandle,
+	visible: bool,
+	toggle_key: Keycode,
+	// Seconds, most recent last
+	frame_times: VecDeque<f32>,
+	// Name and value, in the order they were first watched
+	watches: Vec<(String, String)>,
+	show_colliders: bool,
+	show_camera: bool,
+	// In world coordinates, cleared every draw()
+	colliders: Vec<Aabb>,
+}
+
+impl<'a> DebugOverlay<'a> {
+	pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Result<DebugOverlay<'a>, Error> {
+		let mut text = Text::new(texture_creator)?;
+		let font = text.load_font(text::DEFAULT_FONT, FONT_SIZE)?;
+
+		Ok(DebugOverlay {
+			text,
+			font,
+			visible: false,
+			toggle_key: Keycode::F3,
+			frame_times: VecDeque::with_capacity(GRAPH_FRAMES),
+			watches: Vec::new(),
+			show_colliders: false,
+			show_camera: false,
+			colliders: Vec::new(),
+		})
+	}
+
+	// Key that shows and hides the overlay in handle_event(), F3 by default
+	pub fn toggle_key(mut self, key: Keycode) -> DebugOverlay<'a> {
+		self.toggle_key = key;
+		self
+	}
+
+	// Start out shown instead of hidden
+	pub fn visible(mut self, visible: bool) -> DebugOverlay<'a> {
+		self.visible = visible;
+		self
+	}
+
+	pub fn is_visible(&self) -> bool {
+		self.visible
+	}
+
+	pub fn set_visible(&mut self, visible: bool) {
+		self.visible = visible;
+	}
+
+	pub fn toggle(&mut self) {
+		self.visible = !self.visible;
+	}
+
+	// Shows or hides the overlay on the toggle key. True if the event was
+	// that key, so the game can ignore it.
+	pub fn handle_event(&mut self, event: &Event) -> bool {
+		match event {
+			Event::KeyDown{keycode: Some(k), repeat: false, ..} if *k == self.toggle_key => {
+				self.toggle();
+				true
+			},
+			_ => false,
+		}
+	}
+
+	// Outlines everything passed to collider() each frame
+	pub fn set_show_colliders(&mut self, show: bool) {
+		self.show_colliders = show;
+	}
+
+	// Outlines the camera's bounds and, with Follow::DeadZone, its dead zone
+	pub fn set_show_camera(&mut self, show: bool) {
+		self.show_camera = show;
+	}
+
+	// Records how long the last frame took, in seconds, e.g. core.delta()
+	pub fn frame(&mut self, dt: f32) {
+		if self.frame_times.len() == GRAPH_FRAMES {
+			self.frame_times.pop_front();
+		}
+		self.frame_times.push_back(dt.max(0.0));
+	}
+
+	// FPS going by the last frame alone
+	pub fn fps(&self) -> f32 {
+		match self.frame_times.back() {
+			Some(&dt) if dt > 0.0 => 1.0 / dt,
+			_ => 0.0,
+		}
+	}
+
+	// FPS over about the last second
+	pub fn average_fps(&self) -> f32 {
+		let mut total = 0.0;
+		let mut frames = 0;
+
+		for &dt in self.frame_times.iter().rev() {
+			if total >= AVERAGE_WINDOW {
+				break;
+			}
+			total += dt;
+			frames += 1;
+		}
+
+		if total > 0.0 { frames as f32 / total } else { 0.0 }
+	}
+
+	// Shows `name: value` in the overlay until unwatch()ed. Watching the
+	// same name again just updates its value.
+	pub fn watch<V: Display>(&mut self, name: &str, value: V) {
+		let value = value.to_string();
+
+		match self.watches.iter_mut().find(|(n, _)| n == name) {
+			Some(w) => w.1 = value,
+			None => self.watches.push((name.to_string(), value)),
+		}
+	}
+
+	pub fn unwatch(&mut self, name: &str) {
+		self.watches.retain(|(n, _)| n != name);
+	}
+
+	// A collider to outline this frame, in world coordinates
+	pub fn collider<A: Into<Aabb>>(&mut self, aabb: A) {
+		if self.visible && self.show_colliders {
+			self.colliders.push(aabb.into());
+		}
+	}
+
+	// Draws everything on top of whatever's been drawn so far this frame
+	pub fn draw(&mut self, core: &mut SDLCore) -> Result<(), Error> {
+		let colliders = std::mem::take(&mut self.colliders);
+		if !self.visible {
+			return Ok(());
+		}
+
+		let blend = core.wincan.blend_mode();
+		core.wincan.set_blend_mode(BlendMode::Blend);
+
+		if self.show_colliders {
+			core.wincan.set_draw_color(Color::MAGENTA);
+			for c in colliders.iter() {
+				core.draw_world_rect(c.to_rect())?;
+			}
+		}
+
+		if self.show_camera {
+			self.draw_camera(core)?;
+		}
+
+		self.draw_panel(core, colliders.len())?;
+
+		core.wincan.set_blend_mode(blend);
+		Ok(())
+	}
+
+	fn draw_camera(&self, core: &mut SDLCore) -> Result<(), Error> {
+		core.wincan.set_draw_color(Color::CYAN);
+
+		if let Some(bounds) = core.cam.bounds() {
+			core.wincan.draw_rect(core.cam.rect_to_screen(bounds))?;
+		}
+
+		// What the camera's following should stay inside this box, or
+		// at the crosshair for other modes
+		let (w, h) = (core.cam.width() as i32, core.cam.height() as i32);
+		match core.cam.mode() {
+			Follow::DeadZone{ w: zone_w, h: zone_h } => {
+				let zone = Rect::from_center(Point::new(w / 2, h / 2), zone_w, zone_h);
+				core.wincan.draw_rect(zone)?;
+			},
+			_ => {
+				let c = Point::new(w / 2, h / 2);
+				core.wincan.draw_line(c.offset(-6, 0), c.offset(6, 0))?;
+				core.wincan.draw_line(c.offset(0, -6), c.offset(0, 6))?;
+			},
+		}
+
+		Ok(())
+	}
+
+	fn draw_panel(&mut self, core: &mut SDLCore, colliders: usize) -> Result<(), Error> {
+		let style = TextStyle::new(Color::WHITE);
+		let line_h = self.text.line_height(self.font)? as i32;
+
+		let last_ms = self.frame_times.back().map_or(0.0, |dt| dt * 1000.0);
+		let mut lines = vec![
+			format!("FPS {:.0} (avg {:.1})", self.fps(), self.average_fps()),
+			format!("Frame {:.2} ms", last_ms),
+		];
+		if self.show_colliders {
+			lines.push(format!("Colliders: {}", colliders));
+		}
+		for (name, value) in self.watches.iter() {
+			lines.push(format!("{}: {}", name, value));
+		}
+
+		// Wide enough for the graph, or for the longest watch
+		let mut panel_w = PANEL_W;
+		for line in lines.iter() {
+			let (w, _) = self.text.measure(self.font, line, &style)?;
+			panel_w = panel_w.max(w + 2 * MARGIN as u32);
+		}
+
+		let text_h = line_h * lines.len() as i32;
+		let panel = Rect::new(0, 0, panel_w, (text_h + 3 * MARGIN) as u32 + GRAPH_H);
+		core.wincan.set_draw_color(Color::RGBA(0, 0, 0, 180));
+		core.wincan.fill_rect(panel)?;
+
+		let mut y = MARGIN;
+		for line in lines.iter() {
+			self.text.draw(&mut core.wincan, self.font, line, MARGIN, y, &style)?;
+			y += line_h;
+		}
+
+		// One bar per frame, newest on the right, colored by whether it
+		// made 60 FPS, 30 FPS or neither
+		let graph_top = y + MARGIN;
+		let bottom = graph_top + GRAPH_H as i32;
+		let scale = GRAPH_H as f32 / GRAPH_MAX_MS;
+		let first_x = MARGIN + (GRAPH_FRAMES - self.frame_times.len()) as i32;
+
+		for (i, &dt) in self.frame_times.iter().enumerate() {
+			let ms = dt * 1000.0;
+			let h = ((ms.min(GRAPH_MAX_MS) * scale).round() as u32).max(1);
+
+			core.wincan.set_draw_color(if ms <= GOOD_MS {
+				Color::GREEN
+			}
+			else if ms <= OK_MS {
+				Color::YELLOW
+			}
+			else {
+				Color::RED
+			});
+			core.wincan.fill_rect(Rect::new(first_x + i as i32, bottom - h as i32, 1, h))?;
+		}
+
+		// Marks for 60 and 30 FPS
+		core.wincan.set_draw_color(Color::RGBA(255, 255, 255, 100));
+		for ms in [GOOD_MS, OK_MS].iter() {
+			let y = bottom - (ms * scale).round() as i32;
+			core.wincan.draw_line((MARGIN, y), (MARGIN + GRAPH_FRAMES as i32 - 1, y))?;
+		}
+
+		Ok(())
+	}
+}
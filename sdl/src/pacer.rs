@@ -0,0 +1,252 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::SDLCore;
+use crate::profiler::Phase;
+
+// How long before a deadline to stop sleeping and spin instead. Sleeps can
+// overshoot by a millisecond or more, spinning can't.
+pub const DEFAULT_SPIN: Duration = Duration::from_millis(2);
+
+// Holds frames to a target rate without vsync, by waiting out whatever is
+// left of each frame's time after it's drawn. Most of the wait is slept
+// through, and the last moment spun through so frames start on time.
+//
+// SDLCore runs one in present() when built with frame_limit() (or after
+// set_frame_limit()). With vsync on as well, set it below the display's
+// refresh rate, e.g. to hold a 144Hz display to 60 FPS, or the two take
+// turns waiting.
+#[derive(Clone, Debug)]
+pub struct FramePacer {
+	fps: u32,
+	period: Duration,
+	spin: Duration,
+	// When the current frame should end, None until the first wait()
+	deadline: Option<Instant>,
+	frames: u64,
+	missed: u64,
+	// How far past its deadline the last late frame was
+	last_late: Duration,
+}
+
+impl FramePacer {
+	pub fn new(fps: u32) -> FramePacer {
+		let fps = fps.max(1);
+		FramePacer {
+			fps,
+			period: Duration::from_secs(1) / fps,
+			spin: DEFAULT_SPIN,
+			deadline: None,
+			frames: 0,
+			missed: 0,
+			last_late: Duration::from_secs(0),
+		}
+	}
+
+	// Longer is more precise but burns more CPU; zero only sleeps
+	pub fn spin(mut self, spin: Duration) -> FramePacer {
+		self.spin = spin;
+		self
+	}
+
+	pub fn target_fps(&self) -> u32 {
+		self.fps
+	}
+
+	// Takes effect from the next frame
+	pub fn set_target_fps(&mut self, fps: u32) {
+		let fps = fps.max(1);
+		self.fps = fps;
+		self.period = Duration::from_secs(1) / fps;
+	}
+
+	// Time each frame gets
+	pub fn period(&self) -> Duration {
+		self.period
+	}
+
+	// Waits until the current frame's time is up. False if it already was,
+	// i.e., the frame missed its deadline. Missed frames don't try to catch
+	// up by rushing the ones after; the schedule starts over from now.
+	pub fn wait(&mut self) -> bool {
+		self.frames += 1;
+
+		let deadline = match self.deadline {
+			Some(d) => d,
+			// Nothing to time the first frame from
+			None => {
+				self.deadline = Some(Instant::now() + self.period);
+				return true;
+			},
+		};
+
+		let now = Instant::now();
+		if now > deadline {
+			self.missed += 1;
+			self.last_late = now - deadline;
+			self.deadline = Some(now + self.period);
+			return false;
+		}
+
+		let remaining = deadline - now;
+		if remaining > self.spin {
+			thread::sleep(remaining - self.spin);
+		}
+		while Instant::now() < deadline {
+			std::hint::spin_loop();
+		}
+
+		// From the deadline rather than now, so small overshoots don't add
+		// up and drag the rate down
+		self.deadline = Some(deadline + self.period);
+		true
+	}
+
+	// Frames waited for, the first included
+	pub fn frames(&self) -> u64 {
+		self.frames
+	}
+
+	// Frames that took longer than period() on their own
+	pub fn missed(&self) -> u64 {
+		self.missed
+	}
+
+	pub fn last_late(&self) -> Duration {
+		self.last_late
+	}
+
+	// Forgets the schedule and the counts, e.g. after a loading screen
+	pub fn reset(&mut self) {
+		self.deadline = None;
+		self.frames = 0;
+		self.missed = 0;
+		self.last_late = Duration::from_secs(0);
+	}
+}
+
+impl SDLCore {
+	// None unless there's a frame limit
+	pub fn pacer(&self) -> Option<&FramePacer> {
+		self.pacer.as_ref()
+	}
+
+	// Holds present() to `fps` frames a second, or lifts the limit. Keeps
+	// the missed-frame counts if there already was a limit.
+	//
+	// Headless cores never wait, whether the limit came from here or the
+	// builder: like vsync, they run as fast as they can. pacer() still
+	// reports the limit, so code that toggles it behaves the same.
+	pub fn set_frame_limit(&mut self, fps: Option<u32>) {
+		match (fps, &mut self.pacer) {
+			(Some(fps), Some(p)) => p.set_target_fps(fps),
+			(Some(fps), None) => self.pacer = Some(FramePacer::new(fps)),
+			(None, _) => self.pacer = None,
+		}
+	}
+
+	// Called by present() once the frame is on screen
+	pub(crate) fn pace_frame(&mut self) {
+		if self.pacer.is_none() || self.headless {
+			return;
+		}
+
+		self.begin_phase(Phase::Pace);
+		if let Some(p) = &mut self.pacer {
+			p.wait();
+		}
+		self.end_phase(Phase::Pace);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// 1ms frames, so a short sleep is enough to miss one
+	fn fast() -> FramePacer {
+		FramePacer::new(1000).spin(Duration::from_secs(0))
+	}
+
+	#[test]
+	fn first_frame_does_not_wait() {
+		let mut p = FramePacer::new(1);
+		let start = Instant::now();
+		assert!(p.wait());
+		assert!(start.elapsed() < Duration::from_millis(500));
+		assert_eq!((p.frames(), p.missed()), (1, 0));
+	}
+
+	#[test]
+	fn waits_out_the_period() {
+		let mut p = FramePacer::new(100);
+		p.wait();
+		let start = Instant::now();
+		for _ in 0..3 {
+			assert!(p.wait());
+		}
+		// The first deadline came up to 10ms after start, the rest 10ms apart
+		assert!(start.elapsed() >= Duration::from_millis(20), "{:?}", start.elapsed());
+		assert_eq!((p.frames(), p.missed()), (4, 0));
+	}
+
+	#[test]
+	fn counts_missed_deadlines() {
+		let mut p = fast();
+		p.wait();
+
+		thread::sleep(Duration::from_millis(20));
+		assert!(!p.wait());
+		assert_eq!(p.missed(), 1);
+		assert!(p.last_late() >= Duration::from_millis(15), "{:?}", p.last_late());
+
+		thread::sleep(Duration::from_millis(20));
+		assert!(!p.wait());
+		assert_eq!((p.frames(), p.missed()), (3, 2));
+	}
+
+	#[test]
+	fn starts_over_after_a_stall() {
+		let mut p = FramePacer::new(100).spin(Duration::from_secs(0));
+		p.wait();
+
+		// Way behind: 20 frames' worth
+		thread::sleep(Duration::from_millis(200));
+		assert!(!p.wait());
+
+		// The next frame still gets its full period, instead of the rest
+		// rushing through to catch up
+		let start = Instant::now();
+		assert!(p.wait());
+		assert!(start.elapsed() >= Duration::from_millis(5), "{:?}", start.elapsed());
+		assert_eq!(p.missed(), 1);
+	}
+
+	#[test]
+	fn reset_forgets_the_schedule_and_counts() {
+		let mut p = FramePacer::new(1);
+		p.wait();
+		thread::sleep(Duration::from_millis(5));
+		p.reset();
+		assert_eq!((p.frames(), p.missed(), p.last_late()), (0, 0, Duration::from_secs(0)));
+
+		// Timed from scratch again, so no waiting out the old 1s deadline
+		let start = Instant::now();
+		assert!(p.wait());
+		assert!(start.elapsed() < Duration::from_millis(500));
+		assert_eq!(p.frames(), 1);
+	}
+
+	#[test]
+	fn changing_the_rate_keeps_the_counts() {
+		let mut p = fast();
+		p.wait();
+		thread::sleep(Duration::from_millis(5));
+		p.wait();
+
+		p.set_target_fps(0);
+		assert_eq!(p.target_fps(), 1);
+		assert_eq!(p.period(), Duration::from_secs(1));
+		assert_eq!((p.frames(), p.missed()), (2, 1));
+	}
+}
@@ -0,0 +1,312 @@
+use sdl2::rect::Rect;
+
+use crate::SDLCore;
+use crate::Error;
+use crate::assets::{AssetManager, TextureHandle};
+
+// What a layer does past the edges of its texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+	// Tile the texture endlessly
+	Wrap,
+	// Draw once, but never scroll far enough to show an edge
+	Clamp,
+	// Draw once, scrolling off screen like anything else
+	None,
+}
+
+// Where a layer sits vertically before scrolling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+	Top,
+	Center,
+	// e.g., hills or a city skyline along the bottom of the screen
+	Bottom,
+}
+
+// One scrolling image in a Parallax background
+#[derive(Clone, Debug)]
+pub struct Layer {
+	texture: TextureHandle,
+	// Drawn size, None for the texture's own size
+	size: Option<(u32, u32)>,
+	// How far the layer moves per pixel the camera moves. 1.0 moves with the
+	// world, 0.0 is stuck to the screen, in between looks farther away.
+	factor: (f32, f32),
+	repeat_x: Repeat,
+	repeat_y: Repeat,
+	anchor: Anchor,
+	offset: (i32, i32),
+	// Pixels per second the layer drifts on its own (clouds, SDL12's sky)
+	drift: (f32, f32),
+	// Kept within one copy of the layer on wrapping axes, so it never grows
+	// big enough to lose precision
+	drifted: (f32, f32),
+}
+
+impl Layer {
+	// Defaults to moving with the world, wrapping horizontally, anchored to
+	// the top of the screen
+	pub fn new(texture: TextureHandle) -> Layer {
+		Layer {
+			texture,
+			size: None,
+			factor: (1.0, 1.0),
+			repeat_x: Repeat::Wrap,
+			repeat_y: Repeat::None,
+			anchor: Anchor::Top,
+			offset: (0, 0),
+			drift: (0.0, 0.0),
+			drifted: (0.0, 0.0),
+		}
+	}
+
+	pub fn size(mut self, width: u32, height: u32) -> Layer {
+		self.size = Some((width, height));
+		self
+	}
+
+	pub fn factor(mut self, x: f32, y: f32) -> Layer {
+		self.factor = (x, y);
+		self
+	}
+
+	pub fn repeat(mut self, x: Repeat, y: Repeat) -> Layer {
+		self.repeat_x = x;
+		self.repeat_y = y;
+		self
+	}
+
+	pub fn anchor(mut self, anchor: Anchor) -> Layer {
+		self.anchor = anchor;
+		self
+	}
+
+	// Nudge the layer from where its anchor puts it
+	pub fn offset(mut self, x: i32, y: i32) -> Layer {
+		self.offset = (x, y);
+		self
+	}
+
+	pub fn drift(mut self, x: f32, y: f32) -> Layer {
+		self.drift = (x, y);
+		self
+	}
+
+	// The size given, or else the texture's own. None if it's unloaded.
+	fn drawn_size(&self, assets: &AssetManager) -> Option<(u32, u32)> {
+		match (self.size, assets.texture(self.texture)) {
+			(Some(size), _) => Some(size),
+			(None, Some(t)) => {
+				let q = t.query();
+				Some((q.width, q.height))
+			},
+			(None, None) => None,
+		}
+	}
+
+	// Drifts for dt seconds. Moving a wrapped layer by a whole copy looks
+	// the same, so those axes are kept within [0, size).
+	fn advance(&mut self, dt: f32, (w, h): (u32, u32)) {
+		self.drifted.0 += self.drift.0 * dt;
+		self.drifted.1 += self.drift.1 * dt;
+
+		if self.repeat_x == Repeat::Wrap && w > 0 {
+			self.drifted.0 = self.drifted.0.rem_euclid(w as f32);
+		}
+		if self.repeat_y == Repeat::Wrap && h > 0 {
+			self.drifted.1 = self.drifted.1.rem_euclid(h as f32);
+		}
+	}
+
+	// Where the first copy of the layer goes on screen, for a camera at
+	// `cam` and a screen `screen_h` tall
+	fn origin(&self, cam: (f32, f32), (_, h): (u32, u32), screen_h: u32) -> (i32, i32) {
+		let anchor_y = match self.anchor {
+			Anchor::Top => 0,
+			Anchor::Center => (screen_h as i32 - h as i32) / 2,
+			Anchor::Bottom => screen_h as i32 - h as i32,
+		};
+
+		(
+			(self.offset.0 as f32 - cam.0 * self.factor.0 + self.drifted.0).round() as i32,
+			(anchor_y as f32 + self.offset.1 as f32 - cam.1 * self.factor.1 + self.drifted.1).round() as i32,
+		)
+	}
+}
+
+// Screen positions along one axis to draw copies of a layer at, given
+// where its first copy would go
+fn placements(origin: i32, size: u32, screen: u32, repeat: Repeat) -> Vec<i32> {
+	let (size_i, screen_i) = (size as i32, screen as i32);
+
+	match repeat {
+		Repeat::Wrap if size > 0 => {
+			// Step back to the copy that covers the screen's left/top edge
+			let mut start = origin.rem_euclid(size_i);
+			if start > 0 {
+				start -= size_i;
+			}
+			(0..)
+				.map(|i| start + i * size_i)
+				.take_while(|&x| x < screen_i)
+				.collect()
+		},
+		Repeat::Clamp => {
+			if size >= screen {
+				vec![origin.clamp(screen_i - size_i, 0)]
+			}
+			else {
+				vec![origin.clamp(0, screen_i - size_i)]
+			}
+		},
+		_ => vec![origin],
+	}
+}
+
+// A stack of layers drawn back to front. Replaces SDL12 and SDL13's
+// hand-wrapped background with any number of layers, positioned from the
+// camera.
+#[derive(Clone, Debug, Default)]
+pub struct Parallax {
+	layers: Vec<Layer>,
+}
+
+impl Parallax {
+	pub fn new() -> Parallax {
+		Parallax {
+			layers: Vec::new(),
+		}
+	}
+
+	// Layers are drawn in the order they're added, so start with the farthest
+	pub fn push(&mut self, layer: Layer) {
+		self.layers.push(layer);
+	}
+
+	pub fn layers_mut(&mut self) -> &mut [Layer] {
+		&mut self.layers
+	}
+
+	// Advances drifting layers, dt in seconds. Needs the assets for the
+	// size of any layer without one set.
+	pub fn update(&mut self, dt: f32, assets: &AssetManager) {
+		for l in self.layers.iter_mut() {
+			if let Some(size) = l.drawn_size(assets) {
+				l.advance(dt, size);
+			}
+		}
+	}
+
+	// Draws every layer for the current camera position
+	pub fn draw(&self, core: &mut SDLCore, assets: &AssetManager) -> Result<(), Error> {
+		let cam = (core.cam.x() as f32, core.cam.y() as f32);
+		let (screen_w, screen_h) = (core.cam.width(), core.cam.height());
+
+		for l in &self.layers {
+			let (w, h) = match l.drawn_size(assets) {
+				Some(size) => size,
+				None => continue,
+			};
+
+			let (origin_x, origin_y) = l.origin(cam, (w, h), screen_h);
+
+			for y in placements(origin_y, h, screen_h, l.repeat_y) {
+				for x in placements(origin_x, w, screen_w, l.repeat_x) {
+					assets.draw(&mut core.wincan, l.texture, None, Rect::new(x, y, w, h))?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn layer() -> Layer {
+		Layer::new(TextureHandle::unloaded())
+	}
+
+	#[test]
+	fn wrap_covers_the_screen() {
+		assert_eq!(placements(0, 640, 640, Repeat::Wrap), vec![0]);
+		assert_eq!(placements(-640, 640, 640, Repeat::Wrap), vec![0]);
+		assert_eq!(placements(-700, 640, 640, Repeat::Wrap), vec![-60, 580]);
+		assert_eq!(placements(700, 640, 640, Repeat::Wrap), vec![-580, 60]);
+		assert_eq!(placements(-30, 100, 250, Repeat::Wrap), vec![-30, 70, 170]);
+	}
+
+	#[test]
+	fn clamp_and_none() {
+		// Bigger than the screen: never shows an edge
+		assert_eq!(placements(50, 1000, 640, Repeat::Clamp), vec![0]);
+		assert_eq!(placements(-500, 1000, 640, Repeat::Clamp), vec![-360]);
+		// Smaller: never leaves the screen
+		assert_eq!(placements(-50, 100, 640, Repeat::Clamp), vec![0]);
+		assert_eq!(placements(600, 100, 640, Repeat::Clamp), vec![540]);
+
+		assert_eq!(placements(-5000, 100, 640, Repeat::None), vec![-5000]);
+		assert_eq!(placements(-5000, 0, 640, Repeat::Wrap), vec![-5000]);
+	}
+
+	#[test]
+	fn wraps_at_negative_camera_x() {
+		let l = layer().factor(0.5, 1.0);
+
+		let (x, _) = l.origin((-100.0, 0.0), (640, 480), 480);
+		assert_eq!(x, 50);
+		assert_eq!(placements(x, 640, 640, Repeat::Wrap), vec![-590, 50]);
+
+		let (x, _) = l.origin((-1500.0, 0.0), (640, 480), 480);
+		assert_eq!(x, 750);
+		assert_eq!(placements(x, 640, 640, Repeat::Wrap), vec![-530, 110]);
+	}
+
+	#[test]
+	fn factor_zero_stays_on_screen() {
+		let l = layer().factor(0.0, 0.0).anchor(Anchor::Bottom).offset(10, -5);
+
+		for cam in [(0.0, 0.0), (1234.0, 56.0), (-300.0, -300.0)].iter() {
+			assert_eq!(l.origin(*cam, (640, 100), 480), (10, 480 - 100 - 5));
+		}
+	}
+
+	#[test]
+	fn anchors() {
+		let l = layer().anchor(Anchor::Center);
+		assert_eq!(l.origin((0.0, 0.0), (640, 100), 480), (0, 190));
+
+		let l = layer().anchor(Anchor::Top);
+		assert_eq!(l.origin((0.0, 40.0), (640, 100), 480), (0, -40));
+	}
+
+	#[test]
+	fn drift_stays_within_one_copy() {
+		let mut l = layer().drift(-100.0, 0.0);
+
+		// Long enough that the total, 25 million px, is past where f32 can
+		// count single pixels
+		let steps: i64 = 1_000_003;
+		for _ in 0..steps {
+			l.advance(0.25, (640, 480));
+			assert!(l.drifted.0 >= 0.0 && l.drifted.0 < 640.0, "{}", l.drifted.0);
+		}
+		assert_eq!(l.drifted.0, (-25 * steps).rem_euclid(640) as f32);
+
+		// Still on a whole pixel, so the layer doesn't jitter
+		let (x, _) = l.origin((0.0, 0.0), (640, 480), 480);
+		assert_eq!(x as f32, l.drifted.0);
+	}
+
+	#[test]
+	fn drift_on_unwrapped_axes_keeps_going() {
+		let mut l = layer().drift(0.0, 10.0).repeat(Repeat::Wrap, Repeat::None);
+		for _ in 0..100 {
+			l.advance(1.0, (640, 480));
+		}
+		assert_eq!(l.drifted, (0.0, 1000.0));
+	}
+}
@@ -0,0 +1,509 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::SDLCore;
+use crate::Error;
+
+// Set this environment variable to profile every SDLCore. A file name ending
+// in .csv or .json also saves every frame's timings there at exit; anything
+// else (e.g. 1) just prints the summary.
+pub const PROFILE_VAR: &str = "SDL_RUST_PROFILE";
+
+// Percentiles in each summary
+const PERCENTILES: [f64; 2] = [0.95, 0.99];
+
+// The part of a frame being timed. SDLCore times Input (poll_events()),
+// Present (present()) and Pace (waiting out a frame limit) on its own, and
+// GameLoop times Update and Render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+	Input,
+	Update,
+	Render,
+	Present,
+	Pace,
+	// Anything else a demo wants timed, e.g. Custom("physics")
+	Custom(&'static str),
+}
+
+impl Phase {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Phase::Input => "input",
+			Phase::Update => "update",
+			Phase::Render => "render",
+			Phase::Present => "present",
+			Phase::Pace => "pace",
+			Phase::Custom(name) => name,
+		}
+	}
+}
+
+impl fmt::Display for Phase {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+// One timed phase. Times are from when the Profiler was created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+	pub phase: Phase,
+	pub start: Duration,
+	pub duration: Duration,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameTiming {
+	pub start: Duration,
+	pub total: Duration,
+	// In the order they started
+	pub spans: Vec<Span>,
+}
+
+impl FrameTiming {
+	// Everything spent in `phase` this frame, e.g. across several updates
+	pub fn phase_total(&self, phase: Phase) -> Option<Duration> {
+		let mut spans = self.spans.iter().filter(|s| s.phase == phase).peekable();
+		spans.peek()?;
+		Some(spans.map(|s| s.duration).sum())
+	}
+}
+
+// How long the frame, or one phase of it, took over every frame it was in
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+	// "frame" for whole frames, otherwise the phase's name
+	pub name: String,
+	pub frames: usize,
+	pub min: Duration,
+	pub avg: Duration,
+	pub p95: Duration,
+	pub p99: Duration,
+	pub max: Duration,
+}
+
+impl Summary {
+	fn of(name: &str, mut times: Vec<Duration>) -> Option<Summary> {
+		if times.is_empty() {
+			return None;
+		}
+		times.sort();
+
+		// Nearest rank
+		let n = times.len();
+		let rank = |p: f64| times[((p * n as f64).ceil() as usize).clamp(1, n) - 1];
+
+		Some(Summary {
+			name: name.to_string(),
+			frames: n,
+			min: times[0],
+			avg: times.iter().sum::<Duration>() / n as u32,
+			p95: rank(PERCENTILES[0]),
+			p99: rank(PERCENTILES[1]),
+			max: times[n - 1],
+		})
+	}
+}
+
+fn ms(d: Duration) -> f64 {
+	d.as_secs_f64() * 1000.0
+}
+
+fn us(d: Duration) -> f64 {
+	d.as_secs_f64() * 1_000_000.0
+}
+
+// Times each frame and the phases inside it. Mark phases with begin() and
+// end() (or time()), and frames with end_frame(); SDLCore does all of that
+// itself when profiling is turned on from its builder or PROFILE_VAR, and
+// demos only need core.begin_phase() / core.end_phase() for their own.
+//
+// Every frame is kept, so the raw timings can be saved as CSV (one row per
+// frame) or as a Chrome trace (open it at chrome://tracing or in Perfetto).
+pub struct Profiler {
+	epoch: Instant,
+	frames: Vec<FrameTiming>,
+	current: FrameTiming,
+	// Phases begun but not yet ended, innermost last
+	open: Vec<(Phase, Instant)>,
+	// Set by start() or the first end_frame()
+	started: bool,
+}
+
+impl Default for Profiler {
+	fn default() -> Profiler {
+		Profiler::new()
+	}
+}
+
+impl Profiler {
+	// The first frame starts now, unless start() says otherwise
+	pub fn new() -> Profiler {
+		Profiler {
+			epoch: Instant::now(),
+			frames: Vec::new(),
+			current: FrameTiming::default(),
+			open: Vec::new(),
+			started: false,
+		}
+	}
+
+	// Starts the first frame over from now, dropping anything timed in it
+	// so far, so e.g. loading before the game loop doesn't count as a slow
+	// first frame. Only the first call does anything, and only before the
+	// first end_frame().
+	pub fn start(&mut self) {
+		if self.started {
+			return;
+		}
+		self.started = true;
+
+		self.current.start = Instant::now() - self.epoch;
+		self.current.spans.clear();
+	}
+
+	pub fn begin(&mut self, phase: Phase) {
+		self.open.push((phase, Instant::now()));
+	}
+
+	// Ends the most recent begin() of `phase`, and anything begun inside it
+	// that wasn't ended. Does nothing if `phase` isn't running.
+	pub fn end(&mut self, phase: Phase) {
+		let now = Instant::now();
+
+		if let Some(i) = self.open.iter().rposition(|&(p, _)| p == phase) {
+			for (p, start) in self.open.drain(i..).rev() {
+				self.current.spans.push(Span {
+					phase: p,
+					start: start - self.epoch,
+					duration: now - start,
+				});
+			}
+		}
+	}
+
+	// Times `f` as `phase`
+	pub fn time<T, F: FnOnce() -> T>(&mut self, phase: Phase, f: F) -> T {
+		self.begin(phase);
+		let result = f();
+		self.end(phase);
+		result
+	}
+
+	// Finishes the current frame, ending any phases still running, and
+	// starts the next
+	pub fn end_frame(&mut self) {
+		if let Some(&(outermost, _)) = self.open.first() {
+			self.end(outermost);
+		}
+
+		let now = Instant::now() - self.epoch;
+		let mut frame = std::mem::take(&mut self.current);
+		frame.total = now - frame.start;
+		frame.spans.sort_by_key(|s| s.start);
+		self.frames.push(frame);
+
+		self.current.start = now;
+		self.started = true;
+	}
+
+	pub fn frames(&self) -> &[FrameTiming] {
+		&self.frames
+	}
+
+	// Every phase's name, in the order they were first seen
+	fn phases(&self) -> Vec<Phase> {
+		let mut phases = Vec::new();
+		for s in self.frames.iter().flat_map(|f| f.spans.iter()) {
+			if !phases.contains(&s.phase) {
+				phases.push(s.phase);
+			}
+		}
+		phases
+	}
+
+	// Whole frames first, then each phase
+	pub fn summary(&self) -> Vec<Summary> {
+		let mut summaries = Vec::new();
+		summaries.extend(Summary::of("frame", self.frames.iter().map(|f| f.total).collect()));
+
+		for phase in self.phases() {
+			let times = self.frames.iter().filter_map(|f| f.phase_total(phase)).collect();
+			summaries.extend(Summary::of(phase.name(), times));
+		}
+		summaries
+	}
+
+	// The summary as a table, in milliseconds
+	pub fn report(&self) -> String {
+		let mut out = format!(
+			"{:<12}{:>8}{:>10}{:>10}{:>10}{:>10}{:>10}\n",
+			"", "frames", "min", "avg", "p95", "p99", "max",
+		);
+		for s in self.summary() {
+			out += &format!(
+				"{:<12}{:>8}{:>10.3}{:>10.3}{:>10.3}{:>10.3}{:>10.3}\n",
+				s.name, s.frames, ms(s.min), ms(s.avg), ms(s.p95), ms(s.p99), ms(s.max),
+			);
+		}
+		out
+	}
+
+	// One row per frame: its number, start and total, then the time spent in
+	// each phase, all in milliseconds. Blank if a phase didn't run.
+	pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let path = path.as_ref();
+		let phases = self.phases();
+
+		let mut text = String::from("frame,start_ms,total_ms");
+		for p in phases.iter() {
+			text += &format!(",{}_ms", p.name());
+		}
+		text += "\n";
+
+		for (i, f) in self.frames.iter().enumerate() {
+			text += &format!("{},{:.4},{:.4}", i, ms(f.start), ms(f.total));
+			for &p in phases.iter() {
+				match f.phase_total(p) {
+					Some(d) => text += &format!(",{:.4}", ms(d)),
+					None => text += ",",
+				}
+			}
+			text += "\n";
+		}
+
+		fs::write(path, text).map_err(|e| Error::asset(path, e))
+	}
+
+	// Chrome's trace event format: every frame and phase as a complete ("X")
+	// event, phases nested inside their frame
+	pub fn save_trace<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let path = path.as_ref();
+
+		let mut events = Vec::new();
+		for (i, f) in self.frames.iter().enumerate() {
+			events.push(json!({
+				"name": "frame",
+				"ph": "X",
+				"ts": us(f.start),
+				"dur": us(f.total),
+				"pid": 1,
+				"tid": 1,
+				"args": { "frame": i },
+			}).to_string());
+
+			for s in f.spans.iter() {
+				events.push(json!({
+					"name": s.phase.name(),
+					"ph": "X",
+					"ts": us(s.start),
+					"dur": us(s.duration),
+					"pid": 1,
+					"tid": 1,
+				}).to_string());
+			}
+		}
+
+		let text = format!("{{\"displayTimeUnit\": \"ms\", \"traceEvents\": [\n{}\n]}}\n", events.join(",\n"));
+		fs::write(path, text).map_err(|e| Error::asset(path, e))
+	}
+
+	// save_trace() for .json files, save_csv() for anything else
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let path = path.as_ref();
+		match path.extension().and_then(|e| e.to_str()) {
+			Some(ext) if ext.eq_ignore_ascii_case("json") => self.save_trace(path),
+			_ => self.save_csv(path),
+		}
+	}
+}
+
+// True if PROFILE_VAR's value names a file to save to
+pub(crate) fn export_path(value: &Path) -> Option<PathBuf> {
+	match value.extension().and_then(|e| e.to_str()) {
+		Some(ext) if ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("json") => Some(value.to_path_buf()),
+		_ => None,
+	}
+}
+
+// An SDLCore's profiler, which reports when the SDLCore goes away
+pub(crate) struct Profiling {
+	profiler: Profiler,
+	export: Option<PathBuf>,
+}
+
+impl Profiling {
+	pub(crate) fn new(export: Option<PathBuf>) -> Profiling {
+		Profiling {
+			profiler: Profiler::new(),
+			export,
+		}
+	}
+}
+
+impl Drop for Profiling {
+	fn drop(&mut self) {
+		if self.profiler.frames.is_empty() {
+			return;
+		}
+
+		println!("\nFrame timings (ms):\n{}", self.profiler.report());
+
+		if let Some(path) = &self.export {
+			match self.profiler.save(path) {
+				Ok(()) => println!("Saved frame timings to {}", path.display()),
+				Err(e) => eprintln!("Couldn't save frame timings: {}", e),
+			}
+		}
+	}
+}
+
+impl SDLCore {
+	pub fn is_profiling(&self) -> bool {
+		self.profiling.is_some()
+	}
+
+	// None unless profiling was turned on
+	pub fn profiler(&self) -> Option<&Profiler> {
+		self.profiling.as_ref().map(|p| &p.profiler)
+	}
+
+	// Starts timing `phase`, if profiling. Does nothing otherwise, so these
+	// can be left in.
+	pub fn begin_phase(&mut self, phase: Phase) {
+		if let Some(p) = &mut self.profiling {
+			p.profiler.begin(phase);
+		}
+	}
+
+	pub fn end_phase(&mut self, phase: Phase) {
+		if let Some(p) = &mut self.profiling {
+			p.profiler.end(phase);
+		}
+	}
+
+	// Called by poll_events(), so the first frame is timed from the game
+	// loop rather than from build()
+	pub(crate) fn start_profiled_frame(&mut self) {
+		if let Some(p) = &mut self.profiling {
+			p.profiler.start();
+		}
+	}
+
+	// Called by present() once the frame is on screen
+	pub(crate) fn end_profiled_frame(&mut self) {
+		if let Some(p) = &mut self.profiling {
+			p.profiler.end_frame();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ms(n: u64) -> Duration {
+		Duration::from_millis(n)
+	}
+
+	#[test]
+	fn summary_nearest_rank() {
+		// 1 to 100 ms, out of order
+		let times = (1..=100).rev().map(ms).collect();
+		let s = Summary::of("frame", times).unwrap();
+		assert_eq!(s.frames, 100);
+		assert_eq!(s.min, ms(1));
+		assert_eq!(s.avg, Duration::from_micros(50_500));
+		assert_eq!(s.p95, ms(95));
+		assert_eq!(s.p99, ms(99));
+		assert_eq!(s.max, ms(100));
+
+		// Too few for p95 and p99 to be anything but the slowest
+		let s = Summary::of("frame", vec![ms(4), ms(1), ms(3), ms(2), ms(10), ms(5), ms(6), ms(9), ms(7), ms(8)]).unwrap();
+		assert_eq!(s.p95, ms(10));
+		assert_eq!(s.p99, ms(10));
+
+		// 95% of 20 is exactly the 19th, so no rounding
+		let times = (1..=20).map(ms).collect();
+		let s = Summary::of("frame", times).unwrap();
+		assert_eq!(s.p95, ms(19));
+		assert_eq!(s.p99, ms(20));
+
+		let s = Summary::of("render", vec![ms(7)]).unwrap();
+		assert_eq!(s.name, "render");
+		assert_eq!((s.min, s.avg, s.p95, s.p99, s.max), (ms(7), ms(7), ms(7), ms(7), ms(7)));
+
+		assert_eq!(Summary::of("frame", Vec::new()), None);
+	}
+
+	#[test]
+	fn ending_a_phase_ends_those_inside_it() {
+		let mut p = Profiler::new();
+		p.begin(Phase::Update);
+		p.begin(Phase::Custom("physics"));
+		p.begin(Phase::Custom("ai"));
+		p.end(Phase::Update);
+
+		// Already ended along with Update
+		p.end(Phase::Custom("physics"));
+		assert!(p.open.is_empty());
+
+		p.end_frame();
+		let spans = &p.frames()[0].spans;
+		let phases: Vec<_> = spans.iter().map(|s| s.phase).collect();
+		assert_eq!(phases, vec![Phase::Update, Phase::Custom("physics"), Phase::Custom("ai")]);
+
+		// All ended at the same moment, so each fits inside the last
+		for pair in spans.windows(2) {
+			let (outer, inner) = (&pair[0], &pair[1]);
+			assert!(inner.start >= outer.start);
+			assert_eq!(inner.start + inner.duration, outer.start + outer.duration);
+		}
+	}
+
+	#[test]
+	fn ending_an_inner_phase_leaves_the_outer_running() {
+		let mut p = Profiler::new();
+		p.begin(Phase::Render);
+		p.begin(Phase::Custom("sprites"));
+		p.end(Phase::Custom("sprites"));
+		assert_eq!(p.open.len(), 1);
+
+		// Never begun, so nothing happens
+		p.end(Phase::Update);
+		assert_eq!(p.open.len(), 1);
+
+		// end_frame() ends whatever's left
+		p.end_frame();
+		assert!(p.open.is_empty());
+		let frame = &p.frames()[0];
+		assert!(frame.phase_total(Phase::Render).is_some());
+		assert!(frame.phase_total(Phase::Custom("sprites")).is_some());
+		assert_eq!(frame.phase_total(Phase::Update), None);
+	}
+
+	#[test]
+	fn start_drops_what_came_before_the_first_frame() {
+		let mut p = Profiler::new();
+		p.time(Phase::Custom("load"), || ());
+		p.start();
+		p.time(Phase::Input, || ());
+		p.end_frame();
+
+		let frame = &p.frames()[0];
+		assert_eq!(frame.phase_total(Phase::Custom("load")), None);
+		assert!(frame.phase_total(Phase::Input).is_some());
+
+		// Too late to start over once a frame has ended
+		p.time(Phase::Update, || ());
+		p.start();
+		p.end_frame();
+		assert!(p.frames()[1].phase_total(Phase::Update).is_some());
+		assert_eq!(p.frames()[1].start, p.frames()[0].start + p.frames()[0].total);
+	}
+}
@@ -0,0 +1,757 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use sdl2::controller::{Axis, Button};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::{MouseState, MouseWheelDirection};
+use serde_json::{json, Value};
+
+use crate::SDLCore;
+use crate::Error;
+use crate::input::{mouse_from_name, mouse_name};
+use crate::profiler::Phase;
+
+// Set this environment variable to a file name to record every SDLCore's
+// input there
+pub const RECORD_VAR: &str = "SDL_RUST_RECORD";
+// Set this one to a recording to play it back instead of reading real input
+pub const REPLAY_VAR: &str = "SDL_RUST_REPLAY";
+
+// Everything a demo could learn about input during one frame
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordedFrame {
+	// What SDLCore::delta() said, in seconds
+	pub dt: f32,
+	// Held down when the frame was presented
+	pub keys: Vec<Scancode>,
+	pub events: Vec<Event>,
+}
+
+// A session's input, frame by frame. Frames are counted by SDLCore::present(),
+// the same as for golden tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+	frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+	pub fn new() -> Recording {
+		Recording::default()
+	}
+
+	pub fn frames(&self) -> &[RecordedFrame] {
+		&self.frames
+	}
+
+	// Handy for building playthroughs in tests, e.g., from a
+	// controller::VirtualController's events
+	pub fn push(&mut self, frame: RecordedFrame) {
+		self.frames.push(frame);
+	}
+
+	pub fn len(&self) -> usize {
+		self.frames.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.frames.is_empty()
+	}
+
+	// JSON, one frame per line:
+	//
+	//     {"frames": [
+	//     {"dt": 0.016, "keys": ["D"], "events": [{"type": "keydown", "scancode": "D", ...}]},
+	//     ...
+	//     ]}
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Recording, Error> {
+		let path = path.as_ref();
+
+		let text = fs::read_to_string(path).map_err(|e| Error::asset(path, e))?;
+		let v: Value = serde_json::from_str(&text).map_err(|e| Error::asset(path, e))?;
+
+		let frames = v.get("frames").and_then(Value::as_array)
+			.ok_or_else(|| Error::asset(path, "no frames; is this a recording?"))?;
+
+		let mut recording = Recording::new();
+		for (i, f) in frames.iter().enumerate() {
+			let keys = f.get("keys").and_then(Value::as_array)
+				.into_iter()
+				.flatten()
+				.map(|k| {
+					let k = k.as_str().unwrap_or("");
+					Scancode::from_name(k)
+						.ok_or_else(|| Error::asset(path, format!("frame {}: unknown key \"{}\"", i, k)))
+				})
+				.collect::<Result<_, _>>()?;
+
+			let events = f.get("events").and_then(Value::as_array)
+				.into_iter()
+				.flatten()
+				.map(|e| event_from_json(e).map_err(|msg| Error::asset(path, format!("frame {}: {}", i, msg))))
+				.collect::<Result<_, _>>()?;
+
+			recording.push(RecordedFrame {
+				dt: f.get("dt").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+				keys,
+				events,
+			});
+		}
+
+		Ok(recording)
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let path = path.as_ref();
+
+		let lines = self.frames.iter()
+			.map(|f| json!({
+				"dt": f.dt,
+				"keys": f.keys.iter().map(|k| k.name()).collect::<Vec<_>>(),
+				"events": f.events.iter().filter_map(event_to_json).collect::<Vec<_>>(),
+			}).to_string())
+			.collect::<Vec<_>>();
+
+		let text = format!("{{\"frames\": [\n{}\n]}}\n", lines.join(",\n"));
+		fs::write(path, text).map_err(|e| Error::asset(path, e))
+	}
+}
+
+// Input a replay stands in for. Quit isn't one of them, so the window can
+// still be closed during a replay.
+fn is_input(event: &Event) -> bool {
+	!matches!(event, Event::Quit{..}) && event_to_json(event).is_some()
+}
+
+// None for events that aren't recorded (window, joystick, touch, ...)
+fn event_to_json(event: &Event) -> Option<Value> {
+	let v = match event {
+		Event::Quit{ timestamp } => json!({ "type": "quit", "time": timestamp }),
+		Event::KeyDown{ timestamp, window_id, keycode, scancode, keymod, repeat }
+		| Event::KeyUp{ timestamp, window_id, keycode, scancode, keymod, repeat } => json!({
+			"type": if let Event::KeyDown{..} = event { "keydown" } else { "keyup" },
+			"time": timestamp,
+			"window": window_id,
+			"keycode": keycode.map(Keycode::name),
+			"scancode": scancode.map(Scancode::name),
+			"mod": keymod.bits(),
+			"repeat": repeat,
+		}),
+		Event::TextInput{ timestamp, window_id, text } => json!({
+			"type": "text",
+			"time": timestamp,
+			"window": window_id,
+			"text": text,
+		}),
+		Event::MouseMotion{ timestamp, window_id, which, mousestate, x, y, xrel, yrel } => json!({
+			"type": "mousemotion",
+			"time": timestamp,
+			"window": window_id,
+			"which": which,
+			"state": mousestate.to_sdl_state(),
+			"x": x,
+			"y": y,
+			"xrel": xrel,
+			"yrel": yrel,
+		}),
+		Event::MouseButtonDown{ timestamp, window_id, which, mouse_btn, clicks, x, y }
+		| Event::MouseButtonUp{ timestamp, window_id, which, mouse_btn, clicks, x, y } => json!({
+			"type": if let Event::MouseButtonDown{..} = event { "mousedown" } else { "mouseup" },
+			"time": timestamp,
+			"window": window_id,
+			"which": which,
+			"button": mouse_name(*mouse_btn),
+			"clicks": clicks,
+			"x": x,
+			"y": y,
+		}),
+		Event::MouseWheel{ timestamp, window_id, which, x, y, direction } => json!({
+			"type": "mousewheel",
+			"time": timestamp,
+			"window": window_id,
+			"which": which,
+			"x": x,
+			"y": y,
+			"direction": direction.to_ll(),
+		}),
+		Event::ControllerAxisMotion{ timestamp, which, axis, value } => json!({
+			"type": "axis",
+			"time": timestamp,
+			"which": which,
+			"axis": axis.string(),
+			"value": value,
+		}),
+		Event::ControllerButtonDown{ timestamp, which, button }
+		| Event::ControllerButtonUp{ timestamp, which, button } => json!({
+			"type": if let Event::ControllerButtonDown{..} = event { "buttondown" } else { "buttonup" },
+			"time": timestamp,
+			"which": which,
+			"button": button.string(),
+		}),
+		Event::ControllerDeviceAdded{ timestamp, which } => json!({
+			"type": "controlleradded",
+			"time": timestamp,
+			"which": which,
+		}),
+		Event::ControllerDeviceRemoved{ timestamp, which } => json!({
+			"type": "controllerremoved",
+			"time": timestamp,
+			"which": which,
+		}),
+		_ => return None,
+	};
+
+	Some(v)
+}
+
+fn event_from_json(v: &Value) -> Result<Event, String> {
+	let o = v.as_object().ok_or("event isn't an object")?;
+
+	let int = |key| o.get(key).and_then(Value::as_i64).unwrap_or(0);
+	let string = |key| o.get(key).and_then(Value::as_str).unwrap_or("");
+
+	let timestamp = int("time") as u32;
+	let window_id = int("window") as u32;
+	let which = int("which") as u32;
+
+	let kind = string("type");
+	let event = match kind {
+		"quit" => Event::Quit{ timestamp },
+		"keydown" | "keyup" => {
+			let keycode = o.get("keycode").and_then(Value::as_str).and_then(Keycode::from_name);
+			let scancode = o.get("scancode").and_then(Value::as_str).and_then(Scancode::from_name);
+			let keymod = Mod::from_bits_truncate(int("mod") as u16);
+			let repeat = o.get("repeat").and_then(Value::as_bool).unwrap_or(false);
+
+			if kind == "keydown" {
+				Event::KeyDown{ timestamp, window_id, keycode, scancode, keymod, repeat }
+			}
+			else {
+				Event::KeyUp{ timestamp, window_id, keycode, scancode, keymod, repeat }
+			}
+		},
+		"text" => Event::TextInput{ timestamp, window_id, text: string("text").to_string() },
+		"mousemotion" => Event::MouseMotion {
+			timestamp,
+			window_id,
+			which,
+			mousestate: MouseState::from_sdl_state(int("state") as u32),
+			x: int("x") as i32,
+			y: int("y") as i32,
+			xrel: int("xrel") as i32,
+			yrel: int("yrel") as i32,
+		},
+		"mousedown" | "mouseup" => {
+			let mouse_btn = mouse_from_name(string("button"))
+				.ok_or_else(|| format!("unknown mouse button \"{}\"", string("button")))?;
+			let clicks = int("clicks") as u8;
+			let (x, y) = (int("x") as i32, int("y") as i32);
+
+			if kind == "mousedown" {
+				Event::MouseButtonDown{ timestamp, window_id, which, mouse_btn, clicks, x, y }
+			}
+			else {
+				Event::MouseButtonUp{ timestamp, window_id, which, mouse_btn, clicks, x, y }
+			}
+		},
+		"mousewheel" => Event::MouseWheel {
+			timestamp,
+			window_id,
+			which,
+			x: int("x") as i32,
+			y: int("y") as i32,
+			direction: MouseWheelDirection::from_ll(int("direction") as u32),
+		},
+		"axis" => Event::ControllerAxisMotion {
+			timestamp,
+			which,
+			axis: Axis::from_string(string("axis"))
+				.ok_or_else(|| format!("unknown axis \"{}\"", string("axis")))?,
+			value: int("value") as i16,
+		},
+		"buttondown" | "buttonup" => {
+			let button = Button::from_string(string("button"))
+				.ok_or_else(|| format!("unknown button \"{}\"", string("button")))?;
+
+			if kind == "buttondown" {
+				Event::ControllerButtonDown{ timestamp, which, button }
+			}
+			else {
+				Event::ControllerButtonUp{ timestamp, which, button }
+			}
+		},
+		"controlleradded" => Event::ControllerDeviceAdded{ timestamp, which },
+		"controllerremoved" => Event::ControllerDeviceRemoved{ timestamp, which },
+		_ => return Err(format!("unknown event type \"{}\"", kind)),
+	};
+
+	Ok(event)
+}
+
+enum Mode {
+	Live,
+	Record {
+		path: PathBuf,
+		recording: Recording,
+		// Filled in as the frame goes, pushed by present()
+		current: RecordedFrame,
+	},
+	Replay {
+		recording: Recording,
+		frame: usize,
+		// This frame's events have been handed out
+		delivered: bool,
+	},
+}
+
+// What SDLCore needs to record or replay, and to time frames either way
+pub(crate) struct Session {
+	mode: Mode,
+	last_present: Instant,
+	dt: f32,
+}
+
+impl Session {
+	pub(crate) fn live() -> Session {
+		Session {
+			mode: Mode::Live,
+			last_present: Instant::now(),
+			dt: 0.0,
+		}
+	}
+
+	pub(crate) fn record(path: PathBuf) -> Session {
+		Session {
+			mode: Mode::Record {
+				path,
+				recording: Recording::new(),
+				current: RecordedFrame::default(),
+			},
+			..Session::live()
+		}
+	}
+
+	pub(crate) fn replay(recording: Recording) -> Session {
+		let dt = recording.frames.first().map_or(0.0, |f| f.dt);
+		Session {
+			mode: Mode::Replay {
+				recording,
+				frame: 0,
+				delivered: false,
+			},
+			dt,
+			..Session::live()
+		}
+	}
+
+	// Stops recording and saves, including any events (like the Quit)
+	// polled after the last present(). Nothing more is recorded after,
+	// whether or not the save worked.
+	fn finish(&mut self) -> Result<(), Error> {
+		let (path, mut recording, mut current) = match std::mem::replace(&mut self.mode, Mode::Live) {
+			Mode::Record{ path, recording, current } => (path, recording, current),
+			mode => {
+				self.mode = mode;
+				return Ok(());
+			},
+		};
+
+		if !current.events.is_empty() {
+			current.dt = self.dt;
+			current.keys = recording.frames.last().map(|f| f.keys.clone()).unwrap_or_default();
+			recording.push(current);
+		}
+
+		recording.save(path)
+	}
+
+	// This frame's events, from what was just `polled`. Recording notes
+	// them down; replaying swaps the input among them for the recorded
+	// input, handed out on the first call each frame.
+	fn events(&mut self, polled: Vec<Event>) -> Vec<Event> {
+		match &mut self.mode {
+			Mode::Live => polled,
+			Mode::Record{ current, .. } => {
+				current.events.extend(polled.iter().filter(|e| event_to_json(e).is_some()).cloned());
+				polled
+			},
+			Mode::Replay{ recording, frame, delivered } => {
+				let mut replayed = Vec::new();
+				if !*delivered {
+					if let Some(f) = recording.frames.get(*frame) {
+						replayed = f.events.clone();
+					}
+					*delivered = true;
+				}
+
+				replayed.extend(polled.into_iter().filter(|e| !is_input(e)));
+				replayed
+			},
+		}
+	}
+
+	// The keys held this frame of a replay, none once it's over. None when
+	// not replaying.
+	fn replayed_keys(&self) -> Option<HashSet<Scancode>> {
+		match &self.mode {
+			Mode::Replay{ recording, frame, .. } => Some(recording.frames.get(*frame)
+				.map(|f| f.keys.iter().copied().collect())
+				.unwrap_or_default()),
+			_ => None,
+		}
+	}
+
+	// Moves on to the next frame. `real_dt` is how long this one really
+	// took, and `held` the keys down at its end, for recording. True once a
+	// replay has run out of frames, on that frame only.
+	fn next_frame(&mut self, real_dt: f32, held: impl FnOnce() -> Vec<Scancode>) -> bool {
+		match &mut self.mode {
+			Mode::Live => {
+				self.dt = real_dt;
+				false
+			},
+			Mode::Record{ recording, current, .. } => {
+				let mut done = std::mem::take(current);
+				done.dt = self.dt;
+				done.keys = held();
+				recording.push(done);
+
+				self.dt = real_dt;
+				false
+			},
+			Mode::Replay{ recording, frame, delivered } => {
+				*frame += 1;
+				*delivered = false;
+
+				match recording.frames.get(*frame) {
+					Some(f) => {
+						self.dt = f.dt;
+						false
+					},
+					// An empty recording is over after its first frame
+					None => *frame == recording.frames.len().max(1),
+				}
+			},
+		}
+	}
+}
+
+impl Drop for Session {
+	// For recordings nobody called SDLCore::finish_recording() on. There's
+	// no one left to return an error to, so all it can do is print it.
+	fn drop(&mut self) {
+		if let Err(e) = self.finish() {
+			eprintln!("Couldn't save input recording: {}", e);
+		}
+	}
+}
+
+thread_local! {
+	static PENDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+// The next SDLCore built on this thread replays `recording`, whatever its
+// builder or the environment say. For playthrough tests, together with
+// golden::capture_demo.
+pub fn replay_next(recording: Recording) {
+	PENDING.with(|p| *p.borrow_mut() = Some(recording));
+}
+
+pub(crate) fn take_pending() -> Option<Recording> {
+	PENDING.with(|p| p.borrow_mut().take())
+}
+
+impl SDLCore {
+	// Use instead of event_pump.poll_iter() so sessions can be recorded and
+	// replayed. While replaying, real keyboard, mouse and controller events
+	// are dropped in favor of the recorded ones.
+	pub fn poll_events(&mut self) -> Vec<Event> {
+		self.start_profiled_frame();
+		self.begin_phase(Phase::Input);
+		let polled = self.event_pump.poll_iter().collect();
+		let events = self.session.events(polled);
+		self.end_phase(Phase::Input);
+		events
+	}
+
+	// Use instead of event_pump.keyboard_state().pressed_scancodes(), for the
+	// same reason as poll_events()
+	pub fn pressed_scancodes(&self) -> HashSet<Scancode> {
+		match self.session.replayed_keys() {
+			Some(keys) => keys,
+			None => self.event_pump.keyboard_state().pressed_scancodes().collect(),
+		}
+	}
+
+	// pressed_scancodes() as keycodes, which is what most demos check
+	pub fn pressed_keys(&self) -> HashSet<Keycode> {
+		self.pressed_scancodes()
+			.into_iter()
+			.filter_map(Keycode::from_scancode)
+			.collect()
+	}
+
+	// Seconds between the last two present()s: how long the previous frame
+	// took. Replays get the recorded times back, so time-based movement
+	// comes out the same too.
+	pub fn delta(&self) -> f32 {
+		self.session.dt
+	}
+
+	pub fn is_recording(&self) -> bool {
+		matches!(self.session.mode, Mode::Record{..})
+	}
+
+	// Saves the recording now rather than when the SDLCore is dropped, so
+	// a failed save can be reported. Input after this isn't recorded. Does
+	// nothing if not recording.
+	pub fn finish_recording(&mut self) -> Result<(), Error> {
+		self.session.finish()
+	}
+
+	pub fn is_replaying(&self) -> bool {
+		matches!(self.session.mode, Mode::Replay{..})
+	}
+
+	// Called by present() at the end of every frame. True once a replay has
+	// run out of frames.
+	pub(crate) fn next_replay_frame(&mut self) -> bool {
+		let now = Instant::now();
+		let real_dt = (now - self.session.last_present).as_secs_f32();
+		self.session.last_present = now;
+
+		let event_pump = &self.event_pump;
+		self.session.next_frame(real_dt, || event_pump.keyboard_state().pressed_scancodes().collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use sdl2::event::WindowEvent;
+	use sdl2::mouse::MouseButton;
+
+	#[test]
+	fn recordings_round_trip() {
+		let mut recording = Recording::new();
+		recording.push(RecordedFrame {
+			dt: 1.0 / 60.0,
+			keys: vec![Scancode::D, Scancode::LShift],
+			events: vec![
+				Event::KeyDown {
+					timestamp: 10,
+					window_id: 1,
+					keycode: Some(Keycode::D),
+					scancode: Some(Scancode::D),
+					keymod: Mod::LSHIFTMOD,
+					repeat: false,
+				},
+				Event::KeyUp {
+					timestamp: 11,
+					window_id: 1,
+					keycode: Some(Keycode::Space),
+					scancode: Some(Scancode::Space),
+					keymod: Mod::NOMOD,
+					repeat: true,
+				},
+				Event::TextInput{ timestamp: 12, window_id: 1, text: "d".to_string() },
+			],
+		});
+		recording.push(RecordedFrame {
+			dt: 0.0213,
+			keys: Vec::new(),
+			events: vec![
+				Event::MouseMotion {
+					timestamp: 20,
+					window_id: 1,
+					which: 0,
+					mousestate: MouseState::from_sdl_state(1),
+					x: 320,
+					y: 240,
+					xrel: -3,
+					yrel: 7,
+				},
+				Event::MouseButtonDown{ timestamp: 21, window_id: 1, which: 0, mouse_btn: MouseButton::Left, clicks: 2, x: 320, y: 240 },
+				Event::MouseButtonUp{ timestamp: 22, window_id: 1, which: 0, mouse_btn: MouseButton::X1, clicks: 1, x: 321, y: 241 },
+				Event::MouseWheel{ timestamp: 23, window_id: 1, which: 0, x: 0, y: -1, direction: MouseWheelDirection::Flipped },
+			],
+		});
+		recording.push(RecordedFrame {
+			dt: 0.5,
+			keys: Vec::new(),
+			events: vec![
+				Event::ControllerDeviceAdded{ timestamp: 30, which: 0 },
+				Event::ControllerButtonDown{ timestamp: 31, which: 0, button: Button::A },
+				Event::ControllerButtonUp{ timestamp: 32, which: 0, button: Button::DPadLeft },
+				Event::ControllerAxisMotion{ timestamp: 33, which: 0, axis: Axis::LeftX, value: -32768 },
+				Event::ControllerAxisMotion{ timestamp: 34, which: 0, axis: Axis::TriggerRight, value: 32767 },
+				Event::ControllerDeviceRemoved{ timestamp: 35, which: 0 },
+				Event::Quit{ timestamp: 36 },
+			],
+		});
+
+		let path = std::env::temp_dir().join(format!("sdl_rust_replay_{}.json", std::process::id()));
+		recording.save(&path).unwrap();
+		let loaded = Recording::load(&path);
+		let _ = fs::remove_file(&path);
+
+		assert_eq!(loaded.unwrap(), recording);
+	}
+
+	#[test]
+	fn unrecorded_events_are_left_out() {
+		let mut recording = Recording::new();
+		recording.push(RecordedFrame {
+			dt: 0.25,
+			keys: Vec::new(),
+			events: vec![
+				Event::AppLowMemory{ timestamp: 1 },
+				Event::ControllerDeviceAdded{ timestamp: 2, which: 0 },
+			],
+		});
+
+		let path = std::env::temp_dir().join(format!("sdl_rust_replay_skip_{}.json", std::process::id()));
+		recording.save(&path).unwrap();
+		let loaded = Recording::load(&path);
+		let _ = fs::remove_file(&path);
+
+		assert_eq!(loaded.unwrap().frames()[0].events, vec![Event::ControllerDeviceAdded{ timestamp: 2, which: 0 }]);
+	}
+
+	fn key(down: bool, s: Scancode, timestamp: u32) -> Event {
+		let (keycode, scancode, keymod, repeat) = (Keycode::from_scancode(s), Some(s), Mod::NOMOD, false);
+		if down {
+			Event::KeyDown{ timestamp, window_id: 1, keycode, scancode, keymod, repeat }
+		}
+		else {
+			Event::KeyUp{ timestamp, window_id: 1, keycode, scancode, keymod, repeat }
+		}
+	}
+
+	fn three_frames() -> Recording {
+		let mut recording = Recording::new();
+		recording.push(RecordedFrame {
+			dt: 0.0,
+			keys: vec![Scancode::D],
+			events: vec![key(true, Scancode::D, 1)],
+		});
+		recording.push(RecordedFrame {
+			dt: 0.25,
+			keys: vec![Scancode::D, Scancode::W],
+			events: vec![key(true, Scancode::W, 2)],
+		});
+		recording.push(RecordedFrame {
+			dt: 0.5,
+			keys: Vec::new(),
+			events: vec![key(false, Scancode::D, 3), key(false, Scancode::W, 4)],
+		});
+		recording
+	}
+
+	// What the event pump hands a replaying session each frame: a real key
+	// press that should be ignored, and a window event that shouldn't
+	fn live_events() -> Vec<Event> {
+		vec![
+			key(true, Scancode::Space, 100),
+			Event::Window{ timestamp: 101, window_id: 1, win_event: WindowEvent::Exposed },
+		]
+	}
+
+	#[test]
+	fn replays_frame_by_frame() {
+		let recording = three_frames();
+		let mut session = Session::replay(recording.clone());
+		let window = live_events().pop().unwrap();
+
+		for (i, f) in recording.frames().iter().enumerate() {
+			assert_eq!(session.dt, f.dt, "frame {}", i);
+			assert_eq!(session.replayed_keys(), Some(f.keys.iter().copied().collect()), "frame {}", i);
+
+			let mut expected = f.events.clone();
+			expected.push(window.clone());
+			assert_eq!(session.events(live_events()), expected, "frame {}", i);
+
+			// Polling again in the same frame doesn't repeat them
+			assert_eq!(session.events(live_events()), vec![window.clone()], "frame {}", i);
+
+			let last = i + 1 == recording.frames().len();
+			assert_eq!(session.next_frame(1.0, || panic!("replays don't read the keyboard")), last, "frame {}", i);
+		}
+	}
+
+	#[test]
+	fn reading_past_the_end() {
+		let mut session = Session::replay(three_frames());
+		for _ in 0..3 {
+			session.events(live_events());
+			session.next_frame(1.0, Vec::new);
+		}
+
+		// Nothing recorded left, but the window still works, time stands
+		// still at the last frame's, and the end is only reported once
+		for _ in 0..3 {
+			assert_eq!(session.events(live_events()), vec![live_events().pop().unwrap()]);
+			assert_eq!(session.replayed_keys(), Some(HashSet::new()));
+			assert_eq!(session.dt, 0.5);
+			assert!(!session.next_frame(1.0, Vec::new));
+		}
+	}
+
+	#[test]
+	fn empty_recording_ends_after_one_frame() {
+		let mut session = Session::replay(Recording::new());
+		assert_eq!(session.dt, 0.0);
+		assert_eq!(session.replayed_keys(), Some(HashSet::new()));
+		assert!(session.events(Vec::new()).is_empty());
+		assert!(session.next_frame(1.0, Vec::new));
+		assert!(!session.next_frame(1.0, Vec::new));
+	}
+
+	#[test]
+	fn records_what_replays_back() {
+		let path = std::env::temp_dir().join(format!("sdl_rust_replay_session_{}.json", std::process::id()));
+		let mut session = Session::record(path.clone());
+		assert_eq!(session.replayed_keys(), None);
+
+		// Events pass straight through while recording
+		let polled = vec![key(true, Scancode::D, 1)];
+		assert_eq!(session.events(polled.clone()), polled);
+		assert!(!session.next_frame(0.25, || vec![Scancode::D]));
+		assert_eq!(session.dt, 0.25);
+
+		session.events(vec![key(false, Scancode::D, 2)]);
+		assert!(!session.next_frame(0.5, Vec::new));
+
+		// A Quit after the last present() still makes it in
+		session.events(vec![Event::Quit{ timestamp: 3 }]);
+		let saved = session.finish();
+		let loaded = Recording::load(&path);
+		let _ = fs::remove_file(&path);
+		saved.unwrap();
+		let loaded = loaded.unwrap();
+
+		let dts: Vec<f32> = loaded.frames().iter().map(|f| f.dt).collect();
+		assert_eq!(dts, vec![0.0, 0.25, 0.5]);
+
+		let mut replay = Session::replay(loaded);
+		let expected = [
+			(vec![Scancode::D], vec![key(true, Scancode::D, 1)]),
+			(Vec::new(), vec![key(false, Scancode::D, 2)]),
+			(Vec::new(), vec![Event::Quit{ timestamp: 3 }]),
+		];
+		for (keys, events) in expected.iter() {
+			assert_eq!(replay.replayed_keys(), Some(keys.iter().copied().collect()));
+			assert_eq!(&replay.events(Vec::new()), events);
+			replay.next_frame(1.0, Vec::new);
+		}
+
+		// Nothing more is recorded after finishing
+		session.events(vec![key(true, Scancode::W, 4)]);
+		assert!(session.finish().is_ok());
+		assert!(!path.exists());
+	}
+}
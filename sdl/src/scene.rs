@@ -0,0 +1,186 @@
+use sdl2::event::Event;
+
+use crate::SDLCore;
+use crate::Error;
+use crate::game_loop::{GameLoop, Flow};
+
+// What a scene wants done to the stack after handling an event or update
+pub enum Transition<'a> {
+	None,
+	// Pause this scene and put another on top of it
+	Push(Box<dyn Scene<'a> + 'a>),
+	// Leave this scene, resuming the one below
+	Pop,
+	// Leave this scene for another, without resuming the one below
+	Replace(Box<dyn Scene<'a> + 'a>),
+	// Leave every scene
+	Quit,
+}
+
+// One screen of a game, e.g. a title screen, a level, or a pause menu.
+// Only the top scene of a SceneStack gets events and updates. 'a lets
+// scenes hold textures borrowed from a TextureCreator.
+pub trait Scene<'a> {
+	// Called when the scene is added to the stack
+	fn enter(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		Ok(())
+	}
+
+	// Called when the scene is removed from the stack
+	fn exit(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		Ok(())
+	}
+
+	// Called when another scene is pushed on top of this one
+	fn pause(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		Ok(())
+	}
+
+	// Called when this is the top scene again
+	fn resume(&mut self, _core: &mut SDLCore) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn handle_event(&mut self, _core: &mut SDLCore, _event: &Event) -> Result<Transition<'a>, Error> {
+		Ok(Transition::None)
+	}
+
+	fn update(&mut self, core: &mut SDLCore, dt: f32) -> Result<Transition<'a>, Error>;
+
+	fn render(&self, core: &mut SDLCore, alpha: f32) -> Result<(), Error>;
+
+	// Overlays (e.g., a pause menu) are drawn on top of the scene below
+	// them instead of hiding it
+	fn is_overlay(&self) -> bool {
+		false
+	}
+}
+
+pub struct SceneStack<'a> {
+	scenes: Vec<Box<dyn Scene<'a> + 'a>>,
+}
+
+impl<'a> SceneStack<'a> {
+	pub fn new() -> SceneStack<'a> {
+		SceneStack {
+			scenes: Vec::new(),
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.scenes.is_empty()
+	}
+
+	pub fn len(&self) -> usize {
+		self.scenes.len()
+	}
+
+	pub fn push(&mut self, core: &mut SDLCore, mut scene: Box<dyn Scene<'a> + 'a>) -> Result<(), Error> {
+		if let Some(top) = self.scenes.last_mut() {
+			top.pause(core)?;
+		}
+
+		scene.enter(core)?;
+		self.scenes.push(scene);
+
+		Ok(())
+	}
+
+	pub fn pop(&mut self, core: &mut SDLCore) -> Result<(), Error> {
+		if let Some(mut top) = self.scenes.pop() {
+			top.exit(core)?;
+		}
+
+		if let Some(top) = self.scenes.last_mut() {
+			top.resume(core)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn replace(&mut self, core: &mut SDLCore, mut scene: Box<dyn Scene<'a> + 'a>) -> Result<(), Error> {
+		if let Some(mut top) = self.scenes.pop() {
+			top.exit(core)?;
+		}
+
+		scene.enter(core)?;
+		self.scenes.push(scene);
+
+		Ok(())
+	}
+
+	pub fn clear(&mut self, core: &mut SDLCore) -> Result<(), Error> {
+		while let Some(mut top) = self.scenes.pop() {
+			top.exit(core)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn apply(&mut self, core: &mut SDLCore, transition: Transition<'a>) -> Result<(), Error> {
+		match transition {
+			Transition::None => Ok(()),
+			Transition::Push(scene) => self.push(core, scene),
+			Transition::Pop => self.pop(core),
+			Transition::Replace(scene) => self.replace(core, scene),
+			Transition::Quit => self.clear(core),
+		}
+	}
+
+	// Hands events and then an update to the top scene. Quits once the
+	// stack is empty.
+	pub fn update(&mut self, core: &mut SDLCore, events: &[Event], dt: f32) -> Result<Flow, Error> {
+		for event in events {
+			let transition = match self.scenes.last_mut() {
+				Some(top) => top.handle_event(core, event)?,
+				None => break,
+			};
+			self.apply(core, transition)?;
+		}
+
+		let transition = match self.scenes.last_mut() {
+			Some(top) => top.update(core, dt)?,
+			None => return Ok(Flow::Quit),
+		};
+		self.apply(core, transition)?;
+
+		if self.scenes.is_empty() {
+			Ok(Flow::Quit)
+		}
+		else {
+			Ok(Flow::Continue)
+		}
+	}
+
+	// Draws the top scene, plus every scene it overlays, bottom first
+	pub fn render(&self, core: &mut SDLCore, alpha: f32) -> Result<(), Error> {
+		let first_visible = self.scenes.iter()
+			.rposition(|s| !s.is_overlay())
+			.unwrap_or(0);
+
+		for scene in &self.scenes[first_visible..] {
+			scene.render(core, alpha)?;
+		}
+
+		Ok(())
+	}
+
+	// Drives the stack with a GameLoop until every scene has left
+	pub fn run(&mut self, core: &mut SDLCore, game_loop: &GameLoop) -> Result<(), Error> {
+		game_loop.run(
+			core,
+			self,
+			|core, stack, events, dt| stack.update(core, events, dt),
+			|core, stack, alpha| stack.render(core, alpha),
+		)?;
+
+		// Window may have been closed with scenes still on the stack
+		self.clear(core)
+	}
+}
+
+impl<'a> Default for SceneStack<'a> {
+	fn default() -> SceneStack<'a> {
+		SceneStack::new()
+	}
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::surface::Surface;
+use sdl2::ttf;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::video::WindowContext;
+
+use crate::Error;
+
+// Ships with the repo (see fonts/DejaVu-LICENSE), for when any font will do
+pub const DEFAULT_FONT: &str = "fonts/DejaVuSansMono.ttf";
+
+// How many rendered strings to keep before dropping the least recently drawn
+pub const DEFAULT_CACHE_SIZE: usize = 256;
+
+// SDL_ttf can only be initialized once per process (the launcher runs many
+// demos in one), so every Text shares this and it's never shut down
+static TTF: OnceLock<Result<Sdl2TtfContext, String>> = OnceLock::new();
+
+fn ttf_context() -> Result<&'static Sdl2TtfContext, Error> {
+	TTF.get_or_init(|| ttf::init().map_err(|e| e.to_string()))
+		.as_ref()
+		.map_err(|e| Error::font(e.clone()))
+}
+
+// A loaded font at one point size, from Text::load_font()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontHandle(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Align {
+	Left,
+	Center,
+	Right,
+}
+
+// How a string looks. Starts as plain left-aligned text in one color:
+//
+//     let style = TextStyle::new(Color::WHITE)
+//         .wrap(300)
+//         .align(Align::Center)
+//         .outline(2, Color::BLACK);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextStyle {
+	color: Color,
+	align: Align,
+	// Width to wrap lines at, in pixels
+	wrap: Option<u32>,
+	// Width in pixels and color
+	outline: Option<(u16, Color)>,
+	// Offset in pixels and color
+	shadow: Option<(i32, i32, Color)>,
+}
+
+impl TextStyle {
+	pub fn new(color: Color) -> TextStyle {
+		TextStyle {
+			color,
+			align: Align::Left,
+			wrap: None,
+			outline: None,
+			shadow: None,
+		}
+	}
+
+	// Lines line up against each other on this side. With wrap(), they're
+	// aligned within the wrap width, otherwise within the longest line.
+	pub fn align(mut self, align: Align) -> TextStyle {
+		self.align = align;
+		self
+	}
+
+	// Breaks lines between words so none is wider than `width`. A word
+	// that won't fit on a line by itself is broken wherever it has to be.
+	pub fn wrap(mut self, width: u32) -> TextStyle {
+		self.wrap = Some(width.max(1));
+		self
+	}
+
+	pub fn outline(mut self, width: u16, color: Color) -> TextStyle {
+		self.outline = if width > 0 { Some((width, color)) } else { None };
+		self
+	}
+
+	// A copy of the text in `color` behind it, offset by (dx, dy)
+	pub fn shadow(mut self, dx: i32, dy: i32, color: Color) -> TextStyle {
+		self.shadow = Some((dx, dy, color));
+		self
+	}
+
+	pub fn color(&self) -> Color {
+		self.color
+	}
+
+	fn outline_width(&self) -> u32 {
+		self.outline.map_or(0, |(w, _)| w as u32)
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+	font: FontHandle,
+	text: String,
+	style: TextStyle,
+}
+
+struct Cached<'a> {
+	texture: Texture<'a>,
+	last_used: u64,
+}
+
+// Where each line goes, relative to the text's top left
+struct Layout {
+	lines: Vec<(String, i32, i32)>,
+	width: u32,
+	height: u32,
+}
+
+// Same file, same key, however the path was spelled
+fn cache_key(path: &Path) -> PathBuf {
+	fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn text_width(font: &Font, s: &str) -> Result<u32, Error> {
+	if s.is_empty() {
+		return Ok(0);
+	}
+	font.size_of(s)
+		.map(|(w, _)| w)
+		.map_err(|e| Error::font(e.to_string()))
+}
+
+// Greedy word wrap of one line (no '\n's) to `width`
+fn wrap_line(font: &Font, line: &str, width: u32) -> Result<Vec<String>, Error> {
+	let mut lines = Vec::new();
+	let mut current = String::new();
+
+	for word in line.split_whitespace() {
+		let joined = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+		if text_width(font, &joined)? <= width {
+			current = joined;
+			continue;
+		}
+
+		if !current.is_empty() {
+			lines.push(std::mem::take(&mut current));
+		}
+
+		// Too long for a line of its own, so split it up
+		for c in word.chars() {
+			current.push(c);
+			if current.chars().count() > 1 && text_width(font, &current)? > width {
+				current.pop();
+				lines.push(std::mem::replace(&mut current, c.to_string()));
+			}
+		}
+	}
+
+	lines.push(current);
+	Ok(lines)
+}
+
+fn layout(font: &Font, text: &str, style: &TextStyle) -> Result<Layout, Error> {
+	let mut lines = Vec::new();
+	for line in text.lines() {
+		match style.wrap {
+			Some(width) => lines.extend(wrap_line(font, line, width)?),
+			None => lines.push(line.to_string()),
+		}
+	}
+
+	let widths = lines.iter()
+		.map(|l| text_width(font, l))
+		.collect::<Result<Vec<u32>, Error>>()?;
+	let longest = widths.iter().copied().max().unwrap_or(0);
+	let width = style.wrap.map_or(longest, |w| w.max(longest));
+
+	// Room around the glyphs for outlines and shadows
+	let pad = style.outline_width() as i32;
+	let (sx, sy) = style.shadow.map_or((0, 0), |(dx, dy, _)| (dx, dy));
+	let origin = (pad + (-sx).max(0), pad + (-sy).max(0));
+
+	let skip = font.recommended_line_spacing().max(font.height());
+	let height = if lines.is_empty() {
+		0
+	}
+	else {
+		skip * (lines.len() as i32 - 1) + font.height()
+	};
+
+	let placed = lines.into_iter()
+		.zip(widths)
+		.enumerate()
+		.map(|(i, (line, w))| {
+			let x = match style.align {
+				Align::Left => 0,
+				Align::Center => ((width - w) / 2) as i32,
+				Align::Right => (width - w) as i32,
+			};
+			(line, origin.0 + x, origin.1 + skip * (i as i32))
+		})
+		.collect();
+
+	Ok(Layout {
+		lines: placed,
+		width: width + 2 * pad as u32 + sx.unsigned_abs(),
+		height: height as u32 + 2 * pad as u32 + sy.unsigned_abs(),
+	})
+}
+
+// Draws strings with TrueType fonts. Each string is rendered once and kept
+// as a texture, so drawing the same text every frame is cheap. Text that
+// changes (scores, timers) is fine too; old renders just fall out of the
+// cache. Create one next to SDLCore, from its canvas's TextureCreator:
+//
+//     let texture_creator = self.core.wincan.texture_creator();
+//     let mut text = Text::new(&texture_creator)?;
+//     let font = text.load_font(text::DEFAULT_FONT, 24)?;
+//     text.draw(&mut self.core.wincan, font, "Hello", 10, 10, &TextStyle::new(Color::WHITE))?;
+pub struct Text<'a> {
+	texture_creator: &'a TextureCreator<WindowContext>,
+	fonts: Vec<Font<'static, 'static>>,
+	by_path: HashMap<(PathBuf, u16), FontHandle>,
+	cache: HashMap<CacheKey, Cached<'a>>,
+	cache_size: usize,
+	// Counts draws, to find the least recently used render
+	clock: u64,
+}
+
+impl<'a> Text<'a> {
+	pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Result<Text<'a>, Error> {
+		ttf_context()?;
+
+		Ok(Text {
+			texture_creator,
+			fonts: Vec::new(),
+			by_path: HashMap::new(),
+			cache: HashMap::new(),
+			cache_size: DEFAULT_CACHE_SIZE,
+			clock: 0,
+		})
+	}
+
+	// Loads the font at `path` at `size` points, or hands back the existing
+	// handle if it has already been loaded at that size
+	pub fn load_font<P: AsRef<Path>>(&mut self, path: P, size: u16) -> Result<FontHandle, Error> {
+		let path = path.as_ref();
+		let key = (cache_key(path), size);
+
+		if let Some(&handle) = self.by_path.get(&key) {
+			return Ok(handle);
+		}
+
+		let font = ttf_context()?.load_font(path, size)
+			.map_err(|e| Error::asset(path, e))?;
+
+		let handle = FontHandle(self.fonts.len());
+		self.fonts.push(font);
+		self.by_path.insert(key, handle);
+
+		Ok(handle)
+	}
+
+	fn font(&self, font: FontHandle) -> Result<&Font<'static, 'static>, Error> {
+		self.fonts.get(font.0)
+			.ok_or_else(|| Error::Other(format!("No font loaded for {:?}", font)))
+	}
+
+	// Pixels from one line of text to the next
+	pub fn line_height(&self, font: FontHandle) -> Result<u32, Error> {
+		let font = self.font(font)?;
+		Ok(font.recommended_line_spacing().max(font.height()) as u32)
+	}
+
+	// Width and height `text` would take up if drawn, outline and shadow
+	// included, without drawing it
+	pub fn measure(&self, font: FontHandle, text: &str, style: &TextStyle) -> Result<(u32, u32), Error> {
+		let layout = layout(self.font(font)?, text, style)?;
+		Ok((layout.width, layout.height))
+	}
+
+	// Draws `text` with its top left at (x, y), in screen coordinates, and
+	// returns the area it covered
+	pub fn draw(
+		&mut self,
+		wincan: &mut WindowCanvas,
+		font: FontHandle,
+		text: &str,
+		x: i32,
+		y: i32,
+		style: &TextStyle,
+	) -> Result<Rect, Error> {
+		let key = CacheKey {
+			font,
+			text: text.to_string(),
+			style: *style,
+		};
+
+		self.clock += 1;
+		if !self.cache.contains_key(&key) {
+			let texture = match self.render(font, text, style)? {
+				Some(t) => t,
+				// Nothing to see
+				None => return Ok(Rect::new(x, y, 0, 0)),
+			};
+
+			if self.cache.len() >= self.cache_size {
+				self.evict();
+			}
+			self.cache.insert(key.clone(), Cached { texture, last_used: 0 });
+		}
+
+		let cached = self.cache.get_mut(&key).expect("just cached");
+		cached.last_used = self.clock;
+
+		let query = cached.texture.query();
+		let dst = Rect::new(x, y, query.width, query.height);
+		wincan.copy(&cached.texture, None, dst)?;
+
+		Ok(dst)
+	}
+
+	fn evict(&mut self) {
+		let oldest = self.cache.iter()
+			.min_by_key(|(_, c)| c.last_used)
+			.map(|(k, _)| k.clone());
+
+		if let Some(k) = oldest {
+			self.cache.remove(&k);
+		}
+	}
+
+	// Renders every line into one texture: shadow, then outline, then the
+	// text itself on top. None if there's nothing to draw.
+	fn render(&mut self, font: FontHandle, text: &str, style: &TextStyle) -> Result<Option<Texture<'a>>, Error> {
+		let layout = layout(self.font(font)?, text, style)?;
+		if layout.width == 0 || layout.height == 0 || layout.lines.iter().all(|(l, _, _)| l.is_empty()) {
+			return Ok(None);
+		}
+
+		let outline = style.outline.map_or(0, |(w, _)| w as i32);
+		let mut layers = Vec::new();
+		if let Some((dx, dy, color)) = style.shadow {
+			layers.push((dx - outline, dy - outline, outline as u16, color));
+		}
+		if let Some((width, color)) = style.outline {
+			layers.push((-outline, -outline, width, color));
+		}
+		layers.push((0, 0, 0, style.color));
+
+		// Starting from transparent pixels of the first layer's color keeps
+		// antialiased edges from blending toward black
+		let mut target = Surface::new(layout.width, layout.height, PixelFormatEnum::ARGB8888)
+			.map_err(Error::font)?;
+		let first = layers[0].3;
+		target.fill_rect(None, Color::RGBA(first.r, first.g, first.b, 0))
+			.map_err(Error::font)?;
+
+		let font = self.fonts.get_mut(font.0).expect("checked by layout");
+		for &(dx, dy, width, color) in layers.iter() {
+			font.set_outline_width(width);
+
+			for (line, x, y) in layout.lines.iter() {
+				if line.is_empty() {
+					continue;
+				}
+
+				let mut surface = font.render(line).blended(color)
+					.map_err(|e| Error::font(e.to_string()))?;
+				surface.set_blend_mode(BlendMode::Blend).map_err(Error::font)?;
+
+				let dst = Rect::new(x + dx, y + dy, surface.width(), surface.height());
+				surface.blit(None, &mut target, dst).map_err(Error::font)?;
+			}
+		}
+		font.set_outline_width(0);
+
+		let mut texture = self.texture_creator.create_texture_from_surface(&target)
+			.map_err(|e| Error::font(e.to_string()))?;
+		texture.set_blend_mode(BlendMode::Blend);
+
+		Ok(Some(texture))
+	}
+
+	// How many rendered strings to keep at once
+	pub fn set_cache_size(&mut self, size: usize) {
+		self.cache_size = size.max(1);
+		while self.cache.len() > self.cache_size {
+			self.evict();
+		}
+	}
+
+	pub fn cached(&self) -> usize {
+		self.cache.len()
+	}
+
+	// Frees every rendered string; they're rendered again when next drawn
+	pub fn clear_cache(&mut self) {
+		self.cache.clear();
+	}
+}
@@ -0,0 +1,996 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use sdl2::rect::{Point, Rect};
+use serde_json::Value;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::SDLCore;
+use crate::Error;
+use crate::assets::{AssetManager, TextureHandle};
+
+// Tiled keeps a tile's flips in the top bits of its gid
+const FLIP_H: u32 = 0x8000_0000;
+const FLIP_V: u32 = 0x4000_0000;
+const FLIP_D: u32 = 0x2000_0000;
+const GID_MASK: u32 = !(FLIP_H | FLIP_V | FLIP_D);
+
+// A custom property set in Tiled, on the map, a layer, or a tile
+#[derive(Clone, Debug, PartialEq)]
+pub enum Property {
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	// Also colors, file paths and anything else Tiled stores as text
+	String(String),
+}
+
+impl Property {
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Property::Bool(b) => Some(*b),
+			_ => None,
+		}
+	}
+
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Property::Int(i) => Some(*i),
+			_ => None,
+		}
+	}
+
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Property::Float(f) => Some(*f),
+			Property::Int(i) => Some(*i as f64),
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Property::String(s) => Some(s),
+			_ => None,
+		}
+	}
+}
+
+pub type Properties = HashMap<String, Property>;
+
+// A sprite sheet the map's tiles are cut from
+#[derive(Debug)]
+pub struct Tileset {
+	name: String,
+	// gid of this sheet's first tile. Tile n of the sheet is first_gid + n.
+	first_gid: u32,
+	// The sprite sheet, relative to where cargo runs, like any other asset
+	image: PathBuf,
+	// None until the map's textures are loaded
+	texture: Option<TextureHandle>,
+	tile_width: u32,
+	tile_height: u32,
+	columns: u32,
+	tile_count: u32,
+	margin: u32,
+	spacing: u32,
+	// Only tiles that have properties, by their id within the sheet
+	tiles: HashMap<u32, Properties>,
+}
+
+impl Tileset {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn first_gid(&self) -> u32 {
+		self.first_gid
+	}
+
+	pub fn image(&self) -> &Path {
+		&self.image
+	}
+
+	// None for maps from Tilemap::parse() until load_textures()
+	pub fn texture(&self) -> Option<TextureHandle> {
+		self.texture
+	}
+
+	pub fn tile_count(&self) -> u32 {
+		self.tile_count
+	}
+
+	// Where tile `id` (within this sheet) is in the sheet
+	pub fn src(&self, id: u32) -> Rect {
+		let columns = self.columns.max(1);
+		Rect::new(
+			(self.margin + (id % columns) * (self.tile_width + self.spacing)) as i32,
+			(self.margin + (id / columns) * (self.tile_height + self.spacing)) as i32,
+			self.tile_width,
+			self.tile_height,
+		)
+	}
+
+	pub fn properties(&self, id: u32) -> Option<&Properties> {
+		self.tiles.get(&id)
+	}
+}
+
+// One grid of tiles. Tiled's object, image and group layers are skipped
+// (group layers' tile layers are kept, flattened in order).
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+	name: String,
+	width: u32,
+	height: u32,
+	// Row by row, 0 for no tile, flip bits still set
+	gids: Vec<u32>,
+	visible: bool,
+	offset: (i32, i32),
+	properties: Properties,
+}
+
+impl TileLayer {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn visible(&self) -> bool {
+		self.visible
+	}
+
+	pub fn set_visible(&mut self, visible: bool) {
+		self.visible = visible;
+	}
+
+	// Pixels the whole layer is shifted by
+	pub fn offset(&self) -> (i32, i32) {
+		self.offset
+	}
+
+	pub fn properties(&self) -> &Properties {
+		&self.properties
+	}
+
+	fn raw_gid(&self, x: i32, y: i32) -> Option<u32> {
+		if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+			return None;
+		}
+		Some(self.gids[(y as u32 * self.width + x as u32) as usize])
+	}
+
+	// gid of the tile at (x, y), in tiles, or None if there isn't one
+	pub fn gid(&self, x: i32, y: i32) -> Option<u32> {
+		self.raw_gid(x, y)
+			.map(|g| g & GID_MASK)
+			.filter(|&g| g != 0)
+	}
+}
+
+// A multi-layer map made in the Tiled editor (https://www.mapeditor.org),
+// saved as .tmx or .json
+pub struct Tilemap {
+	width: u32,
+	height: u32,
+	tile_width: u32,
+	tile_height: u32,
+	// Sorted by first_gid
+	tilesets: Vec<Tileset>,
+	layers: Vec<TileLayer>,
+	properties: Properties,
+}
+
+impl Tilemap {
+	// Loads a map and the sprite sheets of all its tilesets. Paths inside the
+	// map are relative to the map file, as Tiled saves them. Tile layers must
+	// use CSV (or, for JSON, array) data, not base64.
+	pub fn load<P: AsRef<Path>>(path: P, assets: &mut AssetManager) -> Result<Tilemap, Error> {
+		let mut map = Tilemap::parse(path)?;
+		map.load_textures(assets)?;
+		Ok(map)
+	}
+
+	// Reads a map and its tilesets without loading any images, so it needs
+	// no renderer. Call load_textures() before drawing it.
+	pub fn parse<P: AsRef<Path>>(path: P) -> Result<Tilemap, Error> {
+		let path = path.as_ref();
+
+		let mut map = match extension(path).as_str() {
+			"tmx" | "xml" => load_tmx(path)?,
+			_ => load_json(path)?,
+		};
+		map.tilesets.sort_by_key(|t| t.first_gid);
+
+		Ok(map)
+	}
+
+	// Loads every tileset's image (see Tileset::image())
+	pub fn load_textures(&mut self, assets: &mut AssetManager) -> Result<(), Error> {
+		for t in self.tilesets.iter_mut() {
+			t.texture = Some(assets.load_texture(&t.image)?);
+		}
+		Ok(())
+	}
+
+	// Size in tiles
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn tile_width(&self) -> u32 {
+		self.tile_width
+	}
+
+	pub fn tile_height(&self) -> u32 {
+		self.tile_height
+	}
+
+	// The whole map in world coordinates, e.g., for Camera::set_bounds()
+	pub fn bounds(&self) -> Rect {
+		Rect::new(0, 0, self.width * self.tile_width, self.height * self.tile_height)
+	}
+
+	pub fn properties(&self) -> &Properties {
+		&self.properties
+	}
+
+	pub fn tilesets(&self) -> &[Tileset] {
+		&self.tilesets
+	}
+
+	// Bottom layer first
+	pub fn layers(&self) -> &[TileLayer] {
+		&self.layers
+	}
+
+	pub fn layers_mut(&mut self) -> &mut [TileLayer] {
+		&mut self.layers
+	}
+
+	pub fn layer(&self, name: &str) -> Option<&TileLayer> {
+		self.layers.iter().find(|l| l.name == name)
+	}
+
+	// The tileset a gid comes from, and the tile's id within it
+	pub fn tileset_for(&self, gid: u32) -> Option<(&Tileset, u32)> {
+		let gid = gid & GID_MASK;
+		self.tilesets.iter()
+			.rev()
+			.find(|t| t.first_gid <= gid)
+			.map(|t| (t, gid - t.first_gid))
+			.filter(|(t, id)| t.tile_count == 0 || *id < t.tile_count)
+	}
+
+	pub fn tile_properties(&self, gid: u32) -> Option<&Properties> {
+		self.tileset_for(gid)
+			.and_then(|(t, id)| t.properties(id))
+	}
+
+	// Looks for `name` on the tiles at (x, y), in tiles, from the top layer
+	// down
+	pub fn property_at(&self, x: i32, y: i32, name: &str) -> Option<&Property> {
+		self.layers.iter()
+			.rev()
+			.filter_map(|l| l.gid(x, y))
+			.filter_map(|g| self.tile_properties(g))
+			.find_map(|p| p.get(name))
+	}
+
+	// Whether any tile at (x, y), in tiles, has its "solid" property set
+	pub fn is_solid(&self, x: i32, y: i32) -> bool {
+		self.layers.iter()
+			.filter_map(|l| l.gid(x, y))
+			.filter_map(|g| self.tile_properties(g))
+			.any(|p| p.get("solid").and_then(Property::as_bool) == Some(true))
+	}
+
+	// The tile a world position is in (possibly outside the map)
+	pub fn world_to_tile(&self, p: Point) -> (i32, i32) {
+		(
+			p.x().div_euclid(self.tile_width as i32),
+			p.y().div_euclid(self.tile_height as i32),
+		)
+	}
+
+	// Where the tile at (x, y) is in the world
+	pub fn tile_rect(&self, x: i32, y: i32) -> Rect {
+		Rect::new(
+			x * self.tile_width as i32,
+			y * self.tile_height as i32,
+			self.tile_width,
+			self.tile_height,
+		)
+	}
+
+	// Range of tiles (inclusive) that `area`, in world coordinates, touches
+	fn tile_range(&self, area: Rect) -> (i32, i32, i32, i32) {
+		let (x0, y0) = self.world_to_tile(area.top_left());
+		let (x1, y1) = self.world_to_tile(Point::new(area.right() - 1, area.bottom() - 1));
+		(
+			x0.max(0),
+			y0.max(0),
+			x1.min(self.width as i32 - 1),
+			y1.min(self.height as i32 - 1),
+		)
+	}
+
+	// World rects of every solid tile touching `area`, for collision
+	pub fn solid_rects(&self, area: Rect) -> Vec<Rect> {
+		let (x0, y0, x1, y1) = self.tile_range(area);
+
+		let mut rects = Vec::new();
+		for y in y0..=y1 {
+			for x in x0..=x1 {
+				if self.is_solid(x, y) {
+					rects.push(self.tile_rect(x, y));
+				}
+			}
+		}
+		rects
+	}
+
+	// Draws every visible layer, bottom first
+	pub fn draw(&self, core: &mut SDLCore, assets: &AssetManager) -> Result<(), Error> {
+		for i in 0..self.layers.len() {
+			if self.layers[i].visible {
+				self.draw_layer(core, assets, i)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	// Draws one layer (visible or not), only the tiles the camera can see
+	pub fn draw_layer(&self, core: &mut SDLCore, assets: &AssetManager, index: usize) -> Result<(), Error> {
+		let layer = match self.layers.get(index) {
+			Some(l) => l,
+			None => return Ok(()),
+		};
+
+		// Tiles bigger than the grid hang up and to the right of their cell,
+		// so look a little past the view for any that reach into it
+		let (extra_w, extra_h) = self.tilesets.iter()
+			.fold((0, 0), |(w, h), t| (w.max(t.tile_width), h.max(t.tile_height)));
+		let extra_x = extra_w.saturating_sub(self.tile_width).div_ceil(self.tile_width);
+		let extra_y = extra_h.saturating_sub(self.tile_height).div_ceil(self.tile_height);
+
+		let view = core.cam.view();
+		let view = Rect::new(
+			view.x() - layer.offset.0 - (extra_x * self.tile_width) as i32,
+			view.y() - layer.offset.1,
+			view.width() + extra_x * self.tile_width,
+			view.height() + extra_y * self.tile_height,
+		);
+		let (x0, y0, x1, y1) = self.tile_range(view);
+
+		for y in y0..=y1 {
+			for x in x0..=x1 {
+				let raw = match layer.raw_gid(x, y) {
+					Some(g) if g & GID_MASK != 0 => g,
+					_ => continue,
+				};
+				let (tileset, id) = match self.tileset_for(raw) {
+					Some(t) => t,
+					None => continue,
+				};
+				let texture = match tileset.texture.and_then(|h| assets.texture(h)) {
+					Some(t) => t,
+					None => continue,
+				};
+
+				// Anchored to the bottom-left of the cell, like Tiled does
+				let cell = self.tile_rect(x, y);
+				let dst = Rect::new(
+					cell.x() + layer.offset.0,
+					cell.bottom() - tileset.tile_height as i32 + layer.offset.1,
+					tileset.tile_width,
+					tileset.tile_height,
+				);
+
+				let (angle, flip_h, flip_v) = orientation(raw);
+				core.copy_world_rotated(texture, tileset.src(id), dst, angle, flip_h, flip_v)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+// How to draw a tile with its gid's flip bits, as (angle, flip_h, flip_v)
+// for SDL, which flips before rotating. Tiled's diagonal flip swaps x and y,
+// which is a vertical flip then a quarter turn clockwise; H and V come after
+// it, so once turned they act on the other axis. Turning is about the
+// tile's middle, which is right for square tiles.
+fn orientation(raw: u32) -> (f64, bool, bool) {
+	let (h, v) = (raw & FLIP_H != 0, raw & FLIP_V != 0);
+	if raw & FLIP_D != 0 {
+		(90.0, v, !h)
+	}
+	else {
+		(0.0, h, v)
+	}
+}
+
+fn extension(path: &Path) -> String {
+	path.extension()
+		.and_then(|e| e.to_str())
+		.unwrap_or("")
+		.to_ascii_lowercase()
+}
+
+// Paths in a map or tileset are relative to the file they're in
+fn relative_to(file: &Path, path: &str) -> PathBuf {
+	file.parent()
+		.unwrap_or_else(|| Path::new(""))
+		.join(path)
+}
+
+fn property(kind: &str, value: &str) -> Property {
+	match kind {
+		"bool" => Property::Bool(value == "true"),
+		"int" | "object" => value.parse().map(Property::Int)
+			.unwrap_or_else(|_| Property::String(value.to_string())),
+		"float" => value.parse().map(Property::Float)
+			.unwrap_or_else(|_| Property::String(value.to_string())),
+		_ => Property::String(value.to_string()),
+	}
+}
+
+fn parse_csv(path: &Path, text: &str) -> Result<Vec<u32>, Error> {
+	text.split(',')
+		.map(str::trim)
+		.filter(|s| !s.is_empty())
+		.map(|s| s.parse::<u32>().map_err(|e| Error::asset(path, format!("bad tile \"{}\": {}", s, e))))
+		.collect()
+}
+
+// Tiled's JSON format (.json, .tmj, .tsj)
+
+fn read_json(path: &Path) -> Result<Value, Error> {
+	let file = File::open(path).map_err(|e| Error::asset(path, e))?;
+	serde_json::from_reader(BufReader::new(file)).map_err(|e| Error::asset(path, e))
+}
+
+fn json_u32(v: &Value, key: &str) -> u32 {
+	v.get(key).and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+fn json_i32(v: &Value, key: &str) -> i32 {
+	v.get(key).and_then(Value::as_f64).unwrap_or(0.0).round() as i32
+}
+
+fn json_str<'v>(v: &'v Value, key: &str) -> &'v str {
+	v.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+fn json_properties(v: &Value) -> Properties {
+	let mut props = Properties::new();
+	if let Some(list) = v.get("properties").and_then(Value::as_array) {
+		for p in list {
+			let value = match p.get("value") {
+				Some(Value::Bool(b)) => Property::Bool(*b),
+				Some(Value::Number(n)) if n.is_i64() => Property::Int(n.as_i64().unwrap_or(0)),
+				Some(Value::Number(n)) => Property::Float(n.as_f64().unwrap_or(0.0)),
+				Some(Value::String(s)) => property(json_str(p, "type"), s),
+				Some(other) => Property::String(other.to_string()),
+				None => continue,
+			};
+			props.insert(json_str(p, "name").to_string(), value);
+		}
+	}
+	props
+}
+
+fn load_json(path: &Path) -> Result<Tilemap, Error> {
+	let v = read_json(path)?;
+
+	if v.get("infinite").and_then(Value::as_bool) == Some(true) {
+		return Err(Error::asset(path, "infinite maps aren't supported"));
+	}
+
+	let mut tilesets = Vec::new();
+	for t in v.get("tilesets").and_then(Value::as_array).into_iter().flatten() {
+		let first_gid = json_u32(t, "firstgid");
+		let tileset = match t.get("source").and_then(Value::as_str) {
+			Some(source) => load_external_tileset(&relative_to(path, source), first_gid)?,
+			None => json_tileset(path, t, first_gid)?,
+		};
+		tilesets.push(tileset);
+	}
+
+	let mut layers = Vec::new();
+	json_layers(path, &v, (0, 0), true, &mut layers)?;
+
+	Ok(Tilemap {
+		width: json_u32(&v, "width"),
+		height: json_u32(&v, "height"),
+		tile_width: json_u32(&v, "tilewidth").max(1),
+		tile_height: json_u32(&v, "tileheight").max(1),
+		tilesets,
+		layers,
+		properties: json_properties(&v),
+	})
+}
+
+fn json_tileset(path: &Path, t: &Value, first_gid: u32) -> Result<Tileset, Error> {
+	let image = t.get("image").and_then(Value::as_str)
+		.ok_or_else(|| Error::asset(path, "tilesets made of separate images aren't supported"))?;
+
+	let mut tiles = HashMap::new();
+	for tile in t.get("tiles").and_then(Value::as_array).into_iter().flatten() {
+		let props = json_properties(tile);
+		if !props.is_empty() {
+			tiles.insert(json_u32(tile, "id"), props);
+		}
+	}
+
+	Ok(Tileset {
+		name: json_str(t, "name").to_string(),
+		first_gid,
+		image: relative_to(path, image),
+		texture: None,
+		tile_width: json_u32(t, "tilewidth"),
+		tile_height: json_u32(t, "tileheight"),
+		columns: json_u32(t, "columns"),
+		tile_count: json_u32(t, "tilecount"),
+		margin: json_u32(t, "margin"),
+		spacing: json_u32(t, "spacing"),
+		tiles,
+	})
+}
+
+fn json_layers(
+	path: &Path,
+	parent: &Value,
+	offset: (i32, i32),
+	visible: bool,
+	layers: &mut Vec<TileLayer>,
+) -> Result<(), Error>
+{
+	for l in parent.get("layers").and_then(Value::as_array).into_iter().flatten() {
+		let offset = (offset.0 + json_i32(l, "offsetx"), offset.1 + json_i32(l, "offsety"));
+		let visible = visible && l.get("visible").and_then(Value::as_bool).unwrap_or(true);
+
+		match json_str(l, "type") {
+			"tilelayer" => {},
+			"group" => {
+				json_layers(path, l, offset, visible, layers)?;
+				continue;
+			},
+			_ => continue,
+		}
+
+		let gids = match l.get("data") {
+			Some(Value::Array(data)) => data.iter()
+				.map(|g| g.as_u64().unwrap_or(0) as u32)
+				.collect(),
+			_ => return Err(Error::asset(path, format!(
+				"layer \"{}\" isn't stored as an array; save it with CSV layer format",
+				json_str(l, "name"),
+			))),
+		};
+
+		layers.push(tile_layer(
+			path,
+			json_str(l, "name").to_string(),
+			json_u32(l, "width"),
+			json_u32(l, "height"),
+			gids,
+			visible,
+			offset,
+			json_properties(l),
+		)?);
+	}
+
+	Ok(())
+}
+
+fn load_external_tileset(path: &Path, first_gid: u32) -> Result<Tileset, Error> {
+	match extension(path).as_str() {
+		"tsx" | "xml" => {
+			let root = read_xml(path)?;
+			tmx_tileset(path, &root, first_gid)
+		},
+		_ => {
+			let v = read_json(path)?;
+			json_tileset(path, &v, first_gid)
+		},
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tile_layer(
+	path: &Path,
+	name: String,
+	width: u32,
+	height: u32,
+	gids: Vec<u32>,
+	visible: bool,
+	offset: (i32, i32),
+	properties: Properties,
+) -> Result<TileLayer, Error>
+{
+	if gids.len() != (width * height) as usize {
+		return Err(Error::asset(path, format!(
+			"layer \"{}\" has {} tiles, expected {}x{}",
+			name, gids.len(), width, height,
+		)));
+	}
+
+	Ok(TileLayer {
+		name,
+		width,
+		height,
+		gids,
+		visible,
+		offset,
+		properties,
+	})
+}
+
+// Tiled's XML format (.tmx, .tsx)
+
+// Just enough of an XML tree to walk a .tmx or .tsx
+struct Element {
+	name: String,
+	attributes: HashMap<String, String>,
+	children: Vec<Element>,
+	text: String,
+}
+
+impl Element {
+	fn attr(&self, key: &str) -> &str {
+		self.attributes.get(key).map(String::as_str).unwrap_or("")
+	}
+
+	fn attr_u32(&self, key: &str) -> u32 {
+		self.attr(key).parse().unwrap_or(0)
+	}
+
+	fn attr_i32(&self, key: &str) -> i32 {
+		self.attr(key).parse::<f64>().unwrap_or(0.0).round() as i32
+	}
+
+	fn child(&self, name: &str) -> Option<&Element> {
+		self.children.iter().find(|c| c.name == name)
+	}
+
+	fn children<'e>(&'e self, name: &'e str) -> impl Iterator<Item = &'e Element> + 'e {
+		self.children.iter().filter(move |c| c.name == name)
+	}
+}
+
+fn read_xml(path: &Path) -> Result<Element, Error> {
+	let file = File::open(path).map_err(|e| Error::asset(path, e))?;
+
+	let mut stack: Vec<Element> = Vec::new();
+	for event in EventReader::new(BufReader::new(file)) {
+		match event.map_err(|e| Error::asset(path, e))? {
+			XmlEvent::StartElement{ name, attributes, .. } => {
+				stack.push(Element {
+					name: name.local_name,
+					attributes: attributes.into_iter()
+						.map(|a| (a.name.local_name, a.value))
+						.collect(),
+					children: Vec::new(),
+					text: String::new(),
+				});
+			},
+			XmlEvent::EndElement{ .. } => {
+				let done = stack.pop()
+					.ok_or_else(|| Error::asset(path, "unbalanced XML"))?;
+				match stack.last_mut() {
+					Some(parent) => parent.children.push(done),
+					None => return Ok(done),
+				}
+			},
+			XmlEvent::Characters(s) | XmlEvent::CData(s) => {
+				if let Some(e) = stack.last_mut() {
+					e.text.push_str(&s);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	Err(Error::asset(path, "no root element"))
+}
+
+fn tmx_properties(e: &Element) -> Properties {
+	let mut props = Properties::new();
+	if let Some(list) = e.child("properties") {
+		for p in list.children("property") {
+			// Multi-line strings are kept as text rather than an attribute
+			let value = match p.attributes.get("value") {
+				Some(v) => v.as_str(),
+				None => p.text.as_str(),
+			};
+			props.insert(p.attr("name").to_string(), property(p.attr("type"), value));
+		}
+	}
+	props
+}
+
+fn load_tmx(path: &Path) -> Result<Tilemap, Error> {
+	let root = read_xml(path)?;
+
+	if root.name != "map" {
+		return Err(Error::asset(path, format!("expected <map>, found <{}>", root.name)));
+	}
+	if root.attr("infinite") == "1" {
+		return Err(Error::asset(path, "infinite maps aren't supported"));
+	}
+
+	let mut tilesets = Vec::new();
+	for t in root.children("tileset") {
+		let first_gid = t.attr_u32("firstgid");
+		let tileset = match t.attributes.get("source") {
+			Some(source) => load_external_tileset(&relative_to(path, source), first_gid)?,
+			None => tmx_tileset(path, t, first_gid)?,
+		};
+		tilesets.push(tileset);
+	}
+
+	let mut layers = Vec::new();
+	tmx_layers(path, &root, (0, 0), true, &mut layers)?;
+
+	Ok(Tilemap {
+		width: root.attr_u32("width"),
+		height: root.attr_u32("height"),
+		tile_width: root.attr_u32("tilewidth").max(1),
+		tile_height: root.attr_u32("tileheight").max(1),
+		tilesets,
+		layers,
+		properties: tmx_properties(&root),
+	})
+}
+
+fn tmx_tileset(path: &Path, t: &Element, first_gid: u32) -> Result<Tileset, Error> {
+	let image = t.child("image")
+		.map(|i| i.attr("source"))
+		.ok_or_else(|| Error::asset(path, "tilesets made of separate images aren't supported"))?;
+
+	let mut tiles = HashMap::new();
+	for tile in t.children("tile") {
+		let props = tmx_properties(tile);
+		if !props.is_empty() {
+			tiles.insert(tile.attr_u32("id"), props);
+		}
+	}
+
+	Ok(Tileset {
+		name: t.attr("name").to_string(),
+		first_gid,
+		image: relative_to(path, image),
+		texture: None,
+		tile_width: t.attr_u32("tilewidth"),
+		tile_height: t.attr_u32("tileheight"),
+		columns: t.attr_u32("columns"),
+		tile_count: t.attr_u32("tilecount"),
+		margin: t.attr_u32("margin"),
+		spacing: t.attr_u32("spacing"),
+		tiles,
+	})
+}
+
+fn tmx_layers(
+	path: &Path,
+	parent: &Element,
+	offset: (i32, i32),
+	visible: bool,
+	layers: &mut Vec<TileLayer>,
+) -> Result<(), Error>
+{
+	for l in &parent.children {
+		let offset = (offset.0 + l.attr_i32("offsetx"), offset.1 + l.attr_i32("offsety"));
+		let visible = visible && l.attr("visible") != "0";
+
+		match l.name.as_str() {
+			"layer" => {},
+			"group" => {
+				tmx_layers(path, l, offset, visible, layers)?;
+				continue;
+			},
+			_ => continue,
+		}
+
+		let data = l.child("data")
+			.ok_or_else(|| Error::asset(path, format!("layer \"{}\" has no data", l.attr("name"))))?;
+
+		let gids = match data.attr("encoding") {
+			"csv" => parse_csv(path, &data.text)?,
+			"" => data.children("tile")
+				.map(|t| t.attr_u32("gid"))
+				.collect(),
+			other => return Err(Error::asset(path, format!(
+				"layer \"{}\" is {}-encoded; save it with CSV layer format",
+				l.attr("name"), other,
+			))),
+		};
+
+		layers.push(tile_layer(
+			path,
+			l.attr("name").to_string(),
+			l.attr_u32("width"),
+			l.attr_u32("height"),
+			gids,
+			visible,
+			offset,
+			tmx_properties(l),
+		)?);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::fs;
+
+	// Written fresh each time, and named for the test so they don't clash
+	fn temp_map(name: &str, text: &str) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("sdl_rust_{}_{}", std::process::id(), name));
+		fs::write(&path, text).unwrap();
+		path
+	}
+
+	fn parse_temp(name: &str, text: &str) -> Result<Tilemap, Error> {
+		let path = temp_map(name, text);
+		let map = Tilemap::parse(&path);
+		let _ = fs::remove_file(&path);
+		map
+	}
+
+	#[test]
+	fn parses_tmx_with_external_tileset() {
+		let map = Tilemap::parse("maps/level.tmx").unwrap();
+		assert_eq!((map.width(), map.height()), (20, 12));
+		assert_eq!((map.tile_width(), map.tile_height()), (100, 100));
+		assert_eq!(map.properties().get("spawn_x"), Some(&Property::Int(1)));
+
+		let names: Vec<_> = map.layers().iter().map(TileLayer::name).collect();
+		assert_eq!(names, vec!["decor", "walls"]);
+
+		let bricks = &map.tilesets()[0];
+		assert_eq!((bricks.name(), bricks.first_gid(), bricks.tile_count()), ("bricks", 1, 4));
+		assert_eq!(bricks.image(), Path::new("maps/../images/bricks.png"));
+		assert_eq!(bricks.texture(), None);
+		assert_eq!(bricks.src(2), Rect::new(200, 0, 100, 100));
+
+		// Tiles 0 and 1 are solid, 2 and 3 are only decoration
+		assert!(map.is_solid(0, 0));
+		assert!(map.is_solid(3, 4));
+		assert!(!map.is_solid(1, 1));
+		assert!(!map.is_solid(9, 1));
+		assert_eq!(map.layer("decor").unwrap().gid(9, 1), Some(4));
+		assert!(!map.is_solid(-1, 0));
+		assert!(!map.is_solid(20, 0));
+
+		let solid = map.solid_rects(Rect::new(0, 0, 200, 200));
+		assert_eq!(solid, vec![Rect::new(0, 0, 100, 100), Rect::new(100, 0, 100, 100), Rect::new(0, 100, 100, 100)]);
+	}
+
+	#[test]
+	fn parses_json() {
+		let map = Tilemap::parse("maps/side_scroll.json").unwrap();
+		assert_eq!((map.width(), map.height()), (20, 5));
+
+		let layer = map.layer("bricks").unwrap();
+		assert_eq!(layer.offset(), (0, -20));
+		assert_eq!(layer.gid(0, 3), None);
+		assert_eq!(layer.gid(2, 4), Some(3));
+
+		let (tileset, id) = map.tileset_for(3).unwrap();
+		assert_eq!((tileset.name(), id), ("bricks", 2));
+		assert!(map.is_solid(4, 4));
+		assert!(!map.is_solid(2, 4));
+	}
+
+	#[test]
+	fn gids_find_their_tileset_without_flip_bits() {
+		let map = parse_temp("tilesets.json", &format!(r#"{{
+			"width": 4, "height": 1, "tilewidth": 16, "tileheight": 16,
+			"tilesets": [
+				{{"firstgid": 5, "name": "b", "image": "b.png", "tilewidth": 16, "tileheight": 16,
+					"columns": 2, "tilecount": 2,
+					"tiles": [{{"id": 1, "properties": [{{"name": "solid", "type": "bool", "value": true}}]}}]}},
+				{{"firstgid": 1, "name": "a", "image": "a.png", "tilewidth": 16, "tileheight": 16,
+					"columns": 4, "tilecount": 4}}
+			],
+			"layers": [
+				{{"type": "tilelayer", "name": "ground", "width": 4, "height": 1,
+					"data": [1, {}, {}, 7]}}
+			]
+		}}"#, 6 | FLIP_H, 5 | FLIP_D | FLIP_V)).unwrap();
+
+		// Sorted by first gid, whatever order the file had
+		let names: Vec<_> = map.tilesets().iter().map(Tileset::name).collect();
+		assert_eq!(names, vec!["a", "b"]);
+
+		let lookup = |gid| map.tileset_for(gid).map(|(t, id)| (t.name().to_string(), id));
+		assert_eq!(lookup(1), Some(("a".to_string(), 0)));
+		assert_eq!(lookup(4), Some(("a".to_string(), 3)));
+		assert_eq!(lookup(5), Some(("b".to_string(), 0)));
+		assert_eq!(lookup(6 | FLIP_H | FLIP_V | FLIP_D), Some(("b".to_string(), 1)));
+		// Past the end of the last sheet
+		assert_eq!(lookup(7), None);
+
+		let ground = map.layer("ground").unwrap();
+		assert_eq!(ground.gid(1, 0), Some(6));
+		assert_eq!(ground.gid(2, 0), Some(5));
+		assert!(map.is_solid(1, 0));
+		assert!(!map.is_solid(2, 0));
+		assert!(!map.is_solid(3, 0));
+	}
+
+	#[test]
+	fn flip_bits_turn_into_sdl_flips() {
+		assert_eq!(orientation(1), (0.0, false, false));
+		assert_eq!(orientation(1 | FLIP_H), (0.0, true, false));
+		assert_eq!(orientation(1 | FLIP_V), (0.0, false, true));
+		assert_eq!(orientation(1 | FLIP_H | FLIP_V), (0.0, true, true));
+
+		// Tiled's rotate buttons: clockwise is D + H, counterclockwise D + V
+		assert_eq!(orientation(1 | FLIP_D), (90.0, false, true));
+		assert_eq!(orientation(1 | FLIP_D | FLIP_H), (90.0, false, false));
+		assert_eq!(orientation(1 | FLIP_D | FLIP_V), (90.0, true, true));
+		assert_eq!(orientation(1 | FLIP_D | FLIP_H | FLIP_V), (90.0, true, false));
+	}
+
+	fn tmx(data: &str) -> String {
+		format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<map width="3" height="1" tilewidth="16" tileheight="16">
+ <tileset firstgid="1" name="a" tilewidth="16" tileheight="16" tilecount="4" columns="4">
+  <image source="a.png"/>
+ </tileset>
+ <layer name="ground" width="3" height="1">
+  {}
+ </layer>
+</map>"#, data)
+	}
+
+	#[test]
+	fn bad_maps_are_errors() {
+		assert!(parse_temp("ok.tmx", &tmx(r#"<data encoding="csv">1,2,3</data>"#)).is_ok());
+
+		let bad = [
+			("csv.tmx", tmx(r#"<data encoding="csv">1,x,3</data>"#)),
+			("negative.tmx", tmx(r#"<data encoding="csv">1,-2,3</data>"#)),
+			("short.tmx", tmx(r#"<data encoding="csv">1,2</data>"#)),
+			("base64.tmx", tmx(r#"<data encoding="base64">AQAAAAIAAAADAAAA</data>"#)),
+			("nodata.tmx", tmx("")),
+			("unclosed.tmx", "<map><layer>".to_string()),
+			("notamap.tmx", "<tileset/>".to_string()),
+			("base64.json", r#"{"width": 1, "height": 1, "layers": [
+				{"type": "tilelayer", "name": "ground", "width": 1, "height": 1, "data": "AQAAAA=="}
+			]}"#.to_string()),
+			("infinite.json", r#"{"infinite": true}"#.to_string()),
+			("broken.json", "{\"width\": ".to_string()),
+		];
+		for (name, text) in bad.iter() {
+			match parse_temp(name, text) {
+				Err(Error::Asset{..}) => {},
+				Err(e) => panic!("{}: wrong kind of error: {}", name, e),
+				Ok(_) => panic!("{} parsed", name),
+			}
+		}
+
+		assert!(Tilemap::parse("maps/no_such_map.tmx").is_err());
+	}
+}
@@ -0,0 +1,64 @@
+extern crate sdl_rust;
+
+use std::error::Error as StdError;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use sdl_rust::Demo;
+use sdl_rust::golden;
+use sdl_rust::golden::Tolerance;
+
+// The demos are examples, so pull them in the same way the launcher does
+#[path = "../examples/sdl11_overengineered_tiling.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl11_overengineered_tiling;
+#[path = "../examples/sdl13_side_scroll.rs"]
+#[allow(warnings, clippy::all)]
+mod sdl13_side_scroll;
+
+const GOLDEN_DIR: &str = "golden";
+const FRAMES: [u32; 3] = [0, 30, 60];
+
+// SDL allows only one event pump at a time, so these take turns
+static SDL: Mutex<()> = Mutex::new(());
+
+fn sdl() -> MutexGuard<'static, ()> {
+	// A failed test still leaves SDL free for the next one
+	SDL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Panics with the error and its causes, e.g. which frames differ or that a
+// golden image hasn't been made yet, rather than the Debug of the Error
+fn fail(e: sdl_rust::Error) {
+	let mut msg = e.to_string();
+	let mut source = e.source();
+	while let Some(s) = source {
+		msg += &format!("\ncaused by: {}", s);
+		source = s.source();
+	}
+	panic!("{}", msg);
+}
+
+#[test]
+fn sdl11_overengineered_tiling() {
+	let _sdl = sdl();
+	golden::check_demo(
+		Path::new(GOLDEN_DIR),
+		"sdl11",
+		sdl11_overengineered_tiling::SDL11::init,
+		&FRAMES,
+		Tolerance::default(),
+	).unwrap_or_else(fail);
+}
+
+#[test]
+fn sdl13_side_scroll() {
+	let _sdl = sdl();
+	golden::check_demo(
+		Path::new(GOLDEN_DIR),
+		"sdl13",
+		sdl13_side_scroll::SDL13::init,
+		&FRAMES,
+		Tolerance::default(),
+	).unwrap_or_else(fail);
+}